@@ -23,10 +23,11 @@ use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use async_trait::async_trait;
-use std::str::FromStr;
 use zenoh::{key_expr::KeyExpr, Config};
-use up_rust::{LocalUriProvider, StaticUriProvider, UMessageBuilder, UPayloadFormat, UTransport,UListener, UMessage, UUri};
+use up_rust::{LocalUriProvider, StaticUriProvider, UMessageBuilder, UPayloadFormat, UTransport,UListener, UMessage};
 use up_transport_zenoh::UPTransportZenoh;
+use sdv_lab_topics as topics;
+use cruise_control_messages::{ActuationCommand, CruiseControlPayload, EngageStatus};
 
 // General constants
 const CLIENT_TIME_MS: u64 = 5_000;
@@ -42,8 +43,8 @@ const MAX_STEERING: f32 = 1.0;
 const MAX_BRAKING:  f32 = 1.0;
 
 // uProtocol resource IDs
-const RESOURCE_VELOCITY_STATUS: u16 = 0x8001;
-const RESOURCE_CLOCK_STATUS: u16 = 0x8002;
+const RESOURCE_VELOCITY_STATUS: u16 = topics::VELOCITY_STATUS.resource_id;
+const RESOURCE_CLOCK_STATUS: u16 = topics::CLOCK_STATUS.resource_id;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -58,6 +59,9 @@ struct Args {
     delta: f64,
     #[clap(long, default_value = None)]
     router: Option<String>,
+    /// Vehicle id of a fleet (e.g. the role name): appended to the uProtocol authorities
+    #[clap(long)]
+    vehicle_id: Option<String>,
 }
 
 // Helper function to create a Zenoh configuration
@@ -83,17 +87,21 @@ struct ActuationListener {
 #[async_trait]
 impl UListener for ActuationListener {
     async fn on_receive(&self, msg: UMessage) {
-        if let Some(payload) = msg.payload {
-            // Convert the binary payload to a string
-            let value = String::from_utf8(payload.to_vec()).unwrap_or_else(|_| "Invalid UTF-8".to_string());
-            log::trace!("[from_uprotocol] actuation_cmd : {}", value);
-            
-            // Update the shared data structure with the new value
-            // This is where the lock is acquired and the data is updated
-            let mut data = self.data.lock().unwrap();
-            *data = Some(value);
-            // Lock is released when data goes out of scope
-        }
+        // Decode protobuf or legacy text payloads into the text representation used below
+        let value = match ActuationCommand::decode_message(&msg) {
+            Ok(command) => command.to_legacy_text(),
+            Err(e) => {
+                log::warn!("[from_uprotocol] invalid actuation_cmd : {}", e);
+                return;
+            }
+        };
+        log::trace!("[from_uprotocol] actuation_cmd : {}", value);
+        
+        // Update the shared data structure with the new value
+        // This is where the lock is acquired and the data is updated
+        let mut data = self.data.lock().unwrap();
+        *data = Some(value);
+        // Lock is released when data goes out of scope
     }
 }
 
@@ -105,17 +113,21 @@ struct EngageListener {
 #[async_trait]
 impl UListener for EngageListener {
     async fn on_receive(&self, msg: UMessage) {
-        if let Some(payload) = msg.payload {
-            // Convert the binary payload to a string
-            let value = String::from_utf8(payload.to_vec()).unwrap_or_else(|_| "Invalid UTF-8".to_string());
-            log::trace!("[from_uprotocol] engage : {}", value);
-            
-            // Update the shared data structure with the new value
-            // This is where the lock is acquired and the data is updated
-            let mut data = self.data.lock().unwrap();
-            *data = Some(value);
-            // Lock is released when data goes out of scope
-        }
+        // Decode protobuf or legacy text payloads into "0"/"1"
+        let value = match EngageStatus::decode_message(&msg) {
+            Ok(status) => status.to_legacy_text(),
+            Err(e) => {
+                log::warn!("[from_uprotocol] invalid engage : {}", e);
+                return;
+            }
+        };
+        log::trace!("[from_uprotocol] engage : {}", value);
+        
+        // Update the shared data structure with the new value
+        // This is where the lock is acquired and the data is updated
+        let mut data = self.data.lock().unwrap();
+        *data = Some(value);
+        // Lock is released when data goes out of scope
     }
 }
 
@@ -188,7 +200,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>{
 
     // Create a uProtocol URI provider for this vehicle
     // This defines the identity of this node in the uProtocol network
-    let uri_provider = StaticUriProvider::new("EGOVehicle", 0, 2);
+    // With a vehicle id, the authorities are the ones derived for that vehicle of a fleet
+    let authority = match &args.vehicle_id {
+        Some(vehicle_id) => topics::vehicle_authority(topics::EGO_VEHICLE, vehicle_id),
+        None => topics::EGO_VEHICLE.to_string(),
+    };
+    let topic_uri = |topic: &topics::Topic| match &args.vehicle_id {
+        Some(vehicle_id) => topic.vehicle_uri(vehicle_id),
+        None => Ok(topic.uri()),
+    };
+    let uri_provider = StaticUriProvider::new(&authority, topics::UE_ID, topics::UE_VERSION_MAJOR);
     
     // Create the uProtocol transport using Zenoh as the underlying transport
     let transport = UPTransportZenoh::builder(uri_provider.get_authority())
@@ -204,7 +225,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>{
     
     // Register the actuation command listener with uProtocol
     // This listener will be called when messages matching the filter are received
-    let actuation_filter = UUri::from_str("//CruiseControl/0/2/8001")?;
+    let actuation_filter = topic_uri(&topics::ACTUATION)?;
     log::info!("Registering actuation command listener [filter: {}]", actuation_filter.to_uri(false));
     transport.register_listener(
         &actuation_filter,
@@ -214,7 +235,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>>{
     
     // Register the engage listener with uProtocol
     // This listener will be called when messages matching the filter are received
-    let engage_filter = UUri::from_str("//AAOS/0/2/8002")?;
+    let engage_filter = topic_uri(&topics::ENGAGE)?;
     log::info!("Registering engage listener [filter: {}]", engage_filter.to_uri(false));
     transport.register_listener(
         &engage_filter,
@@ -27,4 +27,7 @@ pub struct Args {
     pub delta: f64,
     #[clap(long, default_value = None)]
     pub router: Option<String>,
+    /// Vehicle id of a fleet (e.g. the role name): appended to the uProtocol authorities
+    #[clap(long)]
+    pub vehicle_id: Option<String>,
 }
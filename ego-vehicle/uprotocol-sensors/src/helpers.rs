@@ -1,15 +1,21 @@
 use crate::sensors::{Listen, SensorComms};
 use carla::client::{ActorBase, Sensor, World};
+use cruise_control_messages::{ActuationCommand, CruiseControlPayload, DecodeError};
 use log;
 use std::error::Error;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::time::sleep;
-use up_rust::{UMessageBuilder, UPayloadFormat, UTransport, UUri};
+use up_rust::{UMessage, UMessageBuilder, UPayloadFormat, UTransport, UUri};
 
 type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
 
+/// Decodes the PID actuation command of `msg`, published as protobuf or as legacy text.
+pub fn decode_actuation(msg: &UMessage) -> std::result::Result<f32, DecodeError> {
+    ActuationCommand::decode_message(msg).map(|command| command.command as f32)
+}
+
 /// Polls until an actor with `role_name` appears, or `running` flips false.
 pub async fn wait_for_actor_id_by_role(
     carla_world: &World,
@@ -137,3 +143,27 @@ where
     // 7) Return handles to keep things alive
     Ok((comms, actor_id, sensor))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cruise_control_messages::Encoding;
+
+    fn actuation_message(payload: &[u8], format: UPayloadFormat) -> UMessage {
+        UMessageBuilder::publish(sdv_lab_topics::ACTUATION.uri())
+            .build_with_payload(payload.to_vec(), format)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode_actuation() {
+        let (payload, format) = ActuationCommand::from_value(-0.25).encode(Encoding::Protobuf);
+        assert_eq!(decode_actuation(&actuation_message(&payload, format)).unwrap(), -0.25);
+
+        let text = actuation_message(b"0.5", UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        assert_eq!(decode_actuation(&text).unwrap(), 0.5);
+
+        let invalid = actuation_message(b"full throttle", UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+        assert!(decode_actuation(&invalid).is_err());
+    }
+}
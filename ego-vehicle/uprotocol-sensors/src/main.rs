@@ -27,22 +27,22 @@ use carla_data_serde::{
 };
 use clap::Parser;
 use ego_vehicle::args::Args;
-use ego_vehicle::helpers::setup_sensor_with_transport;
+use ego_vehicle::helpers::{decode_actuation, setup_sensor_with_transport};
 use ego_vehicle::sensors::{
     CollisionFactory, ImageFactory, ImuMeasurementFactory, LaneInvasionFactory,
     LidarMeasurementFactory, ObstacleDetectionFactory, RadarMeasurementFactory,
 };
 use log;
 use serde_json;
-use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use up_rust::{
     LocalUriProvider, StaticUriProvider, UListener, UMessage, UMessageBuilder, UPayloadFormat,
-    UTransport, UUri,
+    UTransport,
 };
 use up_transport_zenoh::UPTransportZenoh;
+use sdv_lab_topics as topics;
 use up_transport_zenoh::zenoh_config;
 use zenoh::{Config, key_expr::KeyExpr};
 
@@ -60,16 +60,16 @@ const MAX_STEERING: f32 = 1.0;
 const MAX_BRAKING: f32 = 1.0;
 
 // uProtocol resource IDs
-const RESOURCE_VELOCITY_STATUS: u16 = 0x8001;
-const RESOURCE_CLOCK_STATUS: u16 = 0x8002;
+const RESOURCE_VELOCITY_STATUS: u16 = topics::VELOCITY_STATUS.resource_id;
+const RESOURCE_CLOCK_STATUS: u16 = topics::CLOCK_STATUS.resource_id;
 // uProtocol resource IDs for sensors
-const RESOURCE_LANE_INVASION_SENSOR: u16 = 0x8010;
-const RESOURCE_COLLISION_SENSOR: u16 = 0x8011;
-const RESOURCE_OBSTACLE_DETECTION_SENSOR: u16 = 0x8012;
-const RESOURCE_IMAGE_SENSOR: u16 = 0x8013;
-const RESOURCE_RADAR_SENSOR: u16 = 0x8014;
-const RESOURCE_LIDAR_SENSOR: u16 = 0x8015;
-const RESOURCE_IMU_SENSOR: u16 = 0x8016;
+const RESOURCE_LANE_INVASION_SENSOR: u16 = topics::LANE_INVASION.resource_id;
+const RESOURCE_COLLISION_SENSOR: u16 = topics::COLLISION.resource_id;
+const RESOURCE_OBSTACLE_DETECTION_SENSOR: u16 = topics::OBSTACLE_DETECTION.resource_id;
+const RESOURCE_IMAGE_SENSOR: u16 = topics::IMAGE.resource_id;
+const RESOURCE_RADAR_SENSOR: u16 = topics::RADAR.resource_id;
+const RESOURCE_LIDAR_SENSOR: u16 = topics::LIDAR.resource_id;
+const RESOURCE_IMU_SENSOR: u16 = topics::IMU.resource_id;
 
 // Helper function to create a Zenoh configuration
 pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
@@ -91,24 +91,27 @@ pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
 
 // Listener for actuation command - implements the UListener trait for uProtocol
 struct ActuationListener {
-    data: Arc<Mutex<Option<String>>>, // Shared data structure to store the latest actuation command
+    data: Arc<Mutex<Option<f32>>>, // Shared data structure to store the latest actuation command
 }
 
 #[async_trait]
 impl UListener for ActuationListener {
     async fn on_receive(&self, msg: UMessage) {
-        if let Some(payload) = msg.payload {
-            // Convert the binary payload to a string
-            let value =
-                String::from_utf8(payload.to_vec()).unwrap_or_else(|_| "Invalid UTF-8".to_string());
-            log::trace!("[from_uprotocol] actuation_cmd : {}", value);
+        // Decode protobuf or legacy text payloads, keeping the last command on errors
+        let value = match decode_actuation(&msg) {
+            Ok(value) => value,
+            Err(e) => {
+                log::warn!("[from_uprotocol] invalid actuation_cmd : {}", e);
+                return;
+            }
+        };
+        log::trace!("[from_uprotocol] actuation_cmd : {}", value);
 
-            // Update the shared data structure with the new value
-            // This is where the lock is acquired and the data is updated
-            let mut data = self.data.lock().unwrap();
-            *data = Some(value);
-            // Lock is released when data goes out of scope
-        }
+        // Update the shared data structure with the new value
+        // This is where the lock is acquired and the data is updated
+        let mut data = self.data.lock().unwrap();
+        *data = Some(value);
+        // Lock is released when data goes out of scope
     }
 }
 
@@ -216,7 +219,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create a uProtocol URI provider for this vehicle
     // This defines the identity of this node in the uProtocol network
-    let uri_provider = StaticUriProvider::new("EGOVehicle", 0, 2);
+    // With a vehicle id, the authorities are the ones derived for that vehicle of a fleet
+    let authority = match &args.vehicle_id {
+        Some(vehicle_id) => topics::vehicle_authority(topics::EGO_VEHICLE, vehicle_id),
+        None => topics::EGO_VEHICLE.to_string(),
+    };
+    let topic_uri = |topic: &topics::Topic| match &args.vehicle_id {
+        Some(vehicle_id) => topic.vehicle_uri(vehicle_id),
+        None => Ok(topic.uri()),
+    };
+    let uri_provider = StaticUriProvider::new(&authority, topics::UE_ID, topics::UE_VERSION_MAJOR);
 
     // Create the uProtocol transport using Zenoh as the underlying transport
     let transport: Arc<dyn UTransport> = Arc::new(
@@ -229,12 +241,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create shared data structures for uProtocol subscribers
     // These will store the latest values received from uProtocol messages
-    let actuation_cmd: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+    let actuation_cmd: Arc<Mutex<Option<f32>>> = Arc::new(Mutex::new(None));
     let engage: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(Some(0.to_string())));
 
     // Register the actuation command listener with uProtocol
     // This listener will be called when messages matching the filter are received
-    let actuation_filter = UUri::from_str("//CruiseControl/0/2/8001")?;
+    let actuation_filter = topic_uri(&topics::ACTUATION)?;
     log::info!(
         "Registering actuation command listener [filter: {}]",
         actuation_filter.to_uri(false)
@@ -251,7 +263,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Register the engage listener with uProtocol
     // This listener will be called when messages matching the filter are received
-    let engage_filter = UUri::from_str("//AAOS/0/2/8002")?;
+    let engage_filter = topic_uri(&topics::ENGAGE)?;
     log::info!(
         "Registering engage listener [filter: {}]",
         engage_filter.to_uri(false)
@@ -762,10 +774,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     {
                         // scope blocking to release lock after checking the value
                         let data_actuation_cmd = actuation_cmd.lock().unwrap();
-                        if let Some(ref payload) = *data_actuation_cmd {
-                            if let Ok(val) = payload.parse::<f32>() {
-                                pid_output = val;
-                            }
+                        if let Some(val) = *data_actuation_cmd {
+                            pid_output = val;
                         }
                     }
 
@@ -24,6 +24,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use zenoh::{bytes::Encoding, key_expr::KeyExpr, Config};
+use sdv_lab_topics as topics;
 
 // General constants
 const CLIENT_TIME_MS: u64 = 5_000;
@@ -137,8 +138,8 @@ async fn main() {
     let topic_throttle   = KeyExpr::new("vehicle/status/throttle_status").unwrap();
     let topic_steering   = KeyExpr::new("vehicle/status/steering_status").unwrap();
     let topic_braking    = KeyExpr::new("vehicle/status/braking_status").unwrap();
-    let topic_actuation  = KeyExpr::new("control/command/actuation_cmd").unwrap();
-    let topic_engage     = KeyExpr::new("adas/cruise_control/engage").unwrap();
+    let topic_actuation  = KeyExpr::new(topics::ACTUATION.zenoh_key.unwrap()).unwrap();
+    let topic_engage     = KeyExpr::new(topics::ENGAGE.zenoh_key.unwrap()).unwrap();
 
     log::info!("Declaring Subscriber on '{}'...", &topic_throttle);
 
@@ -262,8 +263,8 @@ async fn main() {
     });
 
     // Publish topics
-    let topic_clock    = KeyExpr::new("vehicle/status/clock_status").unwrap();
-    let topic_velocity = KeyExpr::new("vehicle/status/velocity_status").unwrap();
+    let topic_clock    = KeyExpr::new(topics::CLOCK_STATUS.zenoh_key.unwrap()).unwrap();
+    let topic_velocity = KeyExpr::new(topics::VELOCITY_STATUS.zenoh_key.unwrap()).unwrap();
 
     log::info!("Declaring a Zenoh Publisher on '{topic_clock}'...");
     log::info!("Declaring a Zenoh Publisher on '{topic_velocity}'...");
@@ -0,0 +1,56 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// Embeds the git revision into the recorded session metadata
+use std::path::Path;
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// A commit moves the branch HEAD points to, not HEAD itself: the loose ref, or the packed
+// refs once git packed it
+fn watch_revision() {
+    let mut watched = vec!["HEAD".to_string(), "packed-refs".to_string()];
+    watched.extend(git(&["symbolic-ref", "-q", "HEAD"]));
+    for name in watched {
+        let Some(path) = git(&["rev-parse", "--git-path", &name]) else {
+            continue;
+        };
+        // A missing file would rerun the script on every build; its directory sees it appear
+        let path = Path::new(&path);
+        match path.parent() {
+            Some(parent) if !path.exists() => println!("cargo:rerun-if-changed={}", parent.display()),
+            _ => println!("cargo:rerun-if-changed={}", path.display()),
+        }
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_REVISION");
+
+    let revision = std::env::var("GIT_REVISION").ok().or_else(|| {
+        watch_revision();
+        git(&["rev-parse", "--short", "HEAD"])
+    });
+    println!("cargo:rustc-env=GIT_REVISION={}", revision.unwrap_or_else(|| "unknown".to_string()));
+}
@@ -0,0 +1,398 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Parameters of the adaptive cruise control.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccConfig {
+    /// Time gap in seconds kept to the lead vehicle, on top of the standstill distance
+    pub time_gap: f64,
+    /// Distance in meters kept to a stopped lead vehicle
+    pub standstill_distance: f64,
+    /// Speed correction in m/s per meter of spacing error
+    pub distance_gain: f64,
+    /// Radar detections further away than this distance in meters are ignored
+    pub max_range: f64,
+    /// Half-width in degrees of the radar cone in which a detection is taken as the lead vehicle
+    pub radar_half_angle: f64,
+    /// Seconds without a detection after which the lead vehicle is dropped
+    pub target_timeout: f64,
+    /// Km/h by which the spacing speed has to exceed the set speed before spacing mode is left,
+    /// so that the mode does not flip on every step near the boundary
+    pub mode_hysteresis: f64,
+}
+
+impl Default for AccConfig {
+    fn default() -> Self {
+        AccConfig {
+            time_gap: 1.8,
+            standstill_distance: 5.0,
+            distance_gain: 0.3,
+            max_range: 100.0,
+            radar_half_angle: 5.0,
+            target_timeout: 0.5,
+            mode_hysteresis: 2.0,
+        }
+    }
+}
+
+impl AccConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("time gap", self.time_gap),
+            ("distance gain", self.distance_gain),
+            ("max range", self.max_range),
+            ("radar half angle", self.radar_half_angle),
+            ("target timeout", self.target_timeout),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{} must be a finite, positive number (got {})", name, value));
+            }
+        }
+        for (name, value) in [
+            ("standstill distance", self.standstill_distance),
+            ("mode hysteresis", self.mode_hysteresis),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} must be a finite, non-negative number (got {})", name, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Obstacle detection event of the ego vehicle sensor bridge (`0x8012`). Only the distance
+/// in meters to the detected actor is used.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ObstacleEvent {
+    pub distance: f64,
+}
+
+/// One radar return: radial velocity in m/s (negative when closing in), angles in radians
+/// and depth in meters.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RadarDetection {
+    pub velocity: f64,
+    pub azimuth: f64,
+    #[serde(default)]
+    pub altitude: f64,
+    pub depth: f64,
+}
+
+/// Radar measurement of the ego vehicle sensor bridge (`0x8014`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct RadarMeasurement {
+    pub detections: Vec<RadarDetection>,
+}
+
+/// Estimated lead vehicle: distance in meters and speed relative to the ego vehicle in m/s
+/// (negative when closing in).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Lead {
+    pub distance: f64,
+    pub relative_speed: f64,
+}
+
+/// Fuses the obstacle detector and the radar into a lead vehicle estimate. The radar
+/// measures the relative speed directly; with the obstacle detector alone it is the
+/// low-passed derivative of the distance.
+#[derive(Debug, Clone)]
+pub struct LeadTracker {
+    config: AccConfig,
+    // (time, distance) of the last obstacle event
+    obstacle: Option<(f64, f64)>,
+    obstacle_speed: Option<f64>,
+    // (time, distance, relative speed) of the last radar return in the cone
+    radar: Option<(f64, f64, f64)>,
+}
+
+impl LeadTracker {
+    pub fn new(config: AccConfig) -> Self {
+        LeadTracker {
+            config,
+            obstacle: None,
+            obstacle_speed: None,
+            radar: None,
+        }
+    }
+
+    pub fn obstacle(&mut self, event: &ObstacleEvent, time: f64) {
+        if let Some((previous_time, previous_distance)) = self.obstacle {
+            let delta_time = time - previous_time;
+            if delta_time > self.config.target_timeout || delta_time < 0.0 {
+                self.obstacle_speed = None;
+            } else if delta_time > 0.0 {
+                let raw = (event.distance - previous_distance) / delta_time;
+                self.obstacle_speed = Some(match self.obstacle_speed {
+                    Some(speed) => speed + 0.5 * (raw - speed),
+                    None => raw,
+                });
+            }
+        }
+        self.obstacle = Some((time, event.distance));
+    }
+
+    /// Takes the closest detection within the radar cone and range as the lead vehicle.
+    pub fn radar(&mut self, measurement: &RadarMeasurement, time: f64) {
+        let half_angle = self.config.radar_half_angle.to_radians();
+        let closest = measurement
+            .detections
+            .iter()
+            .filter(|detection| detection.azimuth.abs() <= half_angle && detection.altitude.abs() <= half_angle)
+            .filter(|detection| detection.depth > 0.0 && detection.depth <= self.config.max_range)
+            .min_by(|a, b| a.depth.total_cmp(&b.depth));
+        if let Some(detection) = closest {
+            self.radar = Some((time, detection.depth, detection.velocity));
+        }
+    }
+
+    /// Lead vehicle seen within the target timeout before `now`, the closest one if both
+    /// sensors see one.
+    pub fn lead(&self, now: f64) -> Option<Lead> {
+        let fresh = |time: f64| (0.0..=self.config.target_timeout).contains(&(now - time));
+        let radar = self.radar.filter(|(time, _, _)| fresh(*time));
+        let obstacle = self.obstacle.filter(|(time, _)| fresh(*time));
+
+        let distance = match (radar, obstacle) {
+            (Some((_, radar_distance, _)), Some((_, obstacle_distance))) => radar_distance.min(obstacle_distance),
+            (Some((_, distance, _)), None) | (None, Some((_, distance))) => distance,
+            (None, None) => return None,
+        };
+        let relative_speed = radar
+            .map(|(_, _, speed)| speed)
+            .or(self.obstacle_speed.filter(|_| obstacle.is_some()))
+            .unwrap_or(0.0);
+        Some(Lead { distance, relative_speed })
+    }
+}
+
+/// Which command the adaptive cruise control follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccMode {
+    /// Not engaged
+    Standby,
+    /// Tracking the set speed
+    Speed,
+    /// Keeping the time gap to a lead vehicle slower than the set speed
+    Spacing,
+}
+
+impl fmt::Display for AccMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccMode::Standby => write!(f, "standby"),
+            AccMode::Speed => write!(f, "speed"),
+            AccMode::Spacing => write!(f, "spacing"),
+        }
+    }
+}
+
+/// Published on the ACC status topic whenever the mode changes. Speeds are in km/h.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AccStatus {
+    pub mode: AccMode,
+    pub set_speed: f64,
+    /// Speed reference tracked by the controller
+    pub reference_speed: f64,
+    pub lead: Option<Lead>,
+    /// Distance in meters the spacing controller aims for, with a lead vehicle
+    pub desired_distance: Option<f64>,
+    pub timestamp: f64,
+}
+
+/// Time-gap spacing controller on top of the speed controller. The spacing controller turns
+/// the lead vehicle estimate into a speed reference; the lower of it and the set speed is
+/// handed to the longitudinal controller.
+#[derive(Debug, Clone)]
+pub struct AdaptiveCruise {
+    config: AccConfig,
+    tracker: LeadTracker,
+    mode: AccMode,
+}
+
+impl AdaptiveCruise {
+    pub fn new(config: AccConfig) -> Self {
+        AdaptiveCruise {
+            config,
+            tracker: LeadTracker::new(config),
+            mode: AccMode::Standby,
+        }
+    }
+
+    pub fn config(&self) -> AccConfig {
+        self.config
+    }
+
+    pub fn mode(&self) -> AccMode {
+        self.mode
+    }
+
+    pub fn tracker(&mut self) -> &mut LeadTracker {
+        &mut self.tracker
+    }
+
+    /// Picks the command for a control step at `now` and returns the status, with whether
+    /// the mode changed.
+    pub fn update(&mut self, set_speed: f64, ego_speed: f64, now: f64, timestamp: f64) -> (AccStatus, bool) {
+        let lead = self.tracker.lead(now);
+        let mut status = AccStatus {
+            mode: AccMode::Speed,
+            set_speed,
+            reference_speed: set_speed,
+            lead,
+            desired_distance: None,
+            timestamp,
+        };
+
+        if let Some(lead) = lead {
+            let ego_speed = ego_speed.max(0.0) / 3.6;
+            let desired_distance = self.config.standstill_distance + self.config.time_gap * ego_speed;
+            let spacing_speed = (ego_speed + lead.relative_speed
+                + self.config.distance_gain * (lead.distance - desired_distance))
+                .max(0.0)
+                * 3.6;
+            status.desired_distance = Some(desired_distance);
+            // Spacing starts as soon as the lead is slower, but only ends once it is clearly
+            // faster; the reference is the lower of both speeds either way
+            let release_speed = match self.mode {
+                AccMode::Spacing => set_speed + self.config.mode_hysteresis,
+                _ => set_speed,
+            };
+            if spacing_speed < release_speed {
+                status.mode = AccMode::Spacing;
+                status.reference_speed = spacing_speed.min(set_speed);
+            }
+        }
+
+        let changed = status.mode != self.mode;
+        self.mode = status.mode;
+        (status, changed)
+    }
+
+    /// Status while the controller is not engaged, with whether the mode changed.
+    pub fn standby(&mut self, set_speed: f64, timestamp: f64) -> (AccStatus, bool) {
+        let changed = self.mode != AccMode::Standby;
+        self.mode = AccMode::Standby;
+        let status = AccStatus {
+            mode: AccMode::Standby,
+            set_speed,
+            reference_speed: set_speed,
+            lead: None,
+            desired_distance: None,
+            timestamp,
+        };
+        (status, changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(depth: f64, azimuth_deg: f64, velocity: f64) -> RadarDetection {
+        RadarDetection { velocity, azimuth: azimuth_deg.to_radians(), altitude: 0.0, depth }
+    }
+
+    #[test]
+    fn test_lead_tracking() {
+        let mut tracker = LeadTracker::new(AccConfig::default());
+        assert_eq!(tracker.lead(0.0), None);
+
+        // The closest return inside the cone and range is the lead vehicle
+        let measurement = RadarMeasurement {
+            detections: vec![detection(30.0, 20.0, 0.0), detection(40.0, 1.0, -2.0), detection(150.0, 0.0, 0.0)],
+        };
+        tracker.radar(&measurement, 1.0);
+        assert_eq!(tracker.lead(1.2), Some(Lead { distance: 40.0, relative_speed: -2.0 }));
+
+        // Without the radar, the relative speed is derived from the obstacle distances
+        tracker.obstacle(&ObstacleEvent { distance: 40.0 }, 2.0);
+        tracker.obstacle(&ObstacleEvent { distance: 39.0 }, 2.1);
+        let lead = tracker.lead(2.1).unwrap();
+        assert_eq!(lead.distance, 39.0);
+        assert!((lead.relative_speed + 10.0).abs() < 1e-9);
+
+        assert_eq!(tracker.lead(2.7), None);
+    }
+
+    #[test]
+    fn test_spacing_command() {
+        let mut acc = AdaptiveCruise::new(AccConfig::default());
+        let (status, changed) = acc.update(100.0, 72.0, 0.0, 0.0);
+        assert_eq!((status.mode, status.reference_speed, changed), (AccMode::Speed, 100.0, true));
+
+        // 20 m/s with a 1.8 s gap: 41 m wanted, the lead at 31 m with the same speed is too close
+        acc.tracker().obstacle(&ObstacleEvent { distance: 31.0 }, 1.0);
+        let (status, changed) = acc.update(100.0, 72.0, 1.0, 0.0);
+        assert!(changed);
+        assert_eq!(status.mode, AccMode::Spacing);
+        assert_eq!(status.desired_distance, Some(41.0));
+        assert!((status.reference_speed - (20.0 - 0.3 * 10.0) * 3.6).abs() < 1e-9);
+
+        // A set speed below the spacing command wins
+        let (status, _) = acc.update(50.0, 72.0, 1.0, 0.0);
+        assert_eq!((status.mode, status.reference_speed), (AccMode::Speed, 50.0));
+
+        assert!(acc.standby(50.0, 0.0).1);
+        assert!(!acc.standby(50.0, 0.0).1);
+    }
+
+    #[test]
+    fn test_mode_hysteresis_near_the_set_speed() {
+        let mut acc = AdaptiveCruise::new(AccConfig::default());
+        // At 72 km/h behind a lead of the same speed, the spacing speed is 72 km/h at the 41 m
+        // gap plus 1.08 km/h per meter beyond it
+        let mut step = |distance: f64, now: f64| {
+            acc.tracker().radar(&RadarMeasurement { detections: vec![detection(distance, 0.0, 0.0)] }, now);
+            acc.update(73.0, 72.0, now, 0.0)
+        };
+
+        let (status, changed) = step(41.5, 1.0);
+        assert_eq!((status.mode, changed), (AccMode::Spacing, true));
+        assert!(status.reference_speed < 73.0);
+
+        // Lead distance noise around the boundary keeps the mode, with the reference capped
+        // at the set speed
+        let mut now = 1.0;
+        for distance in [41.9, 41.5, 42.1, 41.8, 42.3] {
+            now += 0.1;
+            let (status, changed) = step(distance, now);
+            assert_eq!((status.mode, changed), (AccMode::Spacing, false), "{}", distance);
+            assert!(status.reference_speed <= 73.0);
+        }
+
+        // Clearly faster than the set speed: back to speed mode
+        let (status, changed) = step(44.0, now + 0.1);
+        assert_eq!((status.mode, status.reference_speed, changed), (AccMode::Speed, 73.0, true));
+        let (status, changed) = step(42.3, now + 0.2);
+        assert_eq!((status.mode, changed), (AccMode::Speed, false));
+    }
+
+    #[test]
+    fn test_sensor_payloads() {
+        let event: ObstacleEvent = serde_json::from_str(r#"{"frame": 12, "distance": 25.5, "other_actor_id": 7}"#).unwrap();
+        assert_eq!(event.distance, 25.5);
+
+        let measurement: RadarMeasurement = serde_json::from_str(
+            r#"{"frame": 12, "detections": [{"velocity": -1.5, "azimuth": 0.01, "altitude": 0.0, "depth": 42.0}]}"#,
+        )
+        .unwrap();
+        assert_eq!(measurement.detections[0].depth, 42.0);
+    }
+}
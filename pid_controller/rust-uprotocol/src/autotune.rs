@@ -0,0 +1,335 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::f64::consts::PI;
+use std::fmt::Write;
+
+/// Parameters of the relay-feedback experiment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelayConfig {
+    /// The command switches between `bias + amplitude` and `bias - amplitude`
+    pub amplitude: f64,
+    /// Initial command bias, adapted while settling so that the oscillation is symmetric
+    pub bias: f64,
+    /// Velocity band in km/h around the setpoint in which the relay does not switch
+    pub hysteresis: f64,
+    /// Oscillation periods discarded before measuring
+    pub settle_cycles: usize,
+    /// Oscillation periods averaged into the result
+    pub measure_cycles: usize,
+}
+
+impl Default for RelayConfig {
+    fn default() -> Self {
+        RelayConfig {
+            amplitude: 0.3,
+            bias: 0.0,
+            hysteresis: 0.5,
+            settle_cycles: 3,
+            measure_cycles: 4,
+        }
+    }
+}
+
+impl RelayConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.amplitude > 0.0 && self.amplitude <= 1.0) {
+            return Err(format!("relay amplitude must be within (0, 1] (got {})", self.amplitude));
+        }
+        if !self.bias.is_finite() || self.bias.abs() >= 1.0 {
+            return Err(format!("relay bias must be within (-1, 1) (got {})", self.bias));
+        }
+        if !self.hysteresis.is_finite() || self.hysteresis < 0.0 {
+            return Err(format!("hysteresis must be a finite, non-negative number (got {})", self.hysteresis));
+        }
+        if self.measure_cycles == 0 {
+            return Err("at least one cycle has to be measured".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Ultimate gain and period identified by a relay experiment, with the measured oscillation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UltimateGains {
+    /// Ultimate gain in command per km/h
+    pub ku: f64,
+    /// Ultimate period in seconds
+    pub tu: f64,
+    /// Half the peak-to-peak velocity oscillation in km/h
+    pub amplitude: f64,
+    /// Command bias the oscillation settled around
+    pub bias: f64,
+}
+
+/// Relay-feedback experiment (Åström–Hägglund) around a velocity setpoint: the relay makes
+/// the loop oscillate at its ultimate period, and the describing function of the relay gives
+/// the ultimate gain `Ku = 4 d / (π sqrt(a² - ε²))` from the relay amplitude `d`, the
+/// oscillation amplitude `a` and the hysteresis `ε`.
+#[derive(Debug, Clone)]
+pub struct RelayExperiment {
+    config: RelayConfig,
+    setpoint: f64,
+    bias: f64,
+    high: bool,
+    last_time: Option<f64>,
+    // Time of the last switch to the high command, which starts a cycle
+    cycle_start: Option<f64>,
+    time_high: f64,
+    time_low: f64,
+    max_velocity: f64,
+    min_velocity: f64,
+    cycles: usize,
+    periods: Vec<f64>,
+    amplitudes: Vec<f64>,
+}
+
+impl RelayExperiment {
+    pub fn new(config: RelayConfig, setpoint: f64) -> Self {
+        RelayExperiment {
+            config,
+            setpoint,
+            bias: config.bias,
+            high: true,
+            last_time: None,
+            cycle_start: None,
+            time_high: 0.0,
+            time_low: 0.0,
+            max_velocity: f64::MIN,
+            min_velocity: f64::MAX,
+            cycles: 0,
+            periods: Vec::new(),
+            amplitudes: Vec::new(),
+        }
+    }
+
+    pub fn setpoint(&self) -> f64 {
+        self.setpoint
+    }
+
+    /// Completed oscillation periods, including the discarded ones.
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Feeds a velocity sample at `time` (seconds) and returns the command to apply.
+    pub fn step(&mut self, velocity: f64, time: f64) -> f64 {
+        if let Some(last_time) = self.last_time {
+            let delta_time = (time - last_time).max(0.0);
+            if self.high {
+                self.time_high += delta_time;
+            } else {
+                self.time_low += delta_time;
+            }
+        }
+        self.last_time = Some(time);
+        self.max_velocity = self.max_velocity.max(velocity);
+        self.min_velocity = self.min_velocity.min(velocity);
+
+        if self.high && velocity > self.setpoint + self.config.hysteresis {
+            self.high = false;
+        } else if !self.high && velocity < self.setpoint - self.config.hysteresis {
+            self.high = true;
+            self.complete_cycle(time);
+        }
+
+        let command = if self.high { self.bias + self.config.amplitude } else { self.bias - self.config.amplitude };
+        command.clamp(-1.0, 1.0)
+    }
+
+    fn complete_cycle(&mut self, time: f64) {
+        if let Some(start) = self.cycle_start {
+            self.cycles += 1;
+            if self.cycles > self.config.settle_cycles {
+                self.periods.push(time - start);
+                self.amplitudes.push((self.max_velocity - self.min_velocity) / 2.0);
+            } else if self.time_high + self.time_low > 0.0 {
+                // A longer high than low phase means the bias is too low to hold the setpoint
+                let asymmetry = (self.time_high - self.time_low) / (self.time_high + self.time_low);
+                let limit = 1.0 - self.config.amplitude;
+                self.bias = (self.bias + 0.5 * self.config.amplitude * asymmetry).clamp(-limit, limit);
+            }
+        }
+        self.cycle_start = Some(time);
+        self.time_high = 0.0;
+        self.time_low = 0.0;
+        self.max_velocity = f64::MIN;
+        self.min_velocity = f64::MAX;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.periods.len() >= self.config.measure_cycles
+    }
+
+    /// Averaged result over the measured cycles, `None` until at least one cycle was measured
+    /// or when the oscillation does not leave the hysteresis band.
+    pub fn result(&self) -> Option<UltimateGains> {
+        if self.periods.is_empty() {
+            return None;
+        }
+        let count = self.periods.len() as f64;
+        let tu = self.periods.iter().sum::<f64>() / count;
+        let amplitude = self.amplitudes.iter().sum::<f64>() / count;
+        let hysteresis = self.config.hysteresis;
+        if amplitude <= hysteresis || tu <= 0.0 {
+            return None;
+        }
+        let ku = 4.0 * self.config.amplitude / (PI * (amplitude * amplitude - hysteresis * hysteresis).sqrt());
+        Some(UltimateGains { ku, tu, amplitude, bias: self.bias })
+    }
+}
+
+/// Tuning rules turning the ultimate gain and period into PID gains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuningRule {
+    /// Classic Ziegler–Nichols: fast, with about 25 % overshoot
+    ZieglerNichols,
+    /// Tyreus–Luyben: less aggressive, for a smooth response
+    TyreusLuyben,
+    /// Pessen integral rule: aggressive disturbance rejection
+    PessenIntegral,
+    /// Ziegler–Nichols variant with some overshoot
+    SomeOvershoot,
+    /// Ziegler–Nichols variant without overshoot
+    NoOvershoot,
+}
+
+impl TuningRule {
+    pub const ALL: [TuningRule; 5] = [
+        TuningRule::ZieglerNichols,
+        TuningRule::TyreusLuyben,
+        TuningRule::PessenIntegral,
+        TuningRule::SomeOvershoot,
+        TuningRule::NoOvershoot,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TuningRule::ZieglerNichols => "ziegler-nichols",
+            TuningRule::TyreusLuyben => "tyreus-luyben",
+            TuningRule::PessenIntegral => "pessen-integral",
+            TuningRule::SomeOvershoot => "some-overshoot",
+            TuningRule::NoOvershoot => "no-overshoot",
+        }
+    }
+
+    /// `(kp, ki, kd)` in parallel form.
+    pub fn gains(&self, ultimate: &UltimateGains) -> (f64, f64, f64) {
+        let (ku, tu) = (ultimate.ku, ultimate.tu);
+        // Proportional gain, integral time and derivative time
+        let (kp, ti, td) = match self {
+            TuningRule::ZieglerNichols => (0.6 * ku, tu / 2.0, tu / 8.0),
+            TuningRule::TyreusLuyben => (ku / 2.2, 2.2 * tu, tu / 6.3),
+            TuningRule::PessenIntegral => (0.7 * ku, 0.4 * tu, 0.15 * tu),
+            TuningRule::SomeOvershoot => (ku / 3.0, tu / 2.0, tu / 3.0),
+            TuningRule::NoOvershoot => (0.2 * ku, tu / 2.0, tu / 3.0),
+        };
+        (kp, kp / ti, kp * td)
+    }
+}
+
+/// Gain schedule file (see [`crate::gain_schedule::GainSchedule`]) with one breakpoint per
+/// experiment speed using `rule`; the gains of the other rules are listed as comments.
+pub fn schedule_toml(results: &[(f64, UltimateGains)], rule: TuningRule) -> String {
+    let mut toml = String::new();
+    let _ = writeln!(toml, "# PID gains identified by relay-feedback auto-tuning, rule: {}", rule.name());
+    let _ = writeln!(toml, "# Load with: pid_controller --gain-schedule <this file>");
+
+    let mut results = results.to_vec();
+    results.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for (speed, ultimate) in &results {
+        let _ = writeln!(toml);
+        let _ = writeln!(
+            toml,
+            "# {} km/h: Ku = {:.6}, Tu = {:.3} s, oscillation amplitude = {:.3} km/h, bias = {:.4}",
+            speed, ultimate.ku, ultimate.tu, ultimate.amplitude, ultimate.bias
+        );
+        for other in TuningRule::ALL.iter().filter(|other| **other != rule) {
+            let (kp, ki, kd) = other.gains(ultimate);
+            let _ = writeln!(toml, "#   {:<16} kp = {:.6}, ki = {:.6}, kd = {:.6}", other.name(), kp, ki, kd);
+        }
+        let (kp, ki, kd) = rule.gains(ultimate);
+        let _ = writeln!(toml, "[[breakpoints]]");
+        let _ = writeln!(toml, "speed = {:?}", speed);
+        let _ = writeln!(toml, "kp = {:.6}", kp);
+        let _ = writeln!(toml, "ki = {:.6}", ki);
+        let _ = writeln!(toml, "kd = {:.6}", kd);
+    }
+    toml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gain_schedule::GainSchedule;
+
+    const DT: f64 = 0.05;
+
+    // First order plant with dead time: T dv/dt = K (u - u0) - (v - v0), measured after `delay` steps
+    fn run_relay(config: RelayConfig, setpoint: f64) -> RelayExperiment {
+        let (gain, time_constant, delay) = (100.0, 5.0, 10);
+        let mut experiment = RelayExperiment::new(config, setpoint);
+        let mut velocity = 0.0;
+        let mut measurements = std::collections::VecDeque::from(vec![0.0; delay]);
+        for i in 0..20_000 {
+            let measured = measurements.pop_front().unwrap();
+            let command = experiment.step(measured, i as f64 * DT);
+            velocity += (gain * (command - 0.2) - velocity) / time_constant * DT;
+            measurements.push_back(velocity);
+            if experiment.is_done() {
+                break;
+            }
+        }
+        experiment
+    }
+
+    #[test]
+    fn test_relay_identifies_ultimate_point() {
+        // The high command has to be able to reach the setpoint
+        let experiment = run_relay(RelayConfig { bias: 0.4, ..RelayConfig::default() }, 40.0);
+        assert!(experiment.is_done());
+        let ultimate = experiment.result().unwrap();
+
+        // Analytically Tu = 2.0 s and Ku = 0.157: the describing function approximation gives a
+        // somewhat longer period and lower gain. The bias converges to the holding command.
+        assert!(ultimate.tu > 2.0 && ultimate.tu < 3.0, "Tu = {}", ultimate.tu);
+        assert!(ultimate.ku > 0.1 && ultimate.ku < 0.2, "Ku = {}", ultimate.ku);
+        assert!((ultimate.bias - 0.6).abs() < 0.1, "bias = {}", ultimate.bias);
+    }
+
+    #[test]
+    fn test_tuning_rules() {
+        let ultimate = UltimateGains { ku: 1.0, tu: 2.0, amplitude: 1.0, bias: 0.0 };
+        let (kp, ki, kd) = TuningRule::ZieglerNichols.gains(&ultimate);
+        assert_eq!((kp, ki, kd), (0.6, 0.6, 0.15));
+        for rule in TuningRule::ALL {
+            let (kp, ki, kd) = rule.gains(&ultimate);
+            assert!(kp > 0.0 && ki > 0.0 && kd > 0.0, "{}", rule.name());
+        }
+    }
+
+    #[test]
+    fn test_schedule_file_is_loadable() {
+        let results = [
+            (80.0, UltimateGains { ku: 0.5, tu: 3.0, amplitude: 1.0, bias: 0.3 }),
+            (30.0, UltimateGains { ku: 1.0, tu: 2.0, amplitude: 1.0, bias: 0.1 }),
+        ];
+        let schedule = GainSchedule::from_toml(&schedule_toml(&results, TuningRule::TyreusLuyben)).unwrap();
+        assert_eq!(schedule.breakpoints().len(), 2);
+        assert_eq!(schedule.breakpoints()[0].speed, 30.0);
+        assert!((schedule.breakpoints()[0].kp - 1.0 / 2.2).abs() < 1e-6);
+    }
+}
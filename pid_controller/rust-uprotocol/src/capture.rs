@@ -0,0 +1,419 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use protobuf::Message;
+use up_rust::UMessage;
+
+// Layout of the capture files of `up_record` and `up_replay`.
+//
+// All numbers are little endian:
+//
+// - Header: `UPCAPTUR`, format version (u32), reserved (u32)
+// - Records: body length (u32), then the body: receive time in seconds since the epoch (f64)
+//   followed by the protobuf encoded `UMessage`, with its attributes and payload
+// - Index, written on close: record count (u64), then per record its file offset (u64) and
+//   receive time (f64)
+// - Footer: index offset (u64), `UPCAPIDX`
+//
+// A capture that was not closed has no index; it is read by scanning the records, up to the
+// last complete one.
+const MAGIC: &[u8; 8] = b"UPCAPTUR";
+const INDEX_MAGIC: &[u8; 8] = b"UPCAPIDX";
+const VERSION: u32 = 1;
+const HEADER_LEN: u64 = 16;
+const FOOTER_LEN: u64 = 16;
+
+/// A captured message and the wall-clock time it was received at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedMessage {
+    /// Seconds since the epoch
+    pub time: f64,
+    pub message: UMessage,
+}
+
+/// Location of a record in the capture file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexEntry {
+    pub offset: u64,
+    pub time: f64,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Output of a capture that can be cut back to a given length, to drop a partly written record.
+pub trait Truncate {
+    fn truncate(&mut self, len: u64) -> io::Result<()>;
+}
+
+impl Truncate for BufWriter<File> {
+    fn truncate(&mut self, len: u64) -> io::Result<()> {
+        self.get_ref().set_len(len)
+    }
+}
+
+impl Truncate for Cursor<Vec<u8>> {
+    fn truncate(&mut self, len: u64) -> io::Result<()> {
+        self.get_mut().truncate(len as usize);
+        Ok(())
+    }
+}
+
+/// Appends messages to a capture file.
+pub struct CaptureWriter<W: Write + Seek + Truncate> {
+    writer: W,
+    position: u64,
+    index: Vec<IndexEntry>,
+}
+
+impl CaptureWriter<BufWriter<File>> {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write + Seek + Truncate> CaptureWriter<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(CaptureWriter { writer, position: HEADER_LEN, index: Vec::new() })
+    }
+
+    pub fn write(&mut self, captured: &CapturedMessage) -> io::Result<()> {
+        let message = captured
+            .message
+            .write_to_bytes()
+            .map_err(|e| invalid_data(format!("failed to encode message: {}", e)))?;
+        let length = u32::try_from(8 + message.len()).map_err(|_| invalid_data("message too large".to_string()))?;
+
+        // A record that fails half way is cut off, so that the next one starts where it did
+        let offset = self.position;
+        let written = self
+            .writer
+            .write_all(&length.to_le_bytes())
+            .and_then(|_| self.writer.write_all(&captured.time.to_le_bytes()))
+            .and_then(|_| self.writer.write_all(&message));
+        if let Err(e) = written {
+            self.writer.seek(SeekFrom::Start(offset))?;
+            self.writer.truncate(offset)?;
+            return Err(e);
+        }
+        self.index.push(IndexEntry { offset: self.position, time: captured.time });
+        self.position += 4 + length as u64;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Writes the index and the footer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let index_offset = self.position;
+        self.writer.write_all(&(self.index.len() as u64).to_le_bytes())?;
+        for entry in &self.index {
+            self.writer.write_all(&entry.offset.to_le_bytes())?;
+            self.writer.write_all(&entry.time.to_le_bytes())?;
+        }
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer.write_all(INDEX_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads the records of a capture file by their index.
+pub struct CaptureReader<R: Read + Seek> {
+    reader: R,
+    end: u64,
+    index: Vec<IndexEntry>,
+    indexed: bool,
+}
+
+impl CaptureReader<BufReader<File>> {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> CaptureReader<R> {
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; HEADER_LEN as usize];
+        reader.read_exact(&mut header).map_err(|_| invalid_data("not a capture file".to_string()))?;
+        if &header[..8] != MAGIC {
+            return Err(invalid_data("not a capture file".to_string()));
+        }
+        let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported capture version {}", version)));
+        }
+
+        let end = reader.seek(SeekFrom::End(0))?;
+        let (index, indexed) = match Self::read_index(&mut reader, end)? {
+            Some(index) => (index, true),
+            None => (Self::scan(&mut reader, end)?, false),
+        };
+        Ok(CaptureReader { reader, end, index, indexed })
+    }
+
+    fn read_index(reader: &mut R, end: u64) -> io::Result<Option<Vec<IndexEntry>>> {
+        if end < HEADER_LEN + FOOTER_LEN + 8 {
+            return Ok(None);
+        }
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        reader.seek(SeekFrom::Start(end - FOOTER_LEN))?;
+        reader.read_exact(&mut footer)?;
+        if &footer[8..] != INDEX_MAGIC {
+            return Ok(None);
+        }
+        // The offset comes from the file: a corrupt one falls back to scanning the records
+        let index_offset = u64::from_le_bytes(footer[..8].try_into().unwrap());
+        let entries_offset = match index_offset.checked_add(8) {
+            Some(offset) if index_offset >= HEADER_LEN && offset <= end - FOOTER_LEN => offset,
+            _ => return Ok(None),
+        };
+
+        reader.seek(SeekFrom::Start(index_offset))?;
+        let count = read_u64(reader)?;
+        if count.checked_mul(16) != Some(end - FOOTER_LEN - entries_offset) {
+            return Ok(None);
+        }
+        let mut index = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let offset = read_u64(reader)?;
+            let time = f64::from_bits(read_u64(reader)?);
+            index.push(IndexEntry { offset, time });
+        }
+        Ok(Some(index))
+    }
+
+    // Walks the records of a capture without index, stopping at the first incomplete one
+    fn scan(reader: &mut R, end: u64) -> io::Result<Vec<IndexEntry>> {
+        let mut index = Vec::new();
+        let mut offset = HEADER_LEN;
+        while offset + 12 <= end {
+            reader.seek(SeekFrom::Start(offset))?;
+            let length = read_u32(reader)? as u64;
+            if length < 8 || offset + 4 + length > end {
+                break;
+            }
+            let time = f64::from_bits(read_u64(reader)?);
+            index.push(IndexEntry { offset, time });
+            offset += 4 + length;
+        }
+        Ok(index)
+    }
+
+    /// Whether the capture was closed properly, with an index.
+    pub fn is_indexed(&self) -> bool {
+        self.indexed
+    }
+
+    pub fn index(&self) -> &[IndexEntry] {
+        &self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Position of the first record received at or after `time`.
+    pub fn position_at(&self, time: f64) -> usize {
+        self.index.partition_point(|entry| entry.time < time)
+    }
+
+    /// Reads the record at `position`.
+    pub fn read(&mut self, position: usize) -> io::Result<CapturedMessage> {
+        let entry = *self
+            .index
+            .get(position)
+            .ok_or_else(|| invalid_data(format!("no record {}", position)))?;
+        self.reader.seek(SeekFrom::Start(entry.offset))?;
+        let length = read_u32(&mut self.reader)? as u64;
+        if length < 8 || entry.offset.saturating_add(4 + length) > self.end {
+            return Err(invalid_data(format!("record {}: invalid length {}", position, length)));
+        }
+        let time = f64::from_bits(read_u64(&mut self.reader)?);
+        let mut body = vec![0u8; (length - 8) as usize];
+        self.reader.read_exact(&mut body)?;
+        let message = UMessage::parse_from_bytes(&body)
+            .map_err(|e| invalid_data(format!("record {}: invalid message: {}", position, e)))?;
+        Ok(CapturedMessage { time, message })
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use up_rust::{UMessageBuilder, UPayloadFormat};
+    use sdv_lab_topics as topics;
+
+    fn captured(time: f64, payload: &str) -> CapturedMessage {
+        let message = UMessageBuilder::publish(topics::VELOCITY_STATUS.uri())
+            .with_ttl(500)
+            .build_with_payload(payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .unwrap();
+        CapturedMessage { time, message }
+    }
+
+    fn write(messages: &[CapturedMessage]) -> CaptureWriter<Cursor<Vec<u8>>> {
+        let mut writer = CaptureWriter::new(Cursor::new(Vec::new())).unwrap();
+        for message in messages {
+            writer.write(message).unwrap();
+        }
+        writer
+    }
+
+    #[test]
+    fn test_round_trip_with_index() {
+        let messages = [captured(10.0, "1.5"), captured(10.05, "2.5"), captured(10.1, "3.5")];
+        let bytes = write(&messages).finish().unwrap().into_inner();
+
+        let mut reader = CaptureReader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.is_indexed());
+        assert_eq!(reader.len(), 3);
+        assert_eq!(reader.read(2).unwrap(), messages[2]);
+        assert_eq!(reader.read(0).unwrap(), messages[0]);
+        assert_eq!(reader.position_at(10.02), 1);
+        assert_eq!(reader.position_at(11.0), 3);
+    }
+
+    #[test]
+    fn test_unfinished_capture_is_scanned() {
+        let messages = [captured(1.0, "1"), captured(2.0, "2")];
+        let mut writer = write(&messages);
+        writer.flush().unwrap();
+        let mut bytes = writer.writer.into_inner();
+        // A record cut short by a crash is dropped
+        bytes.extend_from_slice(&100u32.to_le_bytes());
+        bytes.extend_from_slice(&3.0f64.to_le_bytes());
+
+        let mut reader = CaptureReader::new(Cursor::new(bytes)).unwrap();
+        assert!(!reader.is_indexed());
+        assert_eq!(reader.len(), 2);
+        assert_eq!(reader.read(1).unwrap(), messages[1]);
+
+        assert!(CaptureReader::new(Cursor::new(b"not a capture file".to_vec())).is_err());
+    }
+
+    // Output that fails once `limit` bytes are reached, after writing what fits
+    struct LimitedCursor {
+        cursor: Cursor<Vec<u8>>,
+        limit: u64,
+    }
+
+    impl Write for LimitedCursor {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let room = self.limit.saturating_sub(self.cursor.position()) as usize;
+            if room == 0 {
+                return Err(io::Error::new(io::ErrorKind::StorageFull, "no space left"));
+            }
+            self.cursor.write(&buf[..buf.len().min(room)])
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Seek for LimitedCursor {
+        fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+            self.cursor.seek(position)
+        }
+    }
+
+    impl Truncate for LimitedCursor {
+        fn truncate(&mut self, len: u64) -> io::Result<()> {
+            self.cursor.truncate(len)
+        }
+    }
+
+    #[test]
+    fn test_failed_write_is_cut_off() {
+        let messages = [captured(1.0, "1"), captured(2.0, "2"), captured(3.0, "3")];
+        let output = LimitedCursor { cursor: Cursor::new(Vec::new()), limit: u64::MAX };
+        let mut writer = CaptureWriter::new(output).unwrap();
+        writer.write(&messages[0]).unwrap();
+        let end = writer.writer.cursor.position();
+
+        writer.writer.limit = end + 20;
+        assert!(writer.write(&messages[1]).is_err());
+        assert_eq!(writer.writer.cursor.get_ref().len() as u64, end);
+        assert_eq!(writer.len(), 1);
+
+        writer.writer.limit = u64::MAX;
+        writer.write(&messages[2]).unwrap();
+        let bytes = writer.finish().unwrap().cursor.into_inner();
+
+        let mut reader = CaptureReader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.is_indexed());
+        assert_eq!(reader.len(), 2);
+        assert_eq!(reader.read(1).unwrap(), messages[2]);
+    }
+
+    #[test]
+    fn test_corrupt_footer_falls_back_to_scanning() {
+        let messages = [captured(1.0, "1"), captured(2.0, "2")];
+        let mut bytes = write(&messages).finish().unwrap().into_inner();
+        let footer = bytes.len() - FOOTER_LEN as usize;
+        bytes[footer..footer + 8].copy_from_slice(&(u64::MAX - 3).to_le_bytes());
+
+        let mut reader = CaptureReader::new(Cursor::new(bytes)).unwrap();
+        assert!(!reader.is_indexed());
+        assert_eq!(reader.len(), 2);
+        assert_eq!(reader.read(1).unwrap(), messages[1]);
+    }
+
+    #[test]
+    fn test_record_length_is_bounded_by_the_file() {
+        let messages = [captured(1.0, "1")];
+        let mut bytes = write(&messages).finish().unwrap().into_inner();
+        bytes[HEADER_LEN as usize..HEADER_LEN as usize + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut reader = CaptureReader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.is_indexed());
+        assert!(reader.read(0).is_err());
+    }
+}
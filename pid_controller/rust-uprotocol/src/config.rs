@@ -0,0 +1,911 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use up_rust::UUri;
+use cruise_control_messages::Encoding;
+use sdv_lab_topics as topics;
+
+use crate::acc::AccConfig;
+use crate::control_loop::{LoopMode, VelocityInput};
+use crate::feedforward_controller::FeedForwardController;
+use crate::gain_schedule::GainSchedule;
+use crate::health::HealthConfig;
+use crate::longitudinal_controller::LongitudinalController;
+use crate::mpc_controller::{ModelPredictiveController, PointMassModel};
+use crate::pid_controller::{AntiWindup, DerivativeMode, PIDController, PidGains};
+use crate::recorder::{RecordFormat, RecorderConfig};
+use crate::time_base::{TimeBaseConfig, TimeSource};
+use crate::uprotocol_handler::TopicUris;
+use crate::watchdog::WatchdogConfig;
+
+/// Prefix of the environment variables overriding a setting, e.g. `PID_CONTROLLER_GAINS_KP`.
+pub const ENV_PREFIX: &str = "PID_CONTROLLER_";
+/// Environment variable naming the configuration file.
+pub const ENV_CONFIG_FILE: &str = "PID_CONTROLLER_CONFIG";
+
+/// Configuration of the `pid_controller` binary. It is layered: defaults, then the TOML
+/// file, then `PID_CONTROLLER_<SECTION>_<KEY>` environment variables, then the command line.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ControllerConfig {
+    pub node: NodeConfig,
+    pub transport: TransportConfig,
+    pub topics: TopicConfig,
+    pub controller: ControllerSection,
+    pub gains: GainsConfig,
+    pub limits: LimitsConfig,
+    pub pid: PidConfig,
+    pub feed_forward: FeedForwardConfig,
+    pub mpc: MpcConfig,
+    pub tuning: TuningConfig,
+    pub recording: RecordingConfig,
+    pub actuation: ActuationConfig,
+    pub time_base: TimeBaseSection,
+    pub control_loop: ControlLoopSection,
+    pub watchdog: WatchdogSection,
+    pub acc: AccSection,
+    pub health: HealthSection,
+    pub fleet: FleetSection,
+}
+
+/// uProtocol identity of the controller, also addressing the tuning service.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeConfig {
+    pub authority: String,
+    pub ue_id: u32,
+    pub ue_version_major: u8,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        NodeConfig {
+            authority: topics::PID_CONTROLLER.authority.to_string(),
+            ue_id: topics::UE_ID,
+            ue_version_major: topics::UE_VERSION_MAJOR,
+        }
+    }
+}
+
+/// Zenoh session: peer mode connecting to `router`, or a full Zenoh configuration file.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransportConfig {
+    /// Host of the Zenoh router, connected to on port 7447
+    pub router: Option<String>,
+    /// JSON5 Zenoh configuration file
+    pub zenoh_config: Option<PathBuf>,
+}
+
+/// URIs of the topics used by the controller, by default the ones of the SDV lab topic map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TopicConfig {
+    pub velocity: String,
+    pub clock: String,
+    pub target_speed: String,
+    pub engage: String,
+    pub actuation: String,
+    pub fault: String,
+    pub acc_status: String,
+    pub obstacle: String,
+    pub radar: String,
+}
+
+impl Default for TopicConfig {
+    fn default() -> Self {
+        TopicConfig {
+            velocity: topics::VELOCITY_STATUS.to_string(),
+            clock: topics::CLOCK_STATUS.to_string(),
+            target_speed: topics::TARGET_SPEED.to_string(),
+            engage: topics::ENGAGE.to_string(),
+            actuation: topics::ACTUATION.to_string(),
+            fault: topics::FAULT.to_string(),
+            acc_status: topics::ACC_STATUS.to_string(),
+            obstacle: topics::OBSTACLE_DETECTION.to_string(),
+            radar: topics::RADAR.to_string(),
+        }
+    }
+}
+
+impl TopicConfig {
+    fn entries(&self) -> [(&'static str, &String); 9] {
+        [
+            ("velocity", &self.velocity),
+            ("clock", &self.clock),
+            ("target_speed", &self.target_speed),
+            ("engage", &self.engage),
+            ("actuation", &self.actuation),
+            ("fault", &self.fault),
+            ("acc_status", &self.acc_status),
+            ("obstacle", &self.obstacle),
+            ("radar", &self.radar),
+        ]
+    }
+
+    pub fn uris(&self) -> Result<TopicUris, String> {
+        let parse = |name: &str, uri: &str| {
+            UUri::from_str(uri).map_err(|e| format!("topics.{}: invalid URI '{}': {}", name, uri, e))
+        };
+        Ok(TopicUris {
+            velocity: parse("velocity", &self.velocity)?,
+            clock: parse("clock", &self.clock)?,
+            target_speed: parse("target_speed", &self.target_speed)?,
+            engage: parse("engage", &self.engage)?,
+            actuation: parse("actuation", &self.actuation)?,
+            fault: parse("fault", &self.fault)?,
+            acc_status: parse("acc_status", &self.acc_status)?,
+            obstacle: parse("obstacle", &self.obstacle)?,
+            radar: parse("radar", &self.radar)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControllerKind {
+    #[default]
+    Pid,
+    FeedForward,
+    Mpc,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ControllerSection {
+    /// Longitudinal control strategy
+    pub kind: ControllerKind,
+    /// TOML or YAML table of PID gains by vehicle speed, replacing `[gains]`
+    pub gain_schedule: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GainsConfig {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+}
+
+impl Default for GainsConfig {
+    fn default() -> Self {
+        GainsConfig { kp: 0.125, ki: 0.125 / 8.0, kd: 0.125 / 10.0 }
+    }
+}
+
+/// Saturation of the published acceleration command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    pub output_min: f64,
+    pub output_max: f64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig { output_min: -1.0, output_max: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum AntiWindupMode {
+    None,
+    #[default]
+    Clamping,
+    BackCalculation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DerivativeOn {
+    Error,
+    #[default]
+    Measurement,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PidConfig {
+    pub anti_windup: AntiWindupMode,
+    /// Tracking gain of the back-calculation anti-windup
+    pub tracking_gain: f64,
+    pub derivative_on: DerivativeOn,
+    /// Time constant in seconds of the D-term low-pass filter (0 disables it)
+    pub derivative_filter_tau: f64,
+}
+
+impl Default for PidConfig {
+    fn default() -> Self {
+        PidConfig {
+            anti_windup: AntiWindupMode::Clamping,
+            tracking_gain: 1.0,
+            derivative_on: DerivativeOn::Measurement,
+            derivative_filter_tau: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedForwardConfig {
+    /// Maximum reference acceleration in km/h/s
+    pub max_acceleration: f64,
+    /// Command per km/h/s of reference acceleration
+    pub gain: f64,
+}
+
+impl Default for FeedForwardConfig {
+    fn default() -> Self {
+        FeedForwardConfig { max_acceleration: 5.0, gain: 0.1 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MpcConfig {
+    pub horizon: usize,
+    /// Duration in seconds of one prediction step
+    pub step: f64,
+    pub effort_weight: f64,
+    pub rate_weight: f64,
+    pub observer_gain: f64,
+}
+
+impl Default for MpcConfig {
+    fn default() -> Self {
+        MpcConfig { horizon: 20, step: 0.1, effort_weight: 1.0, rate_weight: 10.0, observer_gain: 0.1 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TuningConfig {
+    /// TTL in milliseconds of the gains status messages
+    pub status_ttl_ms: u32,
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        TuningConfig { status_ttl_ms: 20000 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecordFileFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordingConfig {
+    /// Only keep the summary statistics when disabled
+    pub enabled: bool,
+    pub dir: PathBuf,
+    pub format: RecordFileFormat,
+    /// Rows per file before rotating to a new one
+    pub max_rows: usize,
+    /// Rows buffered in memory before they are written out
+    pub buffer_rows: usize,
+}
+
+impl Default for RecordingConfig {
+    fn default() -> Self {
+        RecordingConfig {
+            enabled: true,
+            dir: PathBuf::from("logs"),
+            format: RecordFileFormat::Csv,
+            max_rows: 100000,
+            buffer_rows: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum PayloadFormat {
+    #[default]
+    Protobuf,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActuationConfig {
+    /// Encoding of the published actuation command
+    pub payload_format: PayloadFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeBaseSource {
+    #[default]
+    Simulation,
+    Wall,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeBaseSection {
+    pub source: TimeBaseSource,
+    /// Largest forward clock step in seconds integrated as-is
+    pub max_clock_step: f64,
+}
+
+impl Default for TimeBaseSection {
+    fn default() -> Self {
+        TimeBaseSection { source: TimeBaseSource::Simulation, max_clock_step: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControlLoopMode {
+    /// A command on every velocity sample
+    #[default]
+    EventDriven,
+    /// A command every `1 / rate_hz` seconds from the latest inputs
+    FixedRate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LoopVelocity {
+    /// The last received sample
+    #[default]
+    Latest,
+    /// The last two samples extrapolated linearly
+    Extrapolated,
+}
+
+/// Highest rate of the fixed-rate loop, well above what the transport delivers
+pub const MAX_RATE_HZ: f64 = 1000.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ControlLoopSection {
+    pub mode: ControlLoopMode,
+    /// Rate of the fixed-rate loop in Hz, at most [`MAX_RATE_HZ`]
+    pub rate_hz: f64,
+    /// Velocity used by the fixed-rate loop between two samples
+    pub velocity: LoopVelocity,
+    /// Longest extrapolation past the last velocity sample in milliseconds
+    pub max_extrapolation_ms: u64,
+}
+
+impl Default for ControlLoopSection {
+    fn default() -> Self {
+        ControlLoopSection {
+            mode: ControlLoopMode::EventDriven,
+            rate_hz: 50.0,
+            velocity: LoopVelocity::Latest,
+            max_extrapolation_ms: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchdogSection {
+    pub enabled: bool,
+    pub velocity_timeout_ms: u64,
+    pub clock_timeout_ms: u64,
+    /// Command published when an input goes stale, in [-1, 1]
+    pub safe_command: f64,
+}
+
+impl Default for WatchdogSection {
+    fn default() -> Self {
+        WatchdogSection { enabled: true, velocity_timeout_ms: 500, clock_timeout_ms: 500, safe_command: 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccSection {
+    pub enabled: bool,
+    pub time_gap: f64,
+    pub standstill_distance: f64,
+    pub distance_gain: f64,
+    pub max_range: f64,
+    pub radar_half_angle: f64,
+    pub target_timeout: f64,
+    pub mode_hysteresis: f64,
+}
+
+impl Default for AccSection {
+    fn default() -> Self {
+        let defaults = AccConfig::default();
+        AccSection {
+            enabled: false,
+            time_gap: defaults.time_gap,
+            standstill_distance: defaults.standstill_distance,
+            distance_gain: defaults.distance_gain,
+            max_range: defaults.max_range,
+            radar_half_angle: defaults.radar_half_angle,
+            target_timeout: defaults.target_timeout,
+            mode_hysteresis: defaults.mode_hysteresis,
+        }
+    }
+}
+
+/// Periodic health status on the `cc_health` topic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthSection {
+    pub enabled: bool,
+    pub interval_ms: u64,
+    /// TTL in milliseconds of each status
+    pub ttl_ms: u32,
+}
+
+impl Default for HealthSection {
+    fn default() -> Self {
+        HealthSection { enabled: true, interval_ms: 1000, ttl_ms: 3000 }
+    }
+}
+
+/// Vehicles controlled by one process, e.g. for platooning with several CARLA `role_name`s.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FleetSection {
+    /// Vehicle ids, each with its own topics derived from `[topics]`, controller, recorder and
+    /// tuning service; empty for a single vehicle on the `[topics]` themselves
+    pub vehicles: Vec<String>,
+}
+
+impl ControllerConfig {
+    /// Layers `file`, the `PID_CONTROLLER_*` variables of `env` and `overrides` (`section.key`
+    /// and value, e.g. from the command line) over the defaults, then validates the result.
+    pub fn load(
+        file: Option<&Path>,
+        env: impl IntoIterator<Item = (String, String)>,
+        overrides: &[(String, String)],
+    ) -> Result<Self, String> {
+        let mut merged = toml::Table::try_from(Self::default()).map_err(|e| e.to_string())?;
+        let known = Self::known_settings();
+
+        if let Some(path) = file {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+            // Deserialize on its own first, so that errors point into the file
+            toml::from_str::<ControllerConfig>(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let table: toml::Table = toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+            for (section, values) in table {
+                if let (Some(toml::Value::Table(target)), toml::Value::Table(values)) = (merged.get_mut(&section), values) {
+                    target.extend(values);
+                }
+            }
+        }
+
+        let mut env: Vec<(String, String)> = env
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX) && name != ENV_CONFIG_FILE)
+            .collect();
+        env.sort();
+        for (name, text) in env {
+            let setting = known
+                .iter()
+                .find(|(section, key, _)| format!("{}{}_{}", ENV_PREFIX, section, key).to_uppercase() == name)
+                .ok_or_else(|| format!("{}: unknown setting", name))?;
+            Self::set(&mut merged, setting, &text).map_err(|e| format!("{}: {}", name, e))?;
+        }
+
+        for (path, text) in overrides {
+            let setting = known
+                .iter()
+                .find(|(section, key, _)| format!("{}.{}", section, key) == *path)
+                .ok_or_else(|| format!("{}: unknown setting", path))?;
+            Self::set(&mut merged, setting, text).map_err(|e| format!("{}: {}", path, e))?;
+        }
+
+        let config: ControllerConfig = toml::Value::Table(merged).try_into().map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// `(section, key, example value)` of every setting, including the optional ones.
+    fn known_settings() -> Vec<(String, String, toml::Value)> {
+        let mut example = Self::default();
+        example.transport.router = Some(String::new());
+        example.transport.zenoh_config = Some(PathBuf::new());
+        example.controller.gain_schedule = Some(PathBuf::new());
+
+        let table = toml::Table::try_from(example).expect("configuration serializes to TOML");
+        let mut settings = Vec::new();
+        for (section, values) in table {
+            if let toml::Value::Table(values) = values {
+                for (key, value) in values {
+                    settings.push((section.clone(), key, value));
+                }
+            }
+        }
+        settings
+    }
+
+    // Parses `text` like the example value of the setting and stores it
+    fn set(merged: &mut toml::Table, (section, key, example): &(String, String, toml::Value), text: &str) -> Result<(), String> {
+        let value = match example {
+            toml::Value::Boolean(_) => match text.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => toml::Value::Boolean(true),
+                "false" | "0" | "no" | "off" => toml::Value::Boolean(false),
+                _ => return Err(format!("expected true or false, got '{}'", text)),
+            },
+            toml::Value::Integer(_) => {
+                toml::Value::Integer(text.parse().map_err(|_| format!("expected an integer, got '{}'", text))?)
+            }
+            toml::Value::Float(_) => {
+                toml::Value::Float(text.parse().map_err(|_| format!("expected a number, got '{}'", text))?)
+            }
+            // Lists are comma-separated, e.g. `PID_CONTROLLER_FLEET_VEHICLES=hero,hero2`
+            toml::Value::Array(_) => toml::Value::Array(
+                text.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| toml::Value::String(item.to_string()))
+                    .collect(),
+            ),
+            _ => toml::Value::String(text.to_string()),
+        };
+        if let Some(toml::Value::Table(values)) = merged.get_mut(section) {
+            values.insert(key.clone(), value);
+        }
+        Ok(())
+    }
+
+    /// Checks every setting and reports all problems at once.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, message: String| {
+            if !ok {
+                errors.push(message);
+            }
+        };
+
+        check(!self.node.authority.is_empty(), "node.authority must not be empty".to_string());
+        check(
+            self.transport.router.is_none() || self.transport.zenoh_config.is_none(),
+            "transport.router and transport.zenoh_config are exclusive".to_string(),
+        );
+        if let Some(path) = &self.transport.zenoh_config {
+            check(path.is_file(), format!("transport.zenoh_config: {} is not a file", path.display()));
+        }
+        for (name, uri) in self.topics.entries() {
+            if let Err(e) = UUri::from_str(uri) {
+                check(false, format!("topics.{}: invalid URI '{}': {}", name, uri, e));
+            }
+        }
+
+        let gains = PidGains { kp: self.gains.kp, ki: self.gains.ki, kd: self.gains.kd, output_limits: None };
+        if let Err(e) = gains.validate() {
+            check(false, format!("gains: {}", e));
+        }
+        if let Some(path) = &self.controller.gain_schedule {
+            check(
+                self.controller.kind != ControllerKind::Mpc,
+                "controller.gain_schedule requires the pid or feed-forward controller".to_string(),
+            );
+            check(path.is_file(), format!("controller.gain_schedule: {} is not a file", path.display()));
+        }
+        let (min, max) = (self.limits.output_min, self.limits.output_max);
+        check(
+            min.is_finite() && max.is_finite() && min < max,
+            format!("limits: output_min must be below output_max (got [{}, {}])", min, max),
+        );
+        check(
+            self.pid.tracking_gain.is_finite() && self.pid.tracking_gain > 0.0,
+            format!("pid.tracking_gain must be positive (got {})", self.pid.tracking_gain),
+        );
+        check(
+            self.pid.derivative_filter_tau.is_finite() && self.pid.derivative_filter_tau >= 0.0,
+            format!("pid.derivative_filter_tau must not be negative (got {})", self.pid.derivative_filter_tau),
+        );
+        check(
+            self.feed_forward.max_acceleration > 0.0,
+            format!("feed_forward.max_acceleration must be positive (got {})", self.feed_forward.max_acceleration),
+        );
+        check(
+            self.feed_forward.gain.is_finite() && self.feed_forward.gain >= 0.0,
+            format!("feed_forward.gain must not be negative (got {})", self.feed_forward.gain),
+        );
+        check(self.mpc.horizon > 0, "mpc.horizon must be at least 1".to_string());
+        check(self.mpc.step > 0.0, format!("mpc.step must be positive (got {})", self.mpc.step));
+        check(
+            self.mpc.effort_weight >= 0.0 && self.mpc.rate_weight >= 0.0,
+            "mpc.effort_weight and mpc.rate_weight must not be negative".to_string(),
+        );
+        check(
+            (0.0..=1.0).contains(&self.mpc.observer_gain),
+            format!("mpc.observer_gain must be between 0 and 1 (got {})", self.mpc.observer_gain),
+        );
+        check(
+            self.recording.max_rows > 0 && self.recording.buffer_rows > 0,
+            "recording.max_rows and recording.buffer_rows must be positive".to_string(),
+        );
+        check(
+            self.time_base.max_clock_step > 0.0,
+            format!("time_base.max_clock_step must be positive (got {})", self.time_base.max_clock_step),
+        );
+        if self.control_loop.mode == ControlLoopMode::FixedRate {
+            check(
+                self.control_loop.rate_hz > 0.0 && self.control_loop.rate_hz <= MAX_RATE_HZ,
+                format!(
+                    "control_loop.rate_hz must be positive and at most {} (got {})",
+                    MAX_RATE_HZ, self.control_loop.rate_hz
+                ),
+            );
+            // The simulation clock only advances on clock ticks, the steps in between would stall
+            check(
+                self.time_base.source == TimeBaseSource::Wall,
+                "control_loop.mode = fixed-rate requires time_base.source = wall".to_string(),
+            );
+        }
+        if self.watchdog.enabled {
+            check(
+                (-1.0..=1.0).contains(&self.watchdog.safe_command),
+                format!("watchdog.safe_command must be within [-1, 1] (got {})", self.watchdog.safe_command),
+            );
+            check(
+                self.watchdog.velocity_timeout_ms > 0 && self.watchdog.clock_timeout_ms > 0,
+                "watchdog timeouts must be positive".to_string(),
+            );
+        }
+        if let Some(acc) = self.acc() {
+            if let Err(e) = acc.validate() {
+                check(false, format!("acc: {}", e));
+            }
+        }
+        for (index, vehicle) in self.fleet.vehicles.iter().enumerate() {
+            check(
+                !vehicle.is_empty() && vehicle.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+                format!("fleet.vehicles: '{}' must only contain letters, digits, '-' and '_'", vehicle),
+            );
+            check(
+                !self.fleet.vehicles[..index].contains(vehicle),
+                format!("fleet.vehicles: '{}' is listed twice", vehicle),
+            );
+        }
+        if self.health.enabled {
+            check(
+                self.health.interval_ms > 0 && self.health.ttl_ms as u64 >= self.health.interval_ms,
+                format!(
+                    "health.ttl_ms must be at least health.interval_ms, which must be positive (got {} and {})",
+                    self.health.ttl_ms, self.health.interval_ms
+                ),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("invalid configuration:\n  - {}", errors.join("\n  - ")))
+        }
+    }
+
+    /// The configuration as TOML, as loaded from a file.
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("configuration serializes to TOML")
+    }
+
+    /// Builds the configured longitudinal controller, loading the gain schedule if any.
+    pub fn build_controller(&self) -> Result<Box<dyn LongitudinalController>, String> {
+        let anti_windup = match self.pid.anti_windup {
+            AntiWindupMode::None => AntiWindup::None,
+            AntiWindupMode::Clamping => AntiWindup::Clamping,
+            AntiWindupMode::BackCalculation => AntiWindup::BackCalculation { tracking_gain: self.pid.tracking_gain },
+        };
+        let derivative_mode = match self.pid.derivative_on {
+            DerivativeOn::Error => DerivativeMode::OnError,
+            DerivativeOn::Measurement => DerivativeMode::OnMeasurement,
+        };
+        let (min, max) = (self.limits.output_min, self.limits.output_max);
+
+        let mut pid = PIDController::new(self.gains.kp, self.gains.ki, self.gains.kd)
+            .with_output_limits(min, max)
+            .with_anti_windup(anti_windup)
+            .with_derivative_mode(derivative_mode)
+            .with_derivative_filter(self.pid.derivative_filter_tau);
+        if let Some(schedule) = self.gain_schedule()? {
+            pid = pid.with_gain_schedule(schedule);
+        }
+
+        Ok(match self.controller.kind {
+            ControllerKind::Pid => Box::new(pid),
+            ControllerKind::FeedForward => Box::new(
+                FeedForwardController::new(pid, self.feed_forward.max_acceleration, self.feed_forward.gain)
+                    .with_output_limits(min, max),
+            ),
+            ControllerKind::Mpc => Box::new(
+                ModelPredictiveController::new(PointMassModel::default(), self.mpc.horizon, self.mpc.step)
+                    .with_weights(self.mpc.effort_weight, self.mpc.rate_weight)
+                    .with_observer_gain(self.mpc.observer_gain)
+                    .with_output_limits(min, max),
+            ),
+        })
+    }
+
+    /// The gain schedule of `controller.gain_schedule`, if any.
+    pub fn gain_schedule(&self) -> Result<Option<GainSchedule>, String> {
+        self.controller.gain_schedule.as_deref().map(GainSchedule::load).transpose()
+    }
+
+    /// Recorder settings, `None` when only the summary is kept.
+    pub fn recorder(&self) -> Option<RecorderConfig> {
+        self.recording.enabled.then(|| RecorderConfig {
+            directory: self.recording.dir.clone(),
+            format: match self.recording.format {
+                RecordFileFormat::Csv => RecordFormat::Csv,
+                RecordFileFormat::Parquet => RecordFormat::Parquet,
+            },
+            buffer_rows: self.recording.buffer_rows,
+            max_rows_per_file: self.recording.max_rows,
+        })
+    }
+
+    pub fn actuation_encoding(&self) -> Encoding {
+        match self.actuation.payload_format {
+            PayloadFormat::Protobuf => Encoding::Protobuf,
+            PayloadFormat::Text => Encoding::Text,
+        }
+    }
+
+    pub fn time_base(&self) -> TimeBaseConfig {
+        TimeBaseConfig {
+            source: match self.time_base.source {
+                TimeBaseSource::Simulation => TimeSource::Simulation,
+                TimeBaseSource::Wall => TimeSource::Wall,
+            },
+            max_step: self.time_base.max_clock_step,
+        }
+    }
+
+    pub fn loop_mode(&self) -> LoopMode {
+        match self.control_loop.mode {
+            ControlLoopMode::EventDriven => LoopMode::EventDriven,
+            ControlLoopMode::FixedRate => LoopMode::FixedRate(Duration::from_secs_f64(1.0 / self.control_loop.rate_hz)),
+        }
+    }
+
+    pub fn velocity_input(&self) -> VelocityInput {
+        match self.control_loop.velocity {
+            LoopVelocity::Latest => VelocityInput::Latest,
+            LoopVelocity::Extrapolated => VelocityInput::Extrapolated {
+                max_horizon: Duration::from_millis(self.control_loop.max_extrapolation_ms),
+            },
+        }
+    }
+
+    pub fn watchdog(&self) -> Option<WatchdogConfig> {
+        self.watchdog.enabled.then(|| WatchdogConfig {
+            velocity_timeout: Duration::from_millis(self.watchdog.velocity_timeout_ms),
+            clock_timeout: Duration::from_millis(self.watchdog.clock_timeout_ms),
+            safe_command: self.watchdog.safe_command,
+            ..WatchdogConfig::default()
+        })
+    }
+
+    pub fn acc(&self) -> Option<AccConfig> {
+        self.acc.enabled.then_some(AccConfig {
+            time_gap: self.acc.time_gap,
+            standstill_distance: self.acc.standstill_distance,
+            distance_gain: self.acc.distance_gain,
+            max_range: self.acc.max_range,
+            radar_half_angle: self.acc.radar_half_angle,
+            target_timeout: self.acc.target_timeout,
+            mode_hysteresis: self.acc.mode_hysteresis,
+        })
+    }
+
+    pub fn health(&self) -> Option<HealthConfig> {
+        self.health.enabled.then(|| HealthConfig {
+            interval: Duration::from_millis(self.health.interval_ms),
+            ttl_ms: self.health.ttl_ms,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let path = std::env::temp_dir().join(format!("pid_controller_config_{}.toml", std::process::id()));
+        std::fs::write(&path, "[gains]\nkp = 0.2\nki = 0.02\n\n[watchdog]\nsafe_command = -0.2\n").unwrap();
+
+        let config = ControllerConfig::load(
+            Some(&path),
+            env(&[("PID_CONTROLLER_GAINS_KI", "0.03"), ("PID_CONTROLLER_FEED_FORWARD_GAIN", "0.5"), ("HOME", "/root")]),
+            &[
+                ("gains.kd".to_string(), "0.01".to_string()),
+                ("watchdog.enabled".to_string(), "false".to_string()),
+                ("fleet.vehicles".to_string(), "hero, hero2".to_string()),
+            ],
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((config.gains.kp, config.gains.ki, config.gains.kd), (0.2, 0.03, 0.01));
+        assert_eq!(config.feed_forward.gain, 0.5);
+        assert_eq!(config.fleet.vehicles, vec!["hero", "hero2"]);
+        assert_eq!(config.watchdog.safe_command, -0.2);
+        assert!(config.watchdog().is_none());
+        assert_eq!(config.limits, LimitsConfig::default());
+
+        // The printed configuration loads back to the same values
+        let printed: ControllerConfig = toml::from_str(&config.to_toml()).unwrap();
+        assert_eq!(printed, config);
+    }
+
+    #[test]
+    fn test_invalid_settings_are_reported() {
+        let load = |vars: &[(&str, &str)]| ControllerConfig::load(None, env(vars), &[]).unwrap_err();
+
+        assert!(load(&[("PID_CONTROLLER_GAINS_KPP", "1")]).contains("PID_CONTROLLER_GAINS_KPP: unknown setting"));
+        assert!(load(&[("PID_CONTROLLER_GAINS_KP", "fast")]).contains("expected a number"));
+        assert!(load(&[("PID_CONTROLLER_CONTROLLER_KIND", "fuzzy")]).contains("unknown variant"));
+
+        // All problems at once
+        let error = load(&[
+            ("PID_CONTROLLER_LIMITS_OUTPUT_MIN", "2"),
+            ("PID_CONTROLLER_TOPICS_VELOCITY", "not a uri"),
+            ("PID_CONTROLLER_TRANSPORT_ROUTER", "localhost"),
+            ("PID_CONTROLLER_TRANSPORT_ZENOH_CONFIG", "/nonexistent/zenoh.json5"),
+        ]);
+        assert!(error.contains("limits: output_min must be below output_max"), "{}", error);
+        assert!(error.contains("topics.velocity: invalid URI"), "{}", error);
+        assert!(error.contains("transport.router and transport.zenoh_config are exclusive"), "{}", error);
+
+        let error = load(&[("PID_CONTROLLER_FLEET_VEHICLES", "hero, hero 2,hero")]);
+        assert!(error.contains("'hero 2' must only contain"), "{}", error);
+        assert!(error.contains("'hero' is listed twice"), "{}", error);
+
+        let error = load(&[("PID_CONTROLLER_CONTROL_LOOP_MODE", "fixed-rate"), ("PID_CONTROLLER_CONTROL_LOOP_RATE_HZ", "0")]);
+        assert!(error.contains("control_loop.rate_hz must be positive"), "{}", error);
+        assert!(error.contains("control_loop.mode = fixed-rate requires time_base.source = wall"), "{}", error);
+
+        // A period that rounds to zero would panic the loop ticker
+        let error = load(&[
+            ("PID_CONTROLLER_CONTROL_LOOP_MODE", "fixed-rate"),
+            ("PID_CONTROLLER_CONTROL_LOOP_RATE_HZ", "1e12"),
+            ("PID_CONTROLLER_TIME_BASE_SOURCE", "wall"),
+        ]);
+        assert!(error.contains("control_loop.rate_hz must be positive and at most 1000"), "{}", error);
+
+        let error = load(&[("PID_CONTROLLER_FEED_FORWARD_GAIN", "-0.1"), ("PID_CONTROLLER_MPC_OBSERVER_GAIN", "1.5")]);
+        assert!(error.contains("feed_forward.gain must not be negative"), "{}", error);
+        assert!(error.contains("mpc.observer_gain must be between 0 and 1"), "{}", error);
+    }
+
+    #[test]
+    fn test_defaults_match_the_topic_map() {
+        let config = ControllerConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.topics.uris().unwrap().actuation, topics::ACTUATION.uri());
+        assert_eq!(config.node.authority, "CruiseControl");
+    }
+}
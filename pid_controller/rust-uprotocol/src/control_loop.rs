@@ -0,0 +1,756 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::future::pending;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use log::{info, debug, error, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::time::{Interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use up_rust::{UMessageBuilder, UPayloadFormat, UTransport, UUri};
+use cruise_control_messages::{ActuationCommand, CruiseControlPayload, Encoding};
+
+use crate::acc::{AccConfig, AccMode, AccStatus, AdaptiveCruise, ObstacleEvent, RadarMeasurement};
+use crate::longitudinal_controller::LongitudinalController;
+use crate::pid_controller::PidTerms;
+use crate::recorder::{RecordRow, RowSender};
+use crate::time_base::{ClockStep, TimeBase, TimeBaseConfig, TimeSource};
+use crate::uprotocol_handler::TopicUris;
+use crate::watchdog::{FaultEvent, Freshness, Input, WatchdogConfig};
+
+// Shortest interval between the two velocity samples the extrapolation slope is taken from
+const MIN_SLOPE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// When the control task computes a new command.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopMode {
+    /// On every velocity sample, at the rate of the vehicle
+    EventDriven,
+    /// Every period, from the latest inputs
+    FixedRate(Duration),
+}
+
+/// Velocity used by the fixed-rate loop between two samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VelocityInput {
+    /// The last received sample
+    Latest,
+    /// The last two samples extrapolated linearly, over at most `max_horizon` past the last one
+    Extrapolated { max_horizon: Duration },
+}
+
+/// Settings of the control task.
+#[derive(Debug, Clone, Copy)]
+pub struct LoopConfig {
+    pub mode: LoopMode,
+    pub velocity_input: VelocityInput,
+    pub actuation_encoding: Encoding,
+    pub time_base: TimeBaseConfig,
+    pub watchdog: Option<WatchdogConfig>,
+    pub acc: Option<AccConfig>,
+}
+
+impl Default for LoopConfig {
+    fn default() -> Self {
+        LoopConfig {
+            mode: LoopMode::EventDriven,
+            velocity_input: VelocityInput::Latest,
+            actuation_encoding: Encoding::Protobuf,
+            time_base: TimeBaseConfig::default(),
+            watchdog: None,
+            acc: None,
+        }
+    }
+}
+
+/// Decoded input of the control task, forwarded by the uProtocol listeners.
+#[derive(Debug)]
+pub enum ControlInput {
+    /// Simulation clock, with the receive time and the TTL of its message for the watchdog
+    Clock { elapsed_seconds: f64, received_at: Instant, ttl: Option<Duration> },
+    Velocity { velocity_kmh: f64, received_at: Instant, ttl: Option<Duration> },
+    TargetSpeed(f64),
+    Engage(bool),
+    Obstacle(ObstacleEvent),
+    Radar(RadarMeasurement),
+    /// Answered once every input sent before it has been processed
+    Sync(oneshot::Sender<()>),
+}
+
+/// Timing of the fixed-rate loop, reported in the health status and the results summary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoopTiming {
+    /// Period of the loop in seconds
+    pub period: f64,
+    pub steps: u64,
+    /// Steps that finished after the start of the next period, and periods without a step
+    pub missed_deadlines: u64,
+    /// Delay in seconds of the steps behind their scheduled start
+    pub mean_jitter: f64,
+    pub max_jitter: f64,
+    /// Longest step in seconds
+    pub max_step_duration: f64,
+}
+
+// Accumulates the timing of the fixed-rate loop step after step
+#[derive(Debug, Clone)]
+struct LoopTimer {
+    period: Duration,
+    previous: Option<Instant>,
+    // The previous step overran its period, a deadline already counted
+    overran: bool,
+    sum_jitter: f64,
+    timing: LoopTiming,
+}
+
+impl LoopTimer {
+    fn new(period: Duration) -> Self {
+        LoopTimer {
+            period,
+            previous: None,
+            overran: false,
+            sum_jitter: 0.0,
+            timing: LoopTiming { period: period.as_secs_f64(), ..LoopTiming::default() },
+        }
+    }
+
+    // Records a step scheduled at `scheduled` that ran from `started` to `finished`, and
+    // returns the number of deadlines it missed
+    fn record(&mut self, scheduled: Instant, started: Instant, finished: Instant) -> u64 {
+        let mut missed = 0;
+        if let Some(previous) = self.previous {
+            // The ticker skips the periods a late step overran, the first of them was counted
+            // with the overrun
+            let periods = scheduled.saturating_duration_since(previous).as_secs_f64() / self.period.as_secs_f64();
+            missed += (periods.round() as u64).saturating_sub(1).saturating_sub(self.overran as u64);
+        }
+        self.overran = finished > scheduled + self.period;
+        if self.overran {
+            missed += 1;
+        }
+        self.previous = Some(scheduled);
+
+        let jitter = started.saturating_duration_since(scheduled).as_secs_f64();
+        let timing = &mut self.timing;
+        timing.steps += 1;
+        timing.missed_deadlines += missed;
+        self.sum_jitter += jitter;
+        timing.mean_jitter = self.sum_jitter / timing.steps as f64;
+        timing.max_jitter = timing.max_jitter.max(jitter);
+        timing.max_step_duration = timing.max_step_duration.max(finished.saturating_duration_since(started).as_secs_f64());
+        missed
+    }
+}
+
+/// Snapshot of the control task state, published on a watch channel after every change.
+#[derive(Debug, Clone)]
+pub struct ControllerState {
+    pub current_velocity: f64,
+    /// Set speed received on the target speed topic
+    pub desired_velocity: f64,
+    /// Speed tracked by the last command: the ACC reference while following a lead vehicle
+    pub reference_speed: Option<f64>,
+    /// Last received simulation clock
+    pub clock_time: f64,
+    pub engaged: bool,
+    /// Last published actuation command and when it was published
+    pub last_command: Option<(f64, Instant)>,
+    /// P, I and D contributions to the last command, for controllers with PID terms
+    pub terms: Option<PidTerms>,
+    pub freshness: Freshness,
+    /// The watchdog fell back to the safe command and the inputs are not all fresh yet
+    pub watchdog_tripped: bool,
+    /// The last controller computation failed
+    pub controller_error: bool,
+    /// Timing of the fixed-rate loop, `None` for the event-driven one
+    pub timing: Option<LoopTiming>,
+}
+
+impl ControllerState {
+    pub fn new(freshness: Freshness) -> Self {
+        ControllerState {
+            current_velocity: 0.0,
+            desired_velocity: 0.0,
+            reference_speed: None,
+            clock_time: 0.0,
+            engaged: false,
+            last_command: None,
+            terms: None,
+            freshness,
+            watchdog_tripped: false,
+            controller_error: false,
+            timing: None,
+        }
+    }
+}
+
+/// The control task: owns the controller state, processes the inputs one after the other and
+/// publishes the actuation command, faults and ACC status.
+pub struct ControlLoop<C: LongitudinalController> {
+    controller: Arc<Mutex<C>>,
+    transport: Arc<dyn UTransport>,
+    topics: TopicUris,
+    config: LoopConfig,
+    time_base: TimeBase,
+    acc: Option<AdaptiveCruise>,
+    recorder: RowSender,
+    state: ControllerState,
+    state_tx: watch::Sender<ControllerState>,
+    // Inputs of the raised stale-input fault, cleared once they are all fresh again
+    fault_inputs: Vec<Input>,
+    // Previous and last velocity samples with their receive time, for the extrapolation; the
+    // previous one is at least `MIN_SLOPE_INTERVAL` older than the last one
+    velocity_samples: [Option<(f64, Instant)>; 2],
+    timer: Option<LoopTimer>,
+    // Log prefix naming the vehicle of a fleet, empty for a single vehicle
+    tag: String,
+}
+
+impl<C: LongitudinalController> ControlLoop<C> {
+    pub fn new(
+        controller: Arc<Mutex<C>>,
+        transport: Arc<dyn UTransport>,
+        topics: TopicUris,
+        config: LoopConfig,
+        recorder: RowSender,
+        state_tx: watch::Sender<ControllerState>,
+    ) -> Self {
+        let mut freshness = Freshness::new(&config.watchdog.unwrap_or_default());
+        if config.time_base.source == TimeSource::Wall {
+            // The clock topic is not needed when the controller runs on the wall clock
+            freshness.set_required(Input::Clock, false);
+        }
+        let mut state = ControllerState::new(freshness);
+        let timer = match config.mode {
+            LoopMode::FixedRate(period) => Some(LoopTimer::new(period)),
+            LoopMode::EventDriven => None,
+        };
+        state.timing = timer.as_ref().map(|timer| timer.timing);
+        state_tx.send_replace(state.clone());
+
+        ControlLoop {
+            controller,
+            transport,
+            topics,
+            config,
+            time_base: TimeBase::new(config.time_base),
+            acc: config.acc.map(AdaptiveCruise::new),
+            recorder,
+            state,
+            state_tx,
+            fault_inputs: Vec::new(),
+            velocity_samples: [None; 2],
+            timer,
+            tag: String::new(),
+        }
+    }
+
+    /// Prefixes the log messages with `vehicle_id`, to tell the vehicles of a fleet apart.
+    pub fn with_vehicle(mut self, vehicle_id: &str) -> Self {
+        self.tag = format!("[{}] ", vehicle_id);
+        self
+    }
+
+    /// Processes `inputs` until `shutdown` is cancelled, then disengages and publishes a zero
+    /// actuation command.
+    pub async fn run(mut self, mut inputs: mpsc::Receiver<ControlInput>, shutdown: CancellationToken) {
+        let mut watchdog = self.config.watchdog.map(|config| Self::ticker(config.check_interval, MissedTickBehavior::Delay));
+        // Fixed-rate steps stay on the grid of the period, a late step drops the periods it overran
+        let mut period = match self.config.mode {
+            LoopMode::FixedRate(period) => Some(Self::ticker(period, MissedTickBehavior::Skip)),
+            LoopMode::EventDriven => None,
+        };
+
+        if let Some(config) = self.config.watchdog {
+            info!(
+                "{}Input watchdog started [velocity timeout: {} ms, clock timeout: {} ms, safe command: {}]",
+                self.tag,
+                config.velocity_timeout.as_millis(),
+                config.clock_timeout.as_millis(),
+                config.safe_command
+            );
+        }
+        match self.config.mode {
+            LoopMode::EventDriven => info!("{}Control loop started [event-driven]", self.tag),
+            LoopMode::FixedRate(period) => {
+                let velocity = match self.config.velocity_input {
+                    VelocityInput::Latest => "latest".to_string(),
+                    VelocityInput::Extrapolated { max_horizon } => format!("extrapolated up to {} ms", max_horizon.as_millis()),
+                };
+                info!("{}Control loop started [fixed rate: {:.1} Hz, velocity: {}]", self.tag, 1.0 / period.as_secs_f64(), velocity);
+            }
+        }
+
+        loop {
+            // Shutdown first, then the timers, so that a burst of inputs does not delay the ticks
+            tokio::select! {
+                biased;
+                _ = shutdown.cancelled() => break,
+                scheduled = Self::tick(&mut period) => self.timed_step(scheduled).await,
+                _ = Self::tick(&mut watchdog) => self.check_inputs().await,
+                input = inputs.recv() => match input {
+                    Some(input) => self.handle(input).await,
+                    None => break,
+                },
+            }
+            self.publish_state();
+        }
+
+        if self.state.engaged {
+            self.deactivate();
+        }
+        self.publish_actuation(0.0).await;
+        self.publish_state();
+        info!("{}Control loop stopped, zero actuation command published", self.tag);
+    }
+
+    fn ticker(period: Duration, behavior: MissedTickBehavior) -> Interval {
+        let mut ticker = tokio::time::interval(period);
+        ticker.set_missed_tick_behavior(behavior);
+        ticker
+    }
+
+    // Scheduled time of the next tick of an optional timer, never for a missing one
+    async fn tick(ticker: &mut Option<Interval>) -> Instant {
+        match ticker {
+            Some(ticker) => ticker.tick().await.into_std(),
+            None => pending().await,
+        }
+    }
+
+    // Fixed-rate step, timed against its schedule
+    async fn timed_step(&mut self, scheduled: Instant) {
+        let started = Instant::now();
+        self.control_step().await;
+        let finished = Instant::now();
+
+        if let Some(timer) = &mut self.timer {
+            let missed = timer.record(scheduled, started, finished);
+            if missed > 0 {
+                debug!(
+                    "Control step missed {} deadline(s), started {:.1} ms late and took {:.1} ms",
+                    missed,
+                    started.saturating_duration_since(scheduled).as_secs_f64() * 1000.0,
+                    finished.saturating_duration_since(started).as_secs_f64() * 1000.0
+                );
+            }
+            self.state.timing = Some(timer.timing);
+        }
+    }
+
+    // Velocity for a control step at `now`: the last sample, or its extrapolation in the
+    // fixed-rate loop
+    fn velocity_at(&self, now: Instant) -> f64 {
+        let current = self.state.current_velocity;
+        let (LoopMode::FixedRate(_), VelocityInput::Extrapolated { max_horizon }) = (self.config.mode, self.config.velocity_input) else {
+            return current;
+        };
+        let [Some((previous, previous_at)), Some((last, last_at))] = self.velocity_samples else {
+            return current;
+        };
+        let interval = last_at.saturating_duration_since(previous_at);
+        if interval < MIN_SLOPE_INTERVAL {
+            return current;
+        }
+        let horizon = now.saturating_duration_since(last_at).min(max_horizon).as_secs_f64();
+        (last + (last - previous) / interval.as_secs_f64() * horizon).max(0.0)
+    }
+
+    // Keeps the sample as the last one; a sample bunched with the last one replaces it, so
+    // that jitter in the delivery does not steepen the slope
+    fn push_velocity_sample(&mut self, velocity_kmh: f64, received_at: Instant) {
+        self.velocity_samples = match self.velocity_samples {
+            [Some(previous), Some((_, last_at))] if received_at.saturating_duration_since(last_at) < MIN_SLOPE_INTERVAL => {
+                [Some(previous), Some((velocity_kmh, received_at))]
+            }
+            [_, last] => [last, Some((velocity_kmh, received_at))],
+        };
+    }
+
+    fn publish_state(&self) {
+        self.state_tx.send_replace(self.state.clone());
+    }
+
+    async fn handle(&mut self, input: ControlInput) {
+        match input {
+            ControlInput::Clock { elapsed_seconds, received_at, ttl } => {
+                self.state.clock_time = elapsed_seconds;
+                self.state.freshness.received(Input::Clock, received_at, ttl);
+                debug!("Received current clock '{:.4}' seconds", elapsed_seconds);
+            }
+            ControlInput::Velocity { velocity_kmh, received_at, ttl } => {
+                self.state.current_velocity = velocity_kmh;
+                self.state.freshness.received(Input::Velocity, received_at, ttl);
+                self.push_velocity_sample(velocity_kmh, received_at);
+                debug!("Received current velocity '{:.2}'", velocity_kmh);
+                if self.config.mode == LoopMode::EventDriven {
+                    self.control_step().await;
+                }
+            }
+            ControlInput::TargetSpeed(speed) => {
+                self.state.desired_velocity = speed;
+                info!("{}Received desired velocity '{:.2}'", self.tag, speed);
+            }
+            ControlInput::Engage(engage) => self.engage(engage),
+            ControlInput::Obstacle(event) => {
+                let time = self.time_base.sample_time(self.state.clock_time);
+                if let Some(acc) = &mut self.acc {
+                    debug!("Obstacle at {:.2} m", event.distance);
+                    acc.tracker().obstacle(&event, time);
+                }
+            }
+            ControlInput::Radar(measurement) => {
+                let time = self.time_base.sample_time(self.state.clock_time);
+                if let Some(acc) = &mut self.acc {
+                    acc.tracker().radar(&measurement, time);
+                }
+            }
+            ControlInput::Sync(reply) => {
+                self.publish_state();
+                let _ = reply.send(());
+            }
+        }
+    }
+
+    fn engage(&mut self, enable: bool) {
+        info!("{}Received engage status: {}", self.tag, enable as u8);
+
+        // Only refuse to engage on stale inputs if the watchdog would disengage right away
+        let stale = match self.config.watchdog {
+            Some(_) if enable && !self.state.engaged => self.state.freshness.stale_inputs(Instant::now()),
+            _ => Vec::new(),
+        };
+
+        if !stale.is_empty() {
+            let names: Vec<String> = stale.iter().map(|input| input.to_string()).collect();
+            warn!("{}Engage refused, stale input ({})", self.tag, names.join(", "));
+        } else if enable && !self.state.engaged {
+            self.activate();
+        } else if !enable && self.state.engaged {
+            self.deactivate();
+        }
+    }
+
+    fn activate(&mut self) {
+        self.state.engaged = true;
+        self.controller.lock().unwrap().reset();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        info!("{}PID controller ACTIVATED at {}", self.tag, timestamp);
+    }
+
+    fn deactivate(&mut self) {
+        self.state.engaged = false;
+        self.controller.lock().unwrap().reset();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        info!("{}PID controller DEACTIVATED at {}", self.tag, timestamp);
+    }
+
+    // Computes and publishes the command from the latest inputs
+    async fn control_step(&mut self) {
+        let (desired_vel, current_vel) = (self.state.desired_velocity, self.velocity_at(Instant::now()));
+        let curr_time = self.time_base.sample_time(self.state.clock_time);
+
+        let mut row = RecordRow {
+            wall_time: wall_time(),
+            time: curr_time,
+            desired_velocity: desired_vel,
+            current_velocity: current_vel,
+            acceleration: f64::NAN,
+            proportional: f64::NAN,
+            integral: f64::NAN,
+            derivative: f64::NAN,
+            engaged: self.state.engaged,
+            following: false,
+        };
+
+        if !self.state.engaged {
+            // Engaging starts from a reset controller, so the time base starts over as well
+            self.time_base.restart();
+            self.state.reference_speed = None;
+            if let Some(acc) = &mut self.acc {
+                let (status, changed) = acc.standby(desired_vel, row.wall_time);
+                if changed {
+                    self.publish_acc_status(&status).await;
+                }
+            }
+            self.record(row);
+            return;
+        }
+
+        let step = self.time_base.step(curr_time);
+
+        // With ACC, track the lower of the set speed and the spacing command
+        let desired_vel = match &mut self.acc {
+            Some(acc) => {
+                let (status, changed) = acc.update(desired_vel, current_vel, curr_time, row.wall_time);
+                if changed {
+                    info!("{}ACC mode: {} (reference {:.2} km/h)", self.tag, status.mode, status.reference_speed);
+                    self.publish_acc_status(&status).await;
+                }
+                row.desired_velocity = status.reference_speed;
+                row.following = status.mode == AccMode::Spacing;
+                status.reference_speed
+            }
+            None => desired_vel,
+        };
+
+        // Compute acceleration using the longitudinal controller
+        let (acceleration, terms) = {
+            let mut pid = self.controller.lock().unwrap();
+            match step {
+                ClockStep::Stalled => {
+                    // Keep the last command in effect until the clock moves again
+                    debug!("Clock did not advance from {:.4} s, holding the last command", curr_time);
+                    return;
+                }
+                ClockStep::Backward { from, to } => {
+                    warn!("{}Clock jumped back from {:.4} s to {:.4} s, resetting the controller", self.tag, from, to);
+                    pid.reset();
+                }
+                ClockStep::Gap(delta) => {
+                    warn!("{}Clock jumped {:.4} s ahead, holding the integrator", self.tag, delta);
+                    pid.hold();
+                }
+                ClockStep::Advance(delta) => debug!("Delta time: {} seconds", delta),
+                ClockStep::Start => {}
+            }
+            match pid.compute(desired_vel, current_vel, curr_time) {
+                Ok(acc) => (acc, pid.terms()),
+                Err(e) => {
+                    error!("{}Controller computation failed: {}", self.tag, e);
+                    self.state.controller_error = true;
+                    return;
+                }
+            }
+        };
+        if let Some(terms) = terms {
+            row.proportional = terms.proportional;
+            row.integral = terms.integral;
+            row.derivative = terms.derivative;
+        }
+
+        // Create and publish uProtocol message
+        self.publish_actuation(acceleration).await;
+        self.state.terms = terms;
+        self.state.reference_speed = Some(desired_vel);
+        self.state.controller_error = false;
+
+        // Store results for later analysis
+        row.acceleration = acceleration;
+        self.record(row);
+    }
+
+    // Watchdog tick: falls back to the safe command when an input went stale while engaged
+    async fn check_inputs(&mut self) {
+        let Some(config) = self.config.watchdog else {
+            return;
+        };
+        let now = Instant::now();
+        let stale = self.state.freshness.stale_inputs(now);
+
+        if self.state.engaged && !stale.is_empty() {
+            let names: Vec<String> = stale.iter().map(|input| input.to_string()).collect();
+            warn!("{}Stale input ({}), falling back to safe command {}", self.tag, names.join(", "), config.safe_command);
+
+            self.deactivate();
+            self.publish_actuation(config.safe_command).await;
+            self.state.terms = None;
+            self.state.watchdog_tripped = true;
+
+            let event = FaultEvent::stale_input(&self.state.freshness, &stale, now, true, config.safe_command, wall_time());
+            self.publish_fault(&event).await;
+            self.fault_inputs = stale;
+        } else if !self.fault_inputs.is_empty() && stale.is_empty() {
+            info!("{}Inputs are fresh again, stale input fault cleared", self.tag);
+            let event = FaultEvent::stale_input(&self.state.freshness, &self.fault_inputs, now, false, config.safe_command, wall_time());
+            self.publish_fault(&event).await;
+            self.state.watchdog_tripped = false;
+            self.fault_inputs.clear();
+        }
+    }
+
+    async fn publish_actuation(&mut self, acceleration: f64) {
+        publish_actuation(&self.transport, self.topics.actuation.clone(), self.config.actuation_encoding, acceleration).await;
+        self.state.last_command = Some((acceleration, Instant::now()));
+    }
+
+    async fn publish_fault(&self, event: &FaultEvent) {
+        let message = UMessageBuilder::publish(self.topics.fault.clone())
+            .build_with_payload(serde_json::to_vec(event).unwrap(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+            .unwrap();
+
+        if let Err(e) = self.transport.send(message).await {
+            error!("Failed to publish fault event: {}", e);
+        }
+    }
+
+    async fn publish_acc_status(&self, status: &AccStatus) {
+        let message = UMessageBuilder::publish(self.topics.acc_status.clone())
+            .build_with_payload(serde_json::to_vec(status).unwrap(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+            .unwrap();
+
+        if let Err(e) = self.transport.send(message).await {
+            error!("Failed to publish ACC status: {}", e);
+        }
+    }
+
+    fn record(&self, row: RecordRow) {
+        self.recorder.send(row);
+    }
+}
+
+pub(crate) async fn publish_actuation(
+    transport: &Arc<dyn UTransport>,
+    actuation_uri: UUri,
+    actuation_encoding: Encoding,
+    acceleration: f64,
+) {
+    let (payload, format) = ActuationCommand::from_value(acceleration).encode(actuation_encoding);
+    let message = UMessageBuilder::publish(actuation_uri)
+        .build_with_payload(payload, format)
+        .unwrap();
+
+    if let Err(e) = transport.send(message).await {
+        error!("Failed to publish acceleration: {}", e);
+    } else {
+        debug!("Publishing Acceleration: {}", acceleration);
+    }
+}
+
+pub(crate) fn wall_time() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loopback_transport::LoopbackTransport;
+    use crate::pid_controller::PIDController;
+    use crate::recorder::{Recorder, RecorderThread};
+
+    #[test]
+    fn test_loop_timer_counts_jitter_and_missed_deadlines() {
+        let period = Duration::from_millis(20);
+        let ms = |ms: u64| Duration::from_millis(ms);
+        let start = Instant::now();
+        let mut timer = LoopTimer::new(period);
+
+        // On time, then 2 ms late
+        assert_eq!(timer.record(start, start, start + ms(1)), 0);
+        assert_eq!(timer.record(start + ms(20), start + ms(22), start + ms(23)), 0);
+        assert_eq!(timer.timing.max_jitter, 0.002);
+        assert_eq!(timer.timing.mean_jitter, 0.001);
+
+        // A 30 ms step misses its deadline, the period it skips is the same miss
+        assert_eq!(timer.record(start + ms(40), start + ms(40), start + ms(70)), 1);
+        assert_eq!(timer.record(start + ms(80), start + ms(80), start + ms(81)), 0);
+        assert_eq!(timer.timing.steps, 4);
+        assert_eq!(timer.timing.missed_deadlines, 1);
+
+        // A 50 ms step misses two deadlines, a tick lost elsewhere counts once
+        assert_eq!(timer.record(start + ms(100), start + ms(100), start + ms(150)), 1);
+        assert_eq!(timer.record(start + ms(160), start + ms(160), start + ms(161)), 1);
+        assert_eq!(timer.record(start + ms(200), start + ms(200), start + ms(201)), 1);
+        assert_eq!(timer.timing.missed_deadlines, 4);
+        assert_eq!(timer.timing.max_step_duration, 0.05);
+        assert_eq!(timer.timing.period, 0.02);
+    }
+
+    #[test]
+    fn test_velocity_is_extrapolated_up_to_the_horizon() {
+        let config = LoopConfig {
+            mode: LoopMode::FixedRate(Duration::from_millis(20)),
+            velocity_input: VelocityInput::Extrapolated { max_horizon: Duration::from_millis(100) },
+            ..LoopConfig::default()
+        };
+        let (state_tx, _state) = watch::channel(ControllerState::new(Freshness::new(&WatchdogConfig::default())));
+        let mut control = ControlLoop::new(
+            Arc::new(Mutex::new(PIDController::new(0.1, 0.0, 0.0))),
+            Arc::new(LoopbackTransport::new()),
+            TopicUris::default(),
+            config,
+            RecorderThread::spawn(Arc::new(Mutex::new(Recorder::summary_only()))).sender(),
+            state_tx,
+        );
+
+        // A single sample is held
+        let start = Instant::now();
+        control.state.current_velocity = 30.0;
+        control.velocity_samples = [None, Some((30.0, start))];
+        assert_eq!(control.velocity_at(start + Duration::from_millis(50)), 30.0);
+
+        // 1 km/h in 50 ms, extrapolated over at most 100 ms
+        let last = start + Duration::from_millis(50);
+        control.state.current_velocity = 31.0;
+        control.velocity_samples = [Some((30.0, start)), Some((31.0, last))];
+        assert!((control.velocity_at(last + Duration::from_millis(25)) - 31.5).abs() < 1e-9);
+        assert!((control.velocity_at(last + Duration::from_millis(500)) - 33.0).abs() < 1e-9);
+
+        // Decelerating to a stop does not extrapolate below 0 km/h
+        control.state.current_velocity = 1.0;
+        control.velocity_samples = [Some((3.0, start)), Some((1.0, last))];
+        assert_eq!(control.velocity_at(last + Duration::from_millis(100)), 0.0);
+
+        control.config.velocity_input = VelocityInput::Latest;
+        assert_eq!(control.velocity_at(last + Duration::from_millis(25)), 1.0);
+    }
+
+    #[test]
+    fn test_bunched_velocity_samples_keep_the_slope() {
+        let config = LoopConfig {
+            mode: LoopMode::FixedRate(Duration::from_millis(20)),
+            velocity_input: VelocityInput::Extrapolated { max_horizon: Duration::from_millis(100) },
+            ..LoopConfig::default()
+        };
+        let (state_tx, _state) = watch::channel(ControllerState::new(Freshness::new(&WatchdogConfig::default())));
+        let mut control = ControlLoop::new(
+            Arc::new(Mutex::new(PIDController::new(0.1, 0.0, 0.0))),
+            Arc::new(LoopbackTransport::new()),
+            TopicUris::default(),
+            config,
+            RecorderThread::spawn(Arc::new(Mutex::new(Recorder::summary_only()))).sender(),
+            state_tx,
+        );
+
+        // Samples 99 ms apart, the last one delivered 0.1 ms after the previous one
+        let start = Instant::now();
+        let late = start + Duration::from_millis(99);
+        let last = late + Duration::from_micros(100);
+        control.push_velocity_sample(30.0, start);
+        control.push_velocity_sample(31.0, late);
+        control.push_velocity_sample(31.1, last);
+        control.state.current_velocity = 31.1;
+        assert_eq!(control.velocity_samples, [Some((30.0, start)), Some((31.1, last))]);
+
+        let velocity = control.velocity_at(last + Duration::from_millis(20));
+        assert!((velocity - 31.1).abs() < 0.5, "extrapolated to {}", velocity);
+
+        // Bunched samples without an older one are not extrapolated
+        control.velocity_samples = [None, None];
+        control.push_velocity_sample(30.0, late);
+        control.push_velocity_sample(31.0, last);
+        control.state.current_velocity = 31.0;
+        assert_eq!(control.velocity_at(last + Duration::from_millis(20)), 31.0);
+    }
+}
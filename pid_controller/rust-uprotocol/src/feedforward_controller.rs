@@ -0,0 +1,175 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::longitudinal_controller::LongitudinalController;
+use crate::pid_controller::{assert_output_limits, PIDController, PidGains, PidTerms};
+
+/// PID feedback on a rate-limited reference plus feed-forward of the reference acceleration.
+///
+/// Setpoint steps are turned into a ramp limited to `max_acceleration` (km/h/s). The PID tracks
+/// the ramp while `feedforward_gain` converts its slope into the command needed to follow it,
+/// so the feedback part only has to correct the model error.
+///
+/// The output limits apply to the combined command: the PID is saturated to the room left by
+/// the feed-forward term, so that its anti-windup sees the actual saturation.
+pub struct FeedForwardController {
+    pid: PIDController,
+    max_acceleration: f64,
+    feedforward_gain: f64,
+    output_limits: Option<(f64, f64)>,
+    reference_velocity: Option<f64>,
+    previous_time: f64,
+}
+
+impl FeedForwardController {
+    pub fn new(pid: PIDController, max_acceleration: f64, feedforward_gain: f64) -> Self {
+        FeedForwardController {
+            pid,
+            max_acceleration,
+            feedforward_gain,
+            output_limits: None,
+            reference_velocity: None,
+            previous_time: 0.0,
+        }
+    }
+
+    /// Saturates the combined feedback and feed-forward command to `[min, max]`.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is not below `max`, or either is NaN.
+    pub fn with_output_limits(mut self, min: f64, max: f64) -> Self {
+        assert_output_limits(min, max);
+        self.output_limits = Some((min, max));
+        self
+    }
+
+    fn limit_feedback(&mut self, feedforward: f64) {
+        let limits = self.output_limits.map(|(min, max)| (min - feedforward, max - feedforward));
+        self.pid.set_output_limits(limits);
+    }
+}
+
+impl LongitudinalController for FeedForwardController {
+    fn compute(&mut self, desired_velocity: f64, current_velocity: f64, current_time: f64) -> Result<f64, String> {
+        let Some(reference) = self.reference_velocity else {
+            // Start the ramp from the measured speed so engaging does not cause a jump
+            self.reference_velocity = Some(current_velocity);
+            self.previous_time = current_time;
+            self.limit_feedback(0.0);
+            return self.pid.compute(current_velocity, current_velocity, current_time);
+        };
+
+        let delta_time = current_time - self.previous_time;
+        if delta_time <= 0.0 {
+            return Err("delta_time must be positive and higher than 0.".to_string());
+        }
+        self.previous_time = current_time;
+
+        let target_acceleration = ((desired_velocity - reference) / delta_time)
+            .clamp(-self.max_acceleration, self.max_acceleration);
+        let reference = reference + target_acceleration * delta_time;
+        self.reference_velocity = Some(reference);
+
+        let feedforward = self.feedforward_gain * target_acceleration;
+        self.limit_feedback(feedforward);
+        let feedback = self.pid.compute(reference, current_velocity, current_time)?;
+        Ok(feedback + feedforward)
+    }
+
+    fn reset(&mut self) {
+        self.pid.reset();
+        self.reference_velocity = None;
+        self.previous_time = 0.0;
+    }
+
+    fn hold(&mut self) {
+        // The ramp restarts from the measured speed, the PID keeps its integrator
+        self.pid.hold();
+        self.reference_velocity = None;
+    }
+
+    fn gains(&self) -> Option<PidGains> {
+        Some(PidGains {
+            output_limits: self.output_limits,
+            ..self.pid.gains()
+        })
+    }
+
+    fn set_gains(&mut self, gains: PidGains) -> Result<(), String> {
+        gains.validate()?;
+        // The PID limits follow from these on the next computation
+        self.pid.set_gains(PidGains { output_limits: None, ..gains })?;
+        self.output_limits = gains.output_limits;
+        Ok(())
+    }
+
+    fn terms(&self) -> Option<PidTerms> {
+        Some(self.pid.terms())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pid_controller::AntiWindup;
+
+    const DT: f64 = 0.05;
+
+    #[test]
+    fn test_reference_is_rate_limited() {
+        // Pure feed-forward: the command is proportional to the reference slope
+        let mut controller = FeedForwardController::new(PIDController::new(0.0, 0.0, 0.0), 2.0, 0.1);
+        controller.compute(50.0, 0.0, DT).unwrap();
+
+        let command = controller.compute(50.0, 0.0, 2.0 * DT).unwrap();
+
+        assert!((command - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tracks_setpoint_on_plant() {
+        // dv/dt = 10 * u - 0.02 * v, in km/h/s
+        let mut controller = FeedForwardController::new(PIDController::new(0.1, 0.02, 0.0), 5.0, 0.1)
+            .with_output_limits(-1.0, 1.0);
+        let mut velocity = 30.0;
+        for i in 1..=2000 {
+            let command = controller.compute(60.0, velocity, i as f64 * DT).unwrap();
+            velocity += (10.0 * command - 0.02 * velocity) * DT;
+        }
+
+        assert!((velocity - 60.0).abs() < 0.5, "velocity {}", velocity);
+    }
+
+    #[test]
+    fn test_feedback_saturates_with_the_feed_forward() {
+        // Feedback 0.905 and feed-forward 0.2 exceed the limit, although the feedback alone does not
+        let pid = PIDController::new(9.0, 1.0, 0.0).with_anti_windup(AntiWindup::Clamping);
+        let mut controller = FeedForwardController::new(pid, 2.0, 0.1).with_output_limits(-1.0, 1.0);
+        controller.compute(50.0, 0.0, DT).unwrap();
+
+        let command = controller.compute(50.0, 0.0, 2.0 * DT).unwrap();
+
+        assert!((command - 1.0).abs() < 1e-9);
+        assert_eq!(controller.terms().unwrap().integral, 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "output limits must satisfy min < max")]
+    fn test_inverted_output_limits_are_rejected() {
+        let _ = FeedForwardController::new(PIDController::new(1.0, 0.0, 0.0), 2.0, 0.1).with_output_limits(1.0, 1.0);
+    }
+}
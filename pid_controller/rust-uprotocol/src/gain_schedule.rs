@@ -0,0 +1,168 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::path::Path;
+use serde::Deserialize;
+
+use crate::pid_controller::PidGains;
+
+/// PID gains at one vehicle speed of a [`GainSchedule`].
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Breakpoint {
+    /// Vehicle speed in km/h
+    pub speed: f64,
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+}
+
+/// Table of PID gains by vehicle speed. Gains are interpolated linearly between the
+/// breakpoints and held constant below the first and above the last one.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GainSchedule {
+    breakpoints: Vec<Breakpoint>,
+}
+
+impl GainSchedule {
+    pub fn new(breakpoints: Vec<Breakpoint>) -> Result<Self, String> {
+        let schedule = GainSchedule { breakpoints };
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    /// Loads a schedule from a `.toml`, `.yaml` or `.yml` file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let schedule = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("yaml") | Some("yml") => Self::from_yaml(&content),
+            _ => Err("unsupported file type, expected .toml, .yaml or .yml".to_string()),
+        };
+        schedule.map_err(|e| format!("invalid gain schedule {}: {}", path.display(), e))
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let schedule: GainSchedule = toml::from_str(content).map_err(|e| e.to_string())?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self, String> {
+        let schedule: GainSchedule = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    /// Checks that there is at least one breakpoint, that the speeds are finite and strictly
+    /// increasing and that all gains are valid.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.breakpoints.is_empty() {
+            return Err("at least one breakpoint is required".to_string());
+        }
+        for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+            if !breakpoint.speed.is_finite() {
+                return Err(format!("breakpoint {}: speed must be finite (got {})", index, breakpoint.speed));
+            }
+            if index > 0 && breakpoint.speed <= self.breakpoints[index - 1].speed {
+                return Err(format!(
+                    "breakpoint {}: speeds must be strictly increasing ({} after {})",
+                    index,
+                    breakpoint.speed,
+                    self.breakpoints[index - 1].speed
+                ));
+            }
+            let gains = PidGains { kp: breakpoint.kp, ki: breakpoint.ki, kd: breakpoint.kd, output_limits: None };
+            gains.validate().map_err(|e| format!("breakpoint {}: {}", index, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Interpolated `(kp, ki, kd)` at `speed` km/h.
+    pub fn gains_at(&self, speed: f64) -> (f64, f64, f64) {
+        let first = &self.breakpoints[0];
+        let last = &self.breakpoints[self.breakpoints.len() - 1];
+        if speed <= first.speed || speed.is_nan() {
+            return (first.kp, first.ki, first.kd);
+        }
+        if speed >= last.speed {
+            return (last.kp, last.ki, last.kd);
+        }
+
+        let upper = self.breakpoints.partition_point(|breakpoint| breakpoint.speed <= speed);
+        let (low, high) = (&self.breakpoints[upper - 1], &self.breakpoints[upper]);
+        let ratio = (speed - low.speed) / (high.speed - low.speed);
+        let lerp = |a: f64, b: f64| a + ratio * (b - a);
+        (lerp(low.kp, high.kp), lerp(low.ki, high.ki), lerp(low.kd, high.kd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+        [[breakpoints]]
+        speed = 20.0
+        kp = 0.2
+        ki = 0.04
+        kd = 0.02
+
+        [[breakpoints]]
+        speed = 120.0
+        kp = 0.1
+        ki = 0.01
+        kd = 0.0
+    "#;
+
+    #[test]
+    fn test_gains_are_interpolated() {
+        let schedule = GainSchedule::from_toml(TOML).unwrap();
+        assert_eq!(schedule.gains_at(0.0), (0.2, 0.04, 0.02));
+        assert_eq!(schedule.gains_at(20.0), (0.2, 0.04, 0.02));
+        assert_eq!(schedule.gains_at(200.0), (0.1, 0.01, 0.0));
+
+        let (kp, ki, kd) = schedule.gains_at(70.0);
+        assert!((kp - 0.15).abs() < 1e-12);
+        assert!((ki - 0.025).abs() < 1e-12);
+        assert!((kd - 0.01).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_yaml_matches_toml() {
+        let yaml = "
+breakpoints:
+  - { speed: 20.0, kp: 0.2, ki: 0.04, kd: 0.02 }
+  - { speed: 120.0, kp: 0.1, ki: 0.01, kd: 0.0 }
+";
+        assert_eq!(GainSchedule::from_yaml(yaml).unwrap(), GainSchedule::from_toml(TOML).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_tables_are_rejected() {
+        let point = |speed, kp| Breakpoint { speed, kp, ki: 0.0, kd: 0.0 };
+        assert!(GainSchedule::new(vec![]).is_err());
+        assert!(GainSchedule::new(vec![point(50.0, 0.1), point(50.0, 0.2)]).is_err());
+        assert!(GainSchedule::new(vec![point(80.0, 0.1), point(50.0, 0.2)]).is_err());
+        assert!(GainSchedule::new(vec![point(f64::INFINITY, 0.1)]).is_err());
+        assert!(GainSchedule::new(vec![point(50.0, -0.1)]).is_err());
+        assert!(GainSchedule::from_toml("[[breakpoints]]\nspeed = 20.0\nkp = 0.1").is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::control_loop::{ControllerState, LoopTiming};
+use crate::pid_controller::PidTerms;
+use crate::watchdog::Input;
+
+pub const RESOURCE_ID_HEALTH: u16 = sdv_lab_topics::HEALTH.resource_id;
+
+/// Period and TTL of the health status publication.
+#[derive(Debug, Clone, Copy)]
+pub struct HealthConfig {
+    pub interval: Duration,
+    /// TTL in milliseconds of each status, so that displays notice a controller that stopped
+    pub ttl_ms: u32,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        HealthConfig {
+            interval: Duration::from_secs(1),
+            ttl_ms: 3000,
+        }
+    }
+}
+
+/// Operating mode reported in the health status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerMode {
+    Disengaged,
+    Engaged,
+    /// The watchdog disengaged the controller and the inputs are not all fresh yet, or the
+    /// last computation failed
+    Fault,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaultFlags {
+    pub stale_velocity: bool,
+    pub stale_clock: bool,
+    /// The watchdog fell back to the safe command and the fault is not cleared yet
+    pub watchdog_tripped: bool,
+    /// The last controller computation returned an error
+    pub controller_error: bool,
+}
+
+impl FaultFlags {
+    pub fn any(&self) -> bool {
+        self.stale_velocity || self.stale_clock || self.watchdog_tripped || self.controller_error
+    }
+}
+
+/// Payload of the health topic, published as JSON at a fixed period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthStatus {
+    /// Counts the published statuses, so that missed heartbeats show up as gaps
+    pub sequence: u64,
+    /// Wall-clock time of the status in seconds since the Unix epoch
+    pub timestamp: f64,
+    /// Seconds since the controller started
+    pub uptime: f64,
+    pub mode: ControllerMode,
+    pub engaged: bool,
+    /// Speeds in km/h; the desired speed is the ACC reference while following a lead vehicle
+    pub current_speed: f64,
+    pub desired_speed: f64,
+    /// Last published actuation command and its age in seconds, `null` before the first one
+    pub last_command: Option<f64>,
+    pub last_command_age: Option<f64>,
+    /// P, I and D contributions to the last command, `null` for controllers without PID terms
+    pub terms: Option<PidTerms>,
+    /// Age in seconds of the last sample of each input, `null` if none was received
+    pub velocity_age: Option<f64>,
+    pub clock_age: Option<f64>,
+    pub faults: FaultFlags,
+    /// Jitter and missed deadlines of the fixed-rate loop, `null` for the event-driven one
+    pub loop_timing: Option<LoopTiming>,
+}
+
+impl HealthStatus {
+    /// Builds the `sequence`-th status from the state published by the control loop.
+    pub fn from_state(state: &ControllerState, sequence: u64, started: Instant, now: Instant, timestamp: f64) -> Self {
+        let freshness = &state.freshness;
+        let stale = freshness.stale_inputs(now);
+        let faults = FaultFlags {
+            stale_velocity: stale.contains(&Input::Velocity),
+            stale_clock: stale.contains(&Input::Clock),
+            watchdog_tripped: state.watchdog_tripped,
+            controller_error: state.controller_error,
+        };
+        let mode = if state.watchdog_tripped || state.controller_error {
+            ControllerMode::Fault
+        } else if state.engaged {
+            ControllerMode::Engaged
+        } else {
+            ControllerMode::Disengaged
+        };
+        let age = |input: Input| freshness.input(input).age(now).map(|age| age.as_secs_f64());
+
+        HealthStatus {
+            sequence,
+            timestamp,
+            uptime: now.saturating_duration_since(started).as_secs_f64(),
+            mode,
+            engaged: state.engaged,
+            current_speed: state.current_velocity,
+            desired_speed: state
+                .reference_speed
+                .filter(|_| state.engaged)
+                .unwrap_or(state.desired_velocity),
+            last_command: state.last_command.map(|(command, _)| command),
+            last_command_age: state
+                .last_command
+                .map(|(_, at)| now.saturating_duration_since(at).as_secs_f64()),
+            terms: state.terms,
+            velocity_age: age(Input::Velocity),
+            clock_age: age(Input::Clock),
+            faults,
+            loop_timing: state.timing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watchdog::{Freshness, WatchdogConfig};
+
+    #[test]
+    fn test_status_reports_commands_and_faults() {
+        let start = Instant::now();
+        let mut state = ControllerState::new(Freshness::new(&WatchdogConfig::default()));
+        state.desired_velocity = 50.0;
+
+        let status = HealthStatus::from_state(&state, 1, start, start, 0.0);
+        assert_eq!(status.sequence, 1);
+        assert_eq!(status.mode, ControllerMode::Disengaged);
+        assert_eq!((status.last_command, status.velocity_age), (None, None));
+        assert!(status.faults.stale_velocity && status.faults.stale_clock);
+
+        state.freshness.set_required(Input::Clock, false);
+        state.freshness.received(Input::Velocity, start, None);
+        let terms = PidTerms { proportional: 0.3, integral: 0.1, derivative: -0.05 };
+        state.engaged = true;
+        state.current_velocity = 40.0;
+        state.last_command = Some((0.35, start));
+        state.terms = Some(terms);
+        state.reference_speed = Some(45.0);
+
+        let now = start + Duration::from_millis(200);
+        let status = HealthStatus::from_state(&state, 2, start, now, 0.0);
+        assert_eq!(status.sequence, 2);
+        assert_eq!(status.mode, ControllerMode::Engaged);
+        assert_eq!(status.desired_speed, 45.0);
+        assert_eq!((status.last_command, status.last_command_age), (Some(0.35), Some(0.2)));
+        assert_eq!(status.terms, Some(terms));
+        assert!(!status.faults.any());
+
+        state.engaged = false;
+        state.terms = None;
+        state.watchdog_tripped = true;
+        let status = HealthStatus::from_state(&state, 3, start, now, 0.0);
+        assert_eq!(status.mode, ControllerMode::Fault);
+        assert_eq!((status.desired_speed, status.terms), (50.0, None));
+        assert!(status.faults.watchdog_tripped);
+
+        state.watchdog_tripped = false;
+        state.controller_error = true;
+        state.engaged = true;
+        assert_eq!(HealthStatus::from_state(&state, 4, start, now, 0.0).mode, ControllerMode::Fault);
+        state.controller_error = false;
+        assert_eq!(HealthStatus::from_state(&state, 5, start, now, 0.0).mode, ControllerMode::Engaged);
+    }
+}
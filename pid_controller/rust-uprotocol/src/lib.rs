@@ -0,0 +1,36 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+pub mod acc;
+pub mod autotune;
+pub mod capture;
+pub mod config;
+pub mod control_loop;
+pub mod feedforward_controller;
+pub mod gain_schedule;
+pub mod health;
+pub mod longitudinal_controller;
+pub mod loopback_transport;
+pub mod metrics;
+pub mod mpc_controller;
+pub mod pid_controller;
+pub mod recorder;
+pub mod time_base;
+pub mod topic_filter;
+pub mod tuning_service;
+pub mod uprotocol_handler;
+pub mod watchdog;
+pub mod zenoh_peer;
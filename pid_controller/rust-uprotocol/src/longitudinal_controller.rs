@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::pid_controller::{PIDController, PidGains, PidTerms};
+
+/// Common interface of the longitudinal (speed) controllers that can be driven by the
+/// `UProtocolHandler`. Velocities are in km/h, time in seconds and the returned command
+/// is the normalized acceleration published on the actuation topic.
+pub trait LongitudinalController: Send + 'static {
+    fn compute(&mut self, desired_velocity: f64, current_velocity: f64, current_time: f64) -> Result<f64, String>;
+
+    fn reset(&mut self);
+
+    /// Restarts the timing after a pause of the time base, keeping the integrator state: the
+    /// next sample only re-initializes the time. Controllers without such state reset.
+    fn hold(&mut self) {
+        self.reset()
+    }
+
+    /// Current PID gains, or `None` when the controller has none to tune.
+    fn gains(&self) -> Option<PidGains> {
+        None
+    }
+
+    /// Applies new PID gains without a bump in the output.
+    fn set_gains(&mut self, _gains: PidGains) -> Result<(), String> {
+        Err("controller does not support gain tuning".to_string())
+    }
+
+    /// P/I/D contributions to the last output, for controllers built on a PID.
+    fn terms(&self) -> Option<PidTerms> {
+        None
+    }
+}
+
+impl LongitudinalController for PIDController {
+    fn compute(&mut self, desired_velocity: f64, current_velocity: f64, current_time: f64) -> Result<f64, String> {
+        PIDController::compute(self, desired_velocity, current_velocity, current_time)
+    }
+
+    fn reset(&mut self) {
+        PIDController::reset(self)
+    }
+
+    fn hold(&mut self) {
+        PIDController::hold(self)
+    }
+
+    fn gains(&self) -> Option<PidGains> {
+        Some(PIDController::gains(self))
+    }
+
+    fn set_gains(&mut self, gains: PidGains) -> Result<(), String> {
+        PIDController::set_gains(self, gains)
+    }
+
+    fn terms(&self) -> Option<PidTerms> {
+        Some(PIDController::terms(self))
+    }
+}
+
+impl<C: LongitudinalController + ?Sized> LongitudinalController for Box<C> {
+    fn compute(&mut self, desired_velocity: f64, current_velocity: f64, current_time: f64) -> Result<f64, String> {
+        (**self).compute(desired_velocity, current_velocity, current_time)
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+
+    fn hold(&mut self) {
+        (**self).hold()
+    }
+
+    fn gains(&self) -> Option<PidGains> {
+        (**self).gains()
+    }
+
+    fn set_gains(&mut self, gains: PidGains) -> Result<(), String> {
+        (**self).set_gains(gains)
+    }
+
+    fn terms(&self) -> Option<PidTerms> {
+        (**self).terms()
+    }
+}
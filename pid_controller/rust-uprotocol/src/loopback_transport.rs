@@ -0,0 +1,136 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::{Arc, Mutex};
+use up_rust::{UCode, UListener, UMessage, UStatus, UTransport, UUri};
+
+struct Registration {
+    source_filter: UUri,
+    sink_filter: Option<UUri>,
+    listener: Arc<dyn UListener>,
+}
+
+impl Registration {
+    fn matches(&self, message: &UMessage) -> bool {
+        let Some(source) = message.source() else {
+            return false;
+        };
+        let sink_matches = match (&self.sink_filter, message.sink()) {
+            (None, None) => true,
+            (Some(filter), Some(sink)) => filter.matches(sink),
+            _ => false,
+        };
+        self.source_filter.matches(source) && sink_matches
+    }
+}
+
+/// In-process `UTransport` that delivers every sent message to the matching listeners.
+///
+/// Listeners are invoked one after the other before `send` returns, so a test driving a
+/// handler through this transport observes all side effects of a message deterministically.
+/// Every sent message is also kept for later inspection.
+#[derive(Default)]
+pub struct LoopbackTransport {
+    registrations: Mutex<Vec<Registration>>,
+    sent: Mutex<Vec<UMessage>>,
+}
+
+impl LoopbackTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All messages sent so far, in order.
+    pub fn sent_messages(&self) -> Vec<UMessage> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Messages sent so far whose source matches `topic`.
+    pub fn published_to(&self, topic: &UUri) -> Vec<UMessage> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|message| message.source().is_some_and(|source| topic.matches(source)))
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_sent_messages(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+
+    pub fn listener_count(&self) -> usize {
+        self.registrations.lock().unwrap().len()
+    }
+}
+
+#[async_trait::async_trait]
+impl UTransport for LoopbackTransport {
+    async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+        self.sent.lock().unwrap().push(message.clone());
+
+        // Release the lock before dispatching: listeners may send messages themselves
+        let listeners: Vec<Arc<dyn UListener>> = self
+            .registrations
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|registration| registration.matches(&message))
+            .map(|registration| registration.listener.clone())
+            .collect();
+
+        for listener in listeners {
+            listener.on_receive(message.clone()).await;
+        }
+        Ok(())
+    }
+
+    async fn register_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        self.registrations.lock().unwrap().push(Registration {
+            source_filter: source_filter.clone(),
+            sink_filter: sink_filter.cloned(),
+            listener,
+        });
+        Ok(())
+    }
+
+    async fn unregister_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        let mut registrations = self.registrations.lock().unwrap();
+        let position = registrations.iter().position(|registration| {
+            &registration.source_filter == source_filter
+                && registration.sink_filter.as_ref() == sink_filter
+                && Arc::ptr_eq(&registration.listener, &listener)
+        });
+        match position {
+            Some(index) => {
+                registrations.remove(index);
+                Ok(())
+            }
+            None => Err(UStatus::fail_with_code(UCode::NOT_FOUND, "No such listener")),
+        }
+    }
+}
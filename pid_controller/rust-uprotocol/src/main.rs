@@ -14,97 +14,512 @@
 // limitations under the License.
 //
 
-use log::info;
-use clap::Parser;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use log::{error, info};
+use clap::{Parser, ValueEnum};
 use up_transport_zenoh::{UPTransportZenoh, zenoh_config};
-use up_rust::{LocalUriProvider, StaticUriProvider};
+use up_rust::StaticUriProvider;
 use zenoh::{Config};
+use tokio_util::sync::CancellationToken;
 
-use pid_controller::PIDController;
-use uprotocol_handler::UProtocolHandler;
+use sdv_lab_topics as topics;
 
-mod pid_controller;
-mod uprotocol_handler;
+use pid_cruise_control::config::{
+    AntiWindupMode, ControlLoopMode, ControllerConfig, ControllerKind, DerivativeOn, LoopVelocity, PayloadFormat, RecordFileFormat,
+    TimeBaseSource, TransportConfig, ENV_CONFIG_FILE,
+};
+use pid_cruise_control::longitudinal_controller::LongitudinalController;
+use pid_cruise_control::metrics::{MetricsConfig, MetricsReport};
+use pid_cruise_control::recorder::{read_rows, Recorder, SessionMetadata};
+use pid_cruise_control::tuning_service::TuningService;
+use pid_cruise_control::uprotocol_handler::UProtocolHandler;
+use pid_cruise_control::zenoh_peer::peer_config;
 
+/// Settings given on the command line override the configuration file and the
+/// `PID_CONTROLLER_<SECTION>_<KEY>` environment variables.
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
-    #[clap(long, default_value = "127.0.0.1")]
-    host: String,
-    #[clap(long, default_value_t = 2000)]
-    port: u16,
-    #[clap(long, default_value = "CruiseControl")]
-    role: String,
-    #[clap(long, default_value_t = 0.100)]
-    delta: f64,
-    #[clap(long, default_value = None)]
+    /// TOML configuration file (defaults to $PID_CONTROLLER_CONFIG)
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Print the effective configuration as TOML and exit
+    #[clap(long)]
+    print_config: bool,
+    /// Override any setting, e.g. --set gains.kp=0.2 (repeatable)
+    #[clap(long = "set", value_name = "SECTION.KEY=VALUE")]
+    settings: Vec<String>,
+    /// uProtocol authority of the controller node
+    #[clap(long)]
+    authority: Option<String>,
+    #[clap(long)]
     router: Option<String>,
+    /// JSON5 Zenoh configuration file, instead of peer mode towards --router
+    #[clap(long)]
+    zenoh_config: Option<PathBuf>,
+    #[clap(long)]
+    kp: Option<f64>,
+    #[clap(long)]
+    ki: Option<f64>,
+    #[clap(long)]
+    kd: Option<f64>,
+    /// Lower bound of the published acceleration command
+    #[clap(long, allow_hyphen_values = true)]
+    output_min: Option<f64>,
+    /// Upper bound of the published acceleration command
+    #[clap(long, allow_hyphen_values = true)]
+    output_max: Option<f64>,
+    #[clap(long, value_enum)]
+    anti_windup: Option<AntiWindupMode>,
+    /// Tracking gain used by the back-calculation anti-windup
+    #[clap(long)]
+    tracking_gain: Option<f64>,
+    #[clap(long, value_enum)]
+    derivative_on: Option<DerivativeOn>,
+    /// Time constant in seconds of the D-term low-pass filter (0 disables it)
+    #[clap(long)]
+    derivative_filter_tau: Option<f64>,
+    /// TOML or YAML table of PID gains by vehicle speed, interpolated at runtime
+    #[clap(long)]
+    gain_schedule: Option<PathBuf>,
+    /// Longitudinal control strategy
+    #[clap(long, value_enum)]
+    controller: Option<ControllerKind>,
+    /// Maximum reference acceleration in km/h/s of the feed-forward controller
+    #[clap(long)]
+    ff_max_acceleration: Option<f64>,
+    /// Command per km/h/s of reference acceleration added by the feed-forward controller
+    #[clap(long)]
+    ff_gain: Option<f64>,
+    /// Number of prediction steps of the MPC controller
+    #[clap(long)]
+    mpc_horizon: Option<usize>,
+    /// Duration in seconds of one MPC prediction step
+    #[clap(long)]
+    mpc_step: Option<f64>,
+    /// MPC cost weight of the command magnitude
+    #[clap(long)]
+    mpc_effort_weight: Option<f64>,
+    /// MPC cost weight of the change from the previous command
+    #[clap(long)]
+    mpc_rate_weight: Option<f64>,
+    /// Gain of the MPC disturbance observer (0 disables offset compensation)
+    #[clap(long)]
+    mpc_observer_gain: Option<f64>,
+    /// TTL in milliseconds of the gains status messages
+    #[clap(long)]
+    status_ttl_ms: Option<u32>,
+    /// Directory receiving the recorded control samples
+    #[clap(long)]
+    record_dir: Option<PathBuf>,
+    #[clap(long, value_enum)]
+    record_format: Option<RecordFileFormat>,
+    /// Rows per recording file before rotating to a new one
+    #[clap(long)]
+    record_max_rows: Option<usize>,
+    /// Rows buffered in memory before they are written out
+    #[clap(long)]
+    record_buffer_rows: Option<usize>,
+    /// Only keep the summary statistics, without writing any file
+    #[clap(long)]
+    no_record: bool,
+    /// Encoding of the published actuation command (text for subscribers that predate protobuf)
+    #[clap(long, value_enum)]
+    payload_format: Option<PayloadFormat>,
+    /// Maximum age in milliseconds of the last velocity sample before the controller disengages
+    #[clap(long)]
+    velocity_timeout_ms: Option<u64>,
+    /// Maximum age in milliseconds of the last clock tick before the controller disengages
+    #[clap(long)]
+    clock_timeout_ms: Option<u64>,
+    /// Actuation command published when an input goes stale (0 coasts, e.g. -0.2 brakes gently)
+    #[clap(long, allow_hyphen_values = true)]
+    safe_command: Option<f64>,
+    /// Keep the last actuation command in effect when inputs stop arriving
+    #[clap(long)]
+    no_watchdog: bool,
+    /// Time base of the controller: the simulation clock topic, or the local wall clock
+    #[clap(long, value_enum)]
+    time_base: Option<TimeBaseSource>,
+    /// Largest forward clock step in seconds integrated as-is; longer gaps hold the controller
+    #[clap(long)]
+    max_clock_step: Option<f64>,
+    /// When to compute a command: on every velocity sample, or at a fixed rate from the latest inputs
+    #[clap(long, value_enum)]
+    loop_mode: Option<ControlLoopMode>,
+    /// Rate in Hz of the fixed-rate control loop
+    #[clap(long)]
+    loop_rate_hz: Option<f64>,
+    /// Velocity of the fixed-rate loop between samples: the latest one, or extrapolated from the last two
+    #[clap(long, value_enum)]
+    loop_velocity: Option<LoopVelocity>,
+    /// Longest extrapolation in milliseconds past the last velocity sample
+    #[clap(long)]
+    max_extrapolation_ms: Option<u64>,
+    /// Adaptive cruise control: follow a slower lead vehicle seen by the obstacle detector or radar
+    #[clap(long)]
+    acc: bool,
+    /// Time gap in seconds kept to the lead vehicle
+    #[clap(long)]
+    acc_time_gap: Option<f64>,
+    /// Distance in meters kept to a stopped lead vehicle
+    #[clap(long)]
+    acc_standstill_distance: Option<f64>,
+    /// Speed correction in m/s per meter of spacing error
+    #[clap(long)]
+    acc_distance_gain: Option<f64>,
+    /// Range in meters beyond which radar detections are ignored
+    #[clap(long)]
+    acc_max_range: Option<f64>,
+    /// Half-width in degrees of the radar cone in front of the vehicle
+    #[clap(long)]
+    acc_radar_half_angle: Option<f64>,
+    /// Seconds without a detection before the lead vehicle is dropped
+    #[clap(long)]
+    acc_target_timeout: Option<f64>,
+    /// Km/h by which the spacing speed must exceed the set speed before leaving spacing mode
+    #[clap(long)]
+    acc_mode_hysteresis: Option<f64>,
+    /// Period in milliseconds of the health status
+    #[clap(long)]
+    health_interval_ms: Option<u64>,
+    /// Do not publish the health status
+    #[clap(long)]
+    no_health: bool,
+    /// Comma-separated ids of the vehicles to control, e.g. the CARLA role names `hero,hero2`
+    #[clap(long, value_delimiter = ',')]
+    vehicles: Vec<String>,
+}
+
+impl Args {
+    // The command line settings as `section.key` overrides, the `--set` ones last
+    fn overrides(&self) -> Result<Vec<(String, String)>, String> {
+        fn name<T: ValueEnum>(value: &T) -> String {
+            value.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+        }
+        fn path(value: &std::path::Path) -> String {
+            value.display().to_string()
+        }
+
+        let mut overrides = Vec::new();
+        let mut add = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                overrides.push((key.to_string(), value));
+            }
+        };
+        add("node.authority", self.authority.clone());
+        add("transport.router", self.router.clone());
+        add("transport.zenoh_config", self.zenoh_config.as_deref().map(path));
+        add("gains.kp", self.kp.map(|v| v.to_string()));
+        add("gains.ki", self.ki.map(|v| v.to_string()));
+        add("gains.kd", self.kd.map(|v| v.to_string()));
+        add("limits.output_min", self.output_min.map(|v| v.to_string()));
+        add("limits.output_max", self.output_max.map(|v| v.to_string()));
+        add("pid.anti_windup", self.anti_windup.as_ref().map(name));
+        add("pid.tracking_gain", self.tracking_gain.map(|v| v.to_string()));
+        add("pid.derivative_on", self.derivative_on.as_ref().map(name));
+        add("pid.derivative_filter_tau", self.derivative_filter_tau.map(|v| v.to_string()));
+        add("controller.kind", self.controller.as_ref().map(name));
+        add("controller.gain_schedule", self.gain_schedule.as_deref().map(path));
+        add("feed_forward.max_acceleration", self.ff_max_acceleration.map(|v| v.to_string()));
+        add("feed_forward.gain", self.ff_gain.map(|v| v.to_string()));
+        add("mpc.horizon", self.mpc_horizon.map(|v| v.to_string()));
+        add("mpc.step", self.mpc_step.map(|v| v.to_string()));
+        add("mpc.effort_weight", self.mpc_effort_weight.map(|v| v.to_string()));
+        add("mpc.rate_weight", self.mpc_rate_weight.map(|v| v.to_string()));
+        add("mpc.observer_gain", self.mpc_observer_gain.map(|v| v.to_string()));
+        add("tuning.status_ttl_ms", self.status_ttl_ms.map(|v| v.to_string()));
+        add("recording.enabled", self.no_record.then(|| "false".to_string()));
+        add("recording.dir", self.record_dir.as_deref().map(path));
+        add("recording.format", self.record_format.as_ref().map(name));
+        add("recording.max_rows", self.record_max_rows.map(|v| v.to_string()));
+        add("recording.buffer_rows", self.record_buffer_rows.map(|v| v.to_string()));
+        add("actuation.payload_format", self.payload_format.as_ref().map(name));
+        add("watchdog.enabled", self.no_watchdog.then(|| "false".to_string()));
+        add("watchdog.velocity_timeout_ms", self.velocity_timeout_ms.map(|v| v.to_string()));
+        add("watchdog.clock_timeout_ms", self.clock_timeout_ms.map(|v| v.to_string()));
+        add("watchdog.safe_command", self.safe_command.map(|v| v.to_string()));
+        add("time_base.source", self.time_base.as_ref().map(name));
+        add("time_base.max_clock_step", self.max_clock_step.map(|v| v.to_string()));
+        add("control_loop.mode", self.loop_mode.as_ref().map(name));
+        add("control_loop.rate_hz", self.loop_rate_hz.map(|v| v.to_string()));
+        add("control_loop.velocity", self.loop_velocity.as_ref().map(name));
+        add("control_loop.max_extrapolation_ms", self.max_extrapolation_ms.map(|v| v.to_string()));
+        add("acc.enabled", self.acc.then(|| "true".to_string()));
+        add("acc.time_gap", self.acc_time_gap.map(|v| v.to_string()));
+        add("acc.standstill_distance", self.acc_standstill_distance.map(|v| v.to_string()));
+        add("acc.distance_gain", self.acc_distance_gain.map(|v| v.to_string()));
+        add("acc.max_range", self.acc_max_range.map(|v| v.to_string()));
+        add("acc.radar_half_angle", self.acc_radar_half_angle.map(|v| v.to_string()));
+        add("acc.target_timeout", self.acc_target_timeout.map(|v| v.to_string()));
+        add("acc.mode_hysteresis", self.acc_mode_hysteresis.map(|v| v.to_string()));
+        add("health.enabled", self.no_health.then(|| "false".to_string()));
+        add("health.interval_ms", self.health_interval_ms.map(|v| v.to_string()));
+        add("fleet.vehicles", (!self.vehicles.is_empty()).then(|| self.vehicles.join(",")));
+
+        for setting in &self.settings {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| format!("--set {}: expected SECTION.KEY=VALUE", setting))?;
+            overrides.push((key.trim().to_string(), value.trim().to_string()));
+        }
+        Ok(overrides)
+    }
 }
 
 // Helper function to create a Zenoh configuration
-pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
-    let args = Args::parse();
+pub(crate) fn get_zenoh_config(transport: &TransportConfig) -> Result<zenoh_config::Config, String> {
+    if let Some(path) = &transport.zenoh_config {
+        return Config::from_file(path).map_err(|e| format!("failed to load {}: {}", path.display(), e));
+    }
 
-    let zenoh_string = if let Some(router) = &args.router {
-        format!("{{ mode: 'peer', connect: {{ endpoints: [ 'tcp/{}:7447' ] }} }}", router)
-    } else {
-        "{ mode: 'peer' }".to_string()
+    peer_config(transport.router.as_ref().map(|router| format!("{}:7447", router)).as_deref())
+}
+
+// Prints the step response metrics of the recorded session and stores them next to it
+fn report_metrics(files: &[std::path::PathBuf]) {
+    let Some(first) = files.first() else {
+        return;
     };
+    let mut rows = Vec::new();
+    for file in files {
+        match read_rows(file) {
+            Ok(file_rows) => rows.extend(file_rows),
+            Err(e) => {
+                error!("Failed to read {}: {}", file.display(), e);
+                return;
+            }
+        }
+    }
 
-    let zenoh_config = Config::from_json5(&zenoh_string).expect("Failed to load Zenoh config");
+    let report = MetricsReport::from_rows(&rows, &MetricsConfig::default());
+    info!("Step response metrics:\n{}", report);
+    let path = first.with_extension("metrics.json");
+    match std::fs::write(&path, report.to_json()) {
+        Ok(()) => info!("Metrics saved to {}", path.display()),
+        Err(e) => error!("Failed to write {}: {}", path.display(), e),
+    }
+}
 
-    zenoh_config
+// Cancels `shutdown` on SIGINT (CTRL-C) or SIGTERM, which Ankaios sends to stop a workload
+async fn cancel_on_signal(shutdown: CancellationToken) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => info!("Received SIGINT"),
+                    _ = terminate.recv() => info!("Received SIGTERM"),
+                }
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+    shutdown.cancel();
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     // Initialize logging
     env_logger::init();
 
+    let args = Args::parse();
+    let file = args.config.clone().or_else(|| std::env::var_os(ENV_CONFIG_FILE).map(PathBuf::from));
+    let config = match args.overrides().and_then(|overrides| ControllerConfig::load(file.as_deref(), std::env::vars(), &overrides)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    if args.print_config {
+        print!("{}", config.to_toml());
+        return ExitCode::SUCCESS;
+    }
+
+    let shutdown = CancellationToken::new();
+    tokio::spawn(cancel_on_signal(shutdown.clone()));
+
+    match run(config, file, shutdown).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("PID controller failed: {}", e);
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Starts the controller and runs it until `shutdown` is cancelled
+async fn run(config: ControllerConfig, file: Option<PathBuf>, shutdown: CancellationToken) -> Result<(), Box<dyn std::error::Error>> {
     info!("*** Started PID Controller with uProtocol");
+    if let Some(path) = &file {
+        println!("Configuration => {}", path.display());
+    }
+
+    println!("PID => Kp={}, Ki={}, Kd={}", config.gains.kp, config.gains.ki, config.gains.kd);
+    println!(
+        "PID => limits=[{}, {}], anti-windup={:?}, derivative={:?}, filter tau={}",
+        config.limits.output_min,
+        config.limits.output_max,
+        config.pid.anti_windup,
+        config.pid.derivative_on,
+        config.pid.derivative_filter_tau
+    );
+
+    if let Some(schedule) = config.gain_schedule()? {
+        for breakpoint in schedule.breakpoints() {
+            println!(
+                "Gain schedule => {} km/h: Kp={}, Ki={}, Kd={}",
+                breakpoint.speed, breakpoint.kp, breakpoint.ki, breakpoint.kd
+            );
+        }
+    }
+    println!("Controller => {:?}", config.controller.kind);
+
+    // The transport is shared by the vehicles of a fleet, each with its own authorities: the
+    // Zenoh key expressions are built from the authorities of the URIs, the local authority of
+    // the transport only stands in for URIs without one, so the RPC requests sent to
+    // CruiseControl-<vehicle> reach the listeners registered for that authority
+    let transport = Arc::new(
+        UPTransportZenoh::builder(config.node.authority.clone())
+            .map_err(|e| format!("invalid authority name '{}': {}", config.node.authority, e))?
+            .with_config(get_zenoh_config(&config.transport)?)
+            .build()
+            .await?,
+    );
+
+    // One controller per vehicle of the fleet, or a single one on the configured topics
+    let vehicles: Vec<Option<&str>> = if config.fleet.vehicles.is_empty() {
+        vec![None]
+    } else {
+        config.fleet.vehicles.iter().map(|vehicle| Some(vehicle.as_str())).collect()
+    };
+    let mut controllers = Vec::new();
+    for vehicle in vehicles {
+        match start_vehicle(&config, vehicle, transport.clone()).await {
+            Ok(controller) => controllers.push(controller),
+            Err(e) => {
+                for (handler, tuning_service) in &controllers {
+                    tuning_service.stop().await;
+                    handler.stop().await;
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    println!("PID controller running with uProtocol (CTRL-C or SIGTERM to terminate)...");
+    shutdown.cancelled().await;
+    println!("\nShutting down...");
 
-    let kp = 0.125;
-    let ki = kp / 8.0;
-    let kd = kp / 10.0;
+    for (handler, tuning_service) in &controllers {
+        tuning_service.stop().await;
+        handler.stop().await;
+        handler.show_results();
+        report_metrics(&handler.recorded_files());
+    }
+
+    info!("PID controller stopped");
+    Ok(())
+}
 
-    println!("PID => Kp={}, Ki={}, Kd={}", kp, ki, kd);
+type Controller = Box<dyn LongitudinalController>;
 
-    let pid = PIDController::new(kp, ki, kd);
+// Starts the handler and the tuning service of `vehicle`, or of the single vehicle on the
+// configured topics
+async fn start_vehicle(
+    config: &ControllerConfig,
+    vehicle: Option<&str>,
+    transport: Arc<UPTransportZenoh>,
+) -> Result<(UProtocolHandler<Controller>, TuningService<Controller>), Box<dyn std::error::Error>> {
+    let controller = config.build_controller()?;
 
     // Create a uProtocol URI provider for the PID controller
     // This defines the identity of this node in the uProtocol network
-    let uri_provider = StaticUriProvider::new("CruiseControl", 0, 2);
-    
-    // Initialize uProtocol transport with Zenoh
-    let transport = UPTransportZenoh::builder(uri_provider.get_authority())
-        .expect("invalid authority name")
-        .with_config(get_zenoh_config())
-        .build()
-        .await?;
-
-    let handler = UProtocolHandler::new(pid, transport)?;
-
-    handler.start().await?;
-
-    println!("PID controller running with uProtocol (CTRL-C to terminate)...");
-
-    // Set up Ctrl+C handler
-    let handler_clone = std::sync::Arc::new(handler);
-    let handler_for_signal = handler_clone.clone();
-    
-    tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl+c");
-        
-        println!("\nShutting down...");
-        
-        handler_for_signal.store_results();
-        handler_for_signal.show_results();
-        
-        std::process::exit(0);
-    });
-
-    // Keep the main thread alive
-    loop {
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    let authority = match vehicle {
+        Some(vehicle) => topics::vehicle_authority(&config.node.authority, vehicle),
+        None => config.node.authority.clone(),
+    };
+    let uri_provider = Arc::new(StaticUriProvider::new(&authority, config.node.ue_id, config.node.ue_version_major));
+
+    let metadata = SessionMetadata {
+        controller: format!("{:?}", config.controller.kind),
+        gains: controller.gains(),
+        git_revision: env!("GIT_REVISION").to_string(),
+        start_time: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs_f64(),
+    };
+    let recorder = match config.recorder() {
+        Some(mut recorder_config) => {
+            // Each vehicle of a fleet records into its own directory
+            if let Some(vehicle) = vehicle {
+                recorder_config.directory = recorder_config.directory.join(vehicle);
+            }
+            println!("Recording => {:?} in {}", recorder_config.format, recorder_config.directory.display());
+            Recorder::new(recorder_config, metadata)
+        }
+        None => Recorder::summary_only(),
+    };
+
+    let mut handler = UProtocolHandler::new(controller, transport.clone())?
+        .with_topics(config.topics.uris()?)
+        .with_recorder(recorder)
+        .with_actuation_encoding(config.actuation_encoding())
+        .with_time_base(config.time_base())
+        .with_loop_mode(config.loop_mode())
+        .with_velocity_input(config.velocity_input());
+    if let Some(vehicle) = vehicle {
+        println!("Vehicle => {} as {}", vehicle, authority);
+        handler = handler.with_vehicle(vehicle);
+    }
+    if let Some(acc) = config.acc() {
+        handler = handler.with_acc(acc);
+    }
+    if let Some(watchdog) = config.watchdog() {
+        handler = handler.with_watchdog(watchdog);
+    }
+    if let Some(health) = config.health() {
+        handler = handler.with_health(health, uri_provider.clone());
+    }
+
+    if let Err(e) = handler.start().await {
+        handler.stop().await;
+        return Err(e);
+    }
+
+    // Expose the gains for runtime tuning
+    let tuning_service = TuningService::new(handler.controller(), transport, uri_provider, config.tuning.status_ttl_ms);
+    if let Err(e) = tuning_service.start().await {
+        handler.stop().await;
+        return Err(e.into());
+    }
+    Ok((handler, tuning_service))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_values_only_for_signed_settings() {
+        let args = Args::try_parse_from(["pid", "--output-min", "-1.5", "--output-max", "-0.5", "--safe-command", "-0.2"])
+            .unwrap();
+        assert_eq!((args.output_min, args.output_max, args.safe_command), (Some(-1.5), Some(-0.5), Some(-0.2)));
+
+        // A missing gain value is reported instead of taking the next flag as the value
+        assert!(Args::try_parse_from(["pid", "--kp", "--ki", "1"]).is_err());
     }
 }
@@ -0,0 +1,460 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::recorder::RecordRow;
+
+// Absolute slack of the regression check, so that noise around zero (e.g. a steady-state
+// error of 1e-6 km/h turning into 2e-6 km/h) does not count as a regression
+const REGRESSION_SLACK: f64 = 0.01;
+
+/// Thresholds used to evaluate each target-speed step.
+#[derive(Debug, Clone, Copy)]
+pub struct MetricsConfig {
+    /// Lower and upper fraction of the step bounding the rise time (10 % to 90 %)
+    pub rise_bounds: (f64, f64),
+    /// Settling band as a fraction of the step size
+    pub settling_fraction: f64,
+    /// Minimum settling band in km/h, used for small steps
+    pub settling_min_band: f64,
+    /// Trailing window in seconds over which the steady-state error is averaged
+    pub steady_state_window: f64,
+    /// Steps smaller than this in km/h are treated as regulation and get no transient metrics
+    pub min_step: f64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            rise_bounds: (0.1, 0.9),
+            settling_fraction: 0.02,
+            settling_min_band: 0.5,
+            steady_state_window: 2.0,
+            min_step: 1.0,
+        }
+    }
+}
+
+/// Performance of the controller over one constant target-speed segment, or over one
+/// segment spent following a lead vehicle.
+///
+/// Times are in seconds from the start of the segment, speeds in km/h and the integrals
+/// are taken over the clock of the recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepMetrics {
+    pub start_time: f64,
+    pub duration: f64,
+    pub initial_speed: f64,
+    pub target_speed: f64,
+    /// The ACC tracked the spacing speed, whose reference changes every sample, so the
+    /// segment is evaluated as regulation against that reference
+    #[serde(default)]
+    pub following: bool,
+    pub rise_time: Option<f64>,
+    /// Peak excursion beyond the target in percent of the step size
+    pub overshoot_percent: Option<f64>,
+    pub settling_time: Option<f64>,
+    pub steady_state_error: f64,
+    pub iae: f64,
+    pub ise: f64,
+    pub itae: f64,
+    /// Integral of the absolute actuation command
+    pub effort: f64,
+    /// Sum of the absolute changes of the actuation command
+    pub command_variation: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsReport {
+    pub steps: Vec<StepMetrics>,
+}
+
+impl MetricsReport {
+    /// Splits the engaged part of a recording into segments and evaluates each of them.
+    ///
+    /// A segment ends when the controller disengages, when the ACC starts or stops following
+    /// a lead vehicle, at every set-speed change while not following, and when the clock
+    /// goes backward (restarted simulation).
+    pub fn from_rows(rows: &[RecordRow], config: &MetricsConfig) -> Self {
+        let mut steps = Vec::new();
+        let mut segment: Vec<&RecordRow> = Vec::new();
+        for row in rows {
+            let active = row.engaged && !row.acceleration.is_nan();
+            let split = segment.last().is_some_and(|last| {
+                last.following != row.following
+                    || (!row.following && last.desired_velocity != row.desired_velocity)
+                    || row.time < last.time
+            });
+            if !active || split {
+                steps.extend(StepMetrics::evaluate(&segment, config));
+                segment.clear();
+            }
+            if active {
+                segment.push(row);
+            }
+        }
+        steps.extend(StepMetrics::evaluate(&segment, config));
+        MetricsReport { steps }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// Lists the metrics that got worse than `baseline` by more than the relative `tolerance`.
+    ///
+    /// Steps are matched by position, so both reports must come from the same scenario.
+    pub fn regressions(&self, baseline: &MetricsReport, tolerance: f64) -> Vec<String> {
+        let mut regressions = Vec::new();
+        if self.steps.len() != baseline.steps.len() {
+            regressions.push(format!(
+                "step count changed from {} to {}",
+                baseline.steps.len(),
+                self.steps.len()
+            ));
+        }
+        for (index, (current, base)) in self.steps.iter().zip(&baseline.steps).enumerate() {
+            for ((name, value), (_, reference)) in current.comparable().into_iter().zip(base.comparable()) {
+                let regressed = match (value, reference) {
+                    (Some(value), Some(reference)) => value > reference * (1.0 + tolerance) + REGRESSION_SLACK,
+                    // A step that used to settle (or rise) and no longer does
+                    (None, Some(_)) => true,
+                    _ => false,
+                };
+                if regressed {
+                    regressions.push(format!(
+                        "step {}: {} {} -> {}",
+                        index + 1,
+                        name,
+                        format_metric(reference),
+                        format_metric(value)
+                    ));
+                }
+            }
+        }
+        regressions
+    }
+}
+
+impl StepMetrics {
+    fn evaluate(segment: &[&RecordRow], config: &MetricsConfig) -> Option<Self> {
+        let first = segment.first()?;
+        let last = segment.last()?;
+        let start_time = first.time;
+        let initial_speed = first.current_velocity;
+        let target_speed = first.desired_velocity;
+        let following = first.following;
+        let step = target_speed - initial_speed;
+        let transient = !following && step.abs() >= config.min_step;
+
+        let mut iae = 0.0;
+        let mut ise = 0.0;
+        let mut itae = 0.0;
+        let mut effort = 0.0;
+        let mut command_variation = 0.0;
+        for pair in segment.windows(2) {
+            let dt = pair[1].time - pair[0].time;
+            // Repeated clock samples add no time
+            if dt <= 0.0 {
+                command_variation += (pair[1].acceleration - pair[0].acceleration).abs();
+                continue;
+            }
+            let error = (pair[0].desired_velocity - pair[0].current_velocity).abs();
+            iae += error * dt;
+            ise += error * error * dt;
+            itae += (pair[0].time - start_time) * error * dt;
+            effort += pair[0].acceleration.abs() * dt;
+            command_variation += (pair[1].acceleration - pair[0].acceleration).abs();
+        }
+
+        // Progress towards the target as a fraction of the step, 1.0 once reached
+        let progress = |row: &RecordRow| (row.current_velocity - initial_speed) / step;
+        let crossing = |fraction: f64| {
+            segment
+                .iter()
+                .find(|row| progress(row) >= fraction)
+                .map(|row| row.time)
+        };
+        let rise_time = if transient {
+            match (crossing(config.rise_bounds.0), crossing(config.rise_bounds.1)) {
+                (Some(low), Some(high)) => Some(high - low),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        let overshoot_percent = transient.then(|| {
+            let peak = segment.iter().map(|row| progress(row)).fold(f64::NEG_INFINITY, f64::max);
+            ((peak - 1.0) * 100.0).max(0.0)
+        });
+
+        let band = match transient {
+            true => (config.settling_fraction * step.abs()).max(config.settling_min_band),
+            false => config.settling_min_band,
+        };
+        let outside_band = |row: &RecordRow| (row.desired_velocity - row.current_velocity).abs() > band;
+        let settling_time = match segment.iter().rposition(|row| outside_band(row)) {
+            // Still outside the band at the end of the segment
+            Some(index) if index == segment.len() - 1 => None,
+            Some(index) => Some(segment[index + 1].time - start_time),
+            None => Some(0.0),
+        };
+
+        let window: Vec<f64> = segment
+            .iter()
+            .filter(|row| row.time >= last.time - config.steady_state_window)
+            .map(|row| row.desired_velocity - row.current_velocity)
+            .collect();
+        let steady_state_error = window.iter().sum::<f64>() / window.len() as f64;
+
+        Some(StepMetrics {
+            start_time,
+            duration: last.time - start_time,
+            initial_speed,
+            target_speed,
+            following,
+            rise_time,
+            overshoot_percent,
+            settling_time,
+            steady_state_error,
+            iae,
+            ise,
+            itae,
+            effort,
+            command_variation,
+        })
+    }
+
+    // Metrics where a lower value is better, used for regression checks
+    fn comparable(&self) -> Vec<(&'static str, Option<f64>)> {
+        vec![
+            ("rise_time", self.rise_time),
+            ("overshoot_percent", self.overshoot_percent),
+            ("settling_time", self.settling_time),
+            ("steady_state_error", Some(self.steady_state_error.abs())),
+            ("iae", Some(self.iae)),
+            ("ise", Some(self.ise)),
+            ("itae", Some(self.itae)),
+            ("effort", Some(self.effort)),
+        ]
+    }
+}
+
+fn format_metric(value: Option<f64>) -> String {
+    match value {
+        Some(value) => format!("{:.3}", value),
+        None => "n/a".to_string(),
+    }
+}
+
+impl fmt::Display for MetricsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4} {:>8} {:>15} {:>8} {:>9} {:>9} {:>8} {:>9} {:>9} {:>10} {:>8}",
+            "step", "start", "speed", "rise", "overshoot", "settling", "ss err", "IAE", "ISE", "ITAE", "effort"
+        )?;
+        for (index, step) in self.steps.iter().enumerate() {
+            let speed = if step.following {
+                format!("{:.1} -> lead", step.initial_speed)
+            } else {
+                format!("{:.1} -> {:.1}", step.initial_speed, step.target_speed)
+            };
+            let overshoot = match step.overshoot_percent {
+                Some(overshoot) => format!("{:.3}%", overshoot),
+                None => "n/a".to_string(),
+            };
+            writeln!(
+                f,
+                "{:>4} {:>8.2} {:>15} {:>8} {:>9} {:>9} {:>8.3} {:>9.2} {:>9.2} {:>10.2} {:>8.2}",
+                index + 1,
+                step.start_time,
+                speed,
+                format_metric(step.rise_time),
+                overshoot,
+                format_metric(step.settling_time),
+                step.steady_state_error,
+                step.iae,
+                step.ise,
+                step.itae,
+                step.effort
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 0.01;
+
+    fn row(time: f64, desired: f64, current: f64, command: f64) -> RecordRow {
+        RecordRow {
+            wall_time: time,
+            time,
+            desired_velocity: desired,
+            current_velocity: current,
+            acceleration: command,
+            proportional: f64::NAN,
+            integral: f64::NAN,
+            derivative: f64::NAN,
+            engaged: true,
+            following: false,
+        }
+    }
+
+    // First order response with time constant `tau` from `from` to `to`
+    fn first_order(start: f64, duration: f64, from: f64, to: f64, tau: f64) -> Vec<RecordRow> {
+        (0..(duration / DT) as usize)
+            .map(|i| {
+                let t = i as f64 * DT;
+                let speed = to + (from - to) * (-t / tau).exp();
+                row(start + t, to, speed, 0.5)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_first_order_step() {
+        let rows = first_order(0.0, 20.0, 0.0, 50.0, 1.0);
+        let report = MetricsReport::from_rows(&rows, &MetricsConfig::default());
+
+        assert_eq!(report.steps.len(), 1);
+        let step = &report.steps[0];
+        assert!((step.rise_time.unwrap() - 9f64.ln()).abs() < 2.0 * DT, "{:?}", step.rise_time);
+        assert_eq!(step.overshoot_percent, Some(0.0));
+        // The 2 % band of a 50 km/h step is 1 km/h, reached after ln(50) time constants
+        assert!((step.settling_time.unwrap() - 50f64.ln()).abs() < 2.0 * DT, "{:?}", step.settling_time);
+        assert!(step.steady_state_error.abs() < 1e-3);
+        assert!((step.iae - 50.0).abs() < 1.0, "{}", step.iae);
+        assert!((step.effort - 0.5 * 20.0).abs() < 0.1);
+        assert_eq!(step.command_variation, 0.0);
+    }
+
+    #[test]
+    fn test_overshoot_of_underdamped_response() {
+        // Second order with damping 0.3: overshoot exp(-pi * zeta / sqrt(1 - zeta^2)) = 37.2 %
+        let zeta: f64 = 0.3;
+        let wn = 2.0;
+        let wd = wn * (1.0 - zeta * zeta).sqrt();
+        let rows: Vec<RecordRow> = (0..3000)
+            .map(|i| {
+                let t = i as f64 * DT;
+                let response = 1.0
+                    - (-zeta * wn * t).exp() * ((wd * t).cos() + zeta * wn / wd * (wd * t).sin());
+                row(t, 80.0, 60.0 + 20.0 * response, 0.0)
+            })
+            .collect();
+        let step = MetricsReport::from_rows(&rows, &MetricsConfig::default()).steps[0].clone();
+
+        assert!((step.overshoot_percent.unwrap() - 37.2).abs() < 0.5, "{:?}", step.overshoot_percent);
+        assert!(step.settling_time.unwrap() > step.rise_time.unwrap());
+    }
+
+    #[test]
+    fn test_segments_at_target_changes_and_disengagement() {
+        let mut rows = first_order(0.0, 10.0, 30.0, 50.0, 1.0);
+        rows.extend(first_order(10.0, 10.0, 50.0, 40.0, 1.0));
+        let mut disengaged = row(20.0, 40.0, 40.0, f64::NAN);
+        disengaged.engaged = false;
+        rows.push(disengaged);
+        rows.extend(first_order(20.1, 5.0, 40.0, 40.0, 1.0));
+        let report = MetricsReport::from_rows(&rows, &MetricsConfig::default());
+
+        assert_eq!(report.steps.len(), 3);
+        assert_eq!(report.steps[1].start_time, 10.0);
+        assert_eq!(report.steps[1].target_speed, 40.0);
+        assert_eq!(report.steps[1].overshoot_percent, Some(0.0));
+        assert!(report.steps[1].rise_time.is_some());
+        // Holding a constant speed is a regulation segment
+        assert_eq!(report.steps[2].rise_time, None);
+        assert_eq!(report.steps[2].overshoot_percent, None);
+        assert_eq!(report.steps[2].settling_time, Some(0.0));
+    }
+
+    #[test]
+    fn test_backward_clock_starts_a_new_segment() {
+        let mut rows = first_order(100.0, 5.0, 30.0, 50.0, 1.0);
+        rows.extend(first_order(0.0, 5.0, 30.0, 50.0, 1.0));
+        // A repeated clock sample adds no time
+        rows.insert(10, rows[9]);
+        let report = MetricsReport::from_rows(&rows, &MetricsConfig::default());
+
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[1].start_time, 0.0);
+        for step in &report.steps {
+            assert!(step.iae > 0.0 && step.ise > 0.0 && step.itae > 0.0, "{:?}", step);
+            assert!((step.iae - report.steps[1].iae).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_following_is_one_regulation_segment() {
+        let mut rows = first_order(0.0, 5.0, 30.0, 50.0, 1.0);
+        // The spacing reference changes with every sample while following
+        rows.extend((0..500).map(|i| {
+            let t = 5.0 + i as f64 * DT;
+            let mut row = row(t, 45.0 - t, 46.0 - t, -0.2);
+            row.following = true;
+            row
+        }));
+        rows.extend(first_order(10.0, 5.0, 36.0, 50.0, 1.0));
+        let report = MetricsReport::from_rows(&rows, &MetricsConfig::default());
+
+        assert_eq!(report.steps.len(), 3);
+        let following = &report.steps[1];
+        assert!(following.following);
+        assert_eq!(following.rise_time, None);
+        assert_eq!(following.overshoot_percent, None);
+        assert!((following.steady_state_error + 1.0).abs() < 1e-9);
+        assert!((following.iae - 5.0).abs() < 0.1, "{}", following.iae);
+        assert!(!report.steps[2].following);
+        assert!(report.steps[2].rise_time.is_some());
+
+        let table = report.to_string();
+        let line = table.lines().nth(2).unwrap();
+        assert!(line.contains("-> lead"), "{}", line);
+        assert!(!line.contains('%'), "{}", line);
+        assert!(table.lines().nth(1).unwrap().contains("0.000%"));
+    }
+
+    #[test]
+    fn test_regressions_against_baseline() {
+        let config = MetricsConfig::default();
+        let baseline = MetricsReport::from_rows(&first_order(0.0, 20.0, 0.0, 50.0, 1.0), &config);
+        let slower = MetricsReport::from_rows(&first_order(0.0, 20.0, 0.0, 50.0, 1.5), &config);
+
+        let parsed = MetricsReport::from_json(&baseline.to_json()).unwrap();
+        assert_eq!(parsed.steps.len(), 1);
+        assert_eq!(parsed.steps[0].rise_time, baseline.steps[0].rise_time);
+        assert!(baseline.regressions(&parsed, 0.0).is_empty());
+        assert!(baseline.regressions(&slower, 0.1).is_empty());
+
+        let regressions = slower.regressions(&baseline, 0.1);
+        assert!(regressions.iter().any(|r| r.starts_with("step 1: rise_time")), "{:?}", regressions);
+        assert!(regressions.iter().any(|r| r.starts_with("step 1: iae")));
+        // ITAE grows with the square of the time constant
+        assert!(slower.regressions(&baseline, 1.5).is_empty());
+    }
+}
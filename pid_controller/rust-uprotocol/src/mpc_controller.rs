@@ -0,0 +1,218 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::longitudinal_controller::LongitudinalController;
+use crate::pid_controller::assert_output_limits;
+
+/// Point-mass longitudinal model: `dv/dt = acceleration_gain * u - drag * v + d`, with `v` in
+/// km/h, `u` the normalized command and `d` an unmodelled acceleration (grade, wind...).
+#[derive(Debug, Clone, Copy)]
+pub struct PointMassModel {
+    /// Acceleration in km/h/s produced by a full command (`u = 1`)
+    pub acceleration_gain: f64,
+    /// Linearized drag and rolling resistance in 1/s
+    pub drag: f64,
+}
+
+impl Default for PointMassModel {
+    // Rough fit of a passenger car; tune for the vehicle in use
+    fn default() -> Self {
+        PointMassModel {
+            acceleration_gain: 10.0,
+            drag: 0.02,
+        }
+    }
+}
+
+/// Model-predictive speed controller over a [`PointMassModel`].
+///
+/// The command is held constant over the prediction horizon, which turns the quadratic cost
+/// (tracking error, command effort and command change) into a scalar problem solved in closed
+/// form and then clipped to the output limits. An observer estimates the unmodelled
+/// acceleration from the one-step prediction error so steady-state offset is removed.
+pub struct ModelPredictiveController {
+    model: PointMassModel,
+    horizon: usize,
+    step: f64,
+    effort_weight: f64,
+    rate_weight: f64,
+    observer_gain: f64,
+    output_limits: (f64, f64),
+    disturbance: f64,
+    previous_velocity: f64,
+    previous_output: f64,
+    previous_time: Option<f64>,
+}
+
+impl ModelPredictiveController {
+    /// Creates a controller predicting `horizon` steps of `step` seconds ahead.
+    pub fn new(model: PointMassModel, horizon: usize, step: f64) -> Self {
+        ModelPredictiveController {
+            model,
+            horizon: horizon.max(1),
+            step,
+            effort_weight: 1.0,
+            rate_weight: 10.0,
+            observer_gain: 0.1,
+            output_limits: (-1.0, 1.0),
+            disturbance: 0.0,
+            previous_velocity: 0.0,
+            previous_output: 0.0,
+            previous_time: None,
+        }
+    }
+
+    /// Cost weights of the command magnitude and of the change from the previous command.
+    pub fn with_weights(mut self, effort_weight: f64, rate_weight: f64) -> Self {
+        self.effort_weight = effort_weight;
+        self.rate_weight = rate_weight;
+        self
+    }
+
+    /// Gain in `(0, 1]` of the disturbance observer; `0.0` disables offset compensation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `observer_gain` is outside `[0, 1]`.
+    pub fn with_observer_gain(mut self, observer_gain: f64) -> Self {
+        assert!((0.0..=1.0).contains(&observer_gain), "observer gain {} is outside [0, 1]", observer_gain);
+        self.observer_gain = observer_gain;
+        self
+    }
+
+    /// Bounds the optimized command to `[min, max]`.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is not below `max`, or either is NaN.
+    pub fn with_output_limits(mut self, min: f64, max: f64) -> Self {
+        assert_output_limits(min, max);
+        self.output_limits = (min, max);
+        self
+    }
+
+    fn model_acceleration(&self, velocity: f64, command: f64) -> f64 {
+        self.model.acceleration_gain * command - self.model.drag * velocity
+    }
+}
+
+impl LongitudinalController for ModelPredictiveController {
+    fn compute(&mut self, desired_velocity: f64, current_velocity: f64, current_time: f64) -> Result<f64, String> {
+        let Some(previous_time) = self.previous_time else {
+            self.previous_time = Some(current_time);
+            self.previous_velocity = current_velocity;
+            return Ok(self.previous_output);
+        };
+
+        let delta_time = current_time - previous_time;
+        self.previous_time = Some(current_time);
+
+        if delta_time <= 0.0 {
+            return Err("delta_time must be positive and higher than 0.".to_string());
+        }
+
+        let measured_acceleration = (current_velocity - self.previous_velocity) / delta_time;
+        let residual = measured_acceleration
+            - self.model_acceleration(self.previous_velocity, self.previous_output);
+        self.disturbance += self.observer_gain * (residual - self.disturbance);
+        self.previous_velocity = current_velocity;
+
+        // Prediction is affine in the held command: v_k = free_k + forced_k * u
+        let decay = 1.0 - self.model.drag * self.step;
+        let mut free = current_velocity;
+        let mut forced = 0.0;
+        let mut numerator = self.rate_weight * self.previous_output;
+        let mut denominator = self.effort_weight + self.rate_weight;
+        for _ in 0..self.horizon {
+            free = free * decay + self.disturbance * self.step;
+            forced = forced * decay + self.model.acceleration_gain * self.step;
+            numerator += forced * (desired_velocity - free);
+            denominator += forced * forced;
+        }
+
+        let (min, max) = self.output_limits;
+        let command = (numerator / denominator).clamp(min, max);
+        self.previous_output = command;
+
+        Ok(command)
+    }
+
+    fn reset(&mut self) {
+        self.disturbance = 0.0;
+        self.previous_velocity = 0.0;
+        self.previous_output = 0.0;
+        self.previous_time = None;
+    }
+
+    fn hold(&mut self) {
+        // Keep the disturbance estimate and the last command
+        self.previous_time = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 0.05;
+
+    fn run(controller: &mut ModelPredictiveController, target: f64, grade: f64, steps: usize) -> (f64, f64) {
+        let plant = PointMassModel { acceleration_gain: 8.0, drag: 0.03 };
+        let mut velocity = 20.0;
+        let mut peak = velocity;
+        for i in 1..=steps {
+            let command = controller.compute(target, velocity, i as f64 * DT).unwrap();
+            assert!((-1.0..=1.0).contains(&command));
+            velocity += (plant.acceleration_gain * command - plant.drag * velocity - grade) * DT;
+            peak = peak.max(velocity);
+        }
+        (velocity, peak)
+    }
+
+    #[test]
+    fn test_converges_despite_model_mismatch() {
+        let mut controller = ModelPredictiveController::new(PointMassModel::default(), 20, 0.1);
+        let (velocity, peak) = run(&mut controller, 50.0, 0.0, 2000);
+
+        assert!((velocity - 50.0).abs() < 0.5, "velocity {}", velocity);
+        assert!(peak < 52.5, "peak {}", peak);
+    }
+
+    #[test]
+    fn test_observer_rejects_constant_disturbance() {
+        let mut with_observer = ModelPredictiveController::new(PointMassModel::default(), 20, 0.1);
+        let mut without_observer = ModelPredictiveController::new(PointMassModel::default(), 20, 0.1)
+            .with_observer_gain(0.0);
+
+        let (observed, _) = run(&mut with_observer, 50.0, 2.0, 3000);
+        let (unobserved, _) = run(&mut without_observer, 50.0, 2.0, 3000);
+
+        assert!((observed - 50.0).abs() < 0.5, "velocity {}", observed);
+        assert!((unobserved - 50.0).abs() > (observed - 50.0).abs());
+    }
+
+    #[test]
+    #[should_panic(expected = "output limits must satisfy min < max")]
+    fn test_nan_output_limits_are_rejected() {
+        let _ = ModelPredictiveController::new(PointMassModel::default(), 20, 0.1).with_output_limits(-1.0, f64::NAN);
+    }
+
+    #[test]
+    #[should_panic(expected = "observer gain 1.5 is outside [0, 1]")]
+    fn test_out_of_range_observer_gain_is_rejected() {
+        let _ = ModelPredictiveController::new(PointMassModel::default(), 20, 0.1).with_observer_gain(1.5);
+    }
+}
@@ -14,14 +14,88 @@
 // limitations under the License.
 //
 
+use serde::{Deserialize, Serialize};
+
+use crate::gain_schedule::GainSchedule;
+
+/// Strategy used to keep the integral term bounded while the output is saturated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AntiWindup {
+    /// Integrate unconditionally (classic PID behaviour).
+    None,
+    /// Stop integrating while the output is saturated and the error would push it further.
+    Clamping,
+    /// Bleed the integrator back by `tracking_gain * (saturated - unsaturated)`.
+    BackCalculation { tracking_gain: f64 },
+}
+
+/// Signal the derivative term is taken from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DerivativeMode {
+    /// Derivative of the velocity error; kicks on every setpoint change.
+    OnError,
+    /// Negative derivative of the measured velocity; ignores setpoint changes.
+    OnMeasurement,
+}
+
+/// Tunable parameters of a [`PIDController`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PidGains {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    /// `[min, max]` saturation of the output, `None` when unbounded
+    pub output_limits: Option<(f64, f64)>,
+}
+
+impl PidGains {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, gain) in [("kp", self.kp), ("ki", self.ki), ("kd", self.kd)] {
+            if !gain.is_finite() || gain < 0.0 {
+                return Err(format!("{} must be a finite, non-negative number (got {})", name, gain));
+            }
+        }
+        if let Some((min, max)) = self.output_limits {
+            if !min.is_finite() || !max.is_finite() || min >= max {
+                return Err(format!("output limits must be finite with min < max (got [{}, {}])", min, max));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Output limits of the `with_output_limits` builders; NaN fails the comparison as well
+pub(crate) fn assert_output_limits(min: f64, max: f64) {
+    assert!(min < max, "output limits must satisfy min < max (got [{}, {}])", min, max);
+}
+
+/// Contributions of the individual terms to the last computed output (before saturation).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PidTerms {
+    pub proportional: f64,
+    pub integral: f64,
+    pub derivative: f64,
+}
+
 pub struct PIDController {
     kp: f64,
     ki: f64,
     kd: f64,
+    output_limits: Option<(f64, f64)>,
+    anti_windup: AntiWindup,
+    derivative_mode: DerivativeMode,
+    derivative_filter_tau: f64,
     velocity_error: f64,
     previous_error: f64,
+    previous_velocity: f64,
+    filtered_derivative: f64,
     accumulated_error: f64,
-    previous_time: f64,
+    /// Integral contribution held constant while Ki is zero, see [`Self::transfer_gains`]
+    integral_bias: f64,
+    previous_time: Option<f64>,
+    output: f64,
+    terms: PidTerms,
+    gain_schedule: Option<GainSchedule>,
 }
 
 impl PIDController {
@@ -30,21 +104,77 @@ impl PIDController {
             kp,
             ki,
             kd,
+            output_limits: None,
+            anti_windup: AntiWindup::None,
+            derivative_mode: DerivativeMode::OnError,
+            derivative_filter_tau: 0.0,
             velocity_error: 0.0,
             previous_error: 0.0,
+            previous_velocity: 0.0,
+            filtered_derivative: 0.0,
             accumulated_error: 0.0,
-            previous_time: 0.0,
+            integral_bias: 0.0,
+            previous_time: None,
+            output: 0.0,
+            terms: PidTerms::default(),
+            gain_schedule: None,
         }
     }
 
+    /// Saturates the controller output to `[min, max]`.
+    ///
+    /// # Panics
+    ///
+    /// If `min` is not below `max`, or either is NaN.
+    pub fn with_output_limits(mut self, min: f64, max: f64) -> Self {
+        assert_output_limits(min, max);
+        self.output_limits = Some((min, max));
+        self
+    }
+
+    pub fn with_anti_windup(mut self, anti_windup: AntiWindup) -> Self {
+        self.anti_windup = anti_windup;
+        self
+    }
+
+    pub fn with_derivative_mode(mut self, derivative_mode: DerivativeMode) -> Self {
+        self.derivative_mode = derivative_mode;
+        self
+    }
+
+    /// Time constant (seconds) of the first-order low-pass applied to the D term.
+    /// A value of `0.0` disables filtering.
+    pub fn with_derivative_filter(mut self, tau: f64) -> Self {
+        self.derivative_filter_tau = tau.max(0.0);
+        self
+    }
+
+    /// Takes Kp/Ki/Kd from `schedule` at the current velocity instead of the constant gains.
+    pub fn with_gain_schedule(mut self, schedule: GainSchedule) -> Self {
+        (self.kp, self.ki, self.kd) = schedule.gains_at(0.0);
+        self.gain_schedule = Some(schedule);
+        self
+    }
+
+    pub fn gain_schedule(&self) -> Option<&GainSchedule> {
+        self.gain_schedule.as_ref()
+    }
+
     pub fn compute(&mut self, desired_velocity: f64, current_velocity: f64, current_time: f64) -> Result<f64, String> {
-        if self.previous_time == 0.0 {
-            self.previous_time = current_time;
-            return Ok(0.0);
+        if let Some(schedule) = &self.gain_schedule {
+            let (kp, ki, kd) = schedule.gains_at(current_velocity);
+            self.transfer_gains(kp, ki, kd);
         }
 
-        let delta_time = current_time - self.previous_time;
-        self.previous_time = current_time;
+        // The first sample (after a reset or hold) only initializes the timing
+        let Some(previous_time) = self.previous_time else {
+            self.previous_time = Some(current_time);
+            self.previous_velocity = current_velocity;
+            return Ok(self.output);
+        };
+
+        let delta_time = current_time - previous_time;
+        self.previous_time = Some(current_time);
 
         if delta_time <= 0.0 {
             return Err("delta_time must be positive and higher than 0.".to_string());
@@ -52,12 +182,50 @@ impl PIDController {
 
         self.previous_error = self.velocity_error;
         self.velocity_error = desired_velocity - current_velocity;
-        self.accumulated_error += self.velocity_error * delta_time;
-        let derivative_error = (self.velocity_error - self.previous_error) / delta_time;
 
-        let acceleration = (self.kp * self.velocity_error)
-            + (self.ki * self.accumulated_error)
-            + (self.kd * derivative_error);
+        let raw_derivative = match self.derivative_mode {
+            DerivativeMode::OnError => (self.velocity_error - self.previous_error) / delta_time,
+            DerivativeMode::OnMeasurement => -(current_velocity - self.previous_velocity) / delta_time,
+        };
+        self.previous_velocity = current_velocity;
+
+        self.filtered_derivative = if self.derivative_filter_tau > 0.0 {
+            let alpha = delta_time / (self.derivative_filter_tau + delta_time);
+            self.filtered_derivative + alpha * (raw_derivative - self.filtered_derivative)
+        } else {
+            raw_derivative
+        };
+
+        let proportional = self.kp * self.velocity_error;
+        let derivative = self.kd * self.filtered_derivative;
+        let candidate_integral = self.accumulated_error + self.velocity_error * delta_time;
+        let unsaturated = proportional + self.ki * candidate_integral + self.integral_bias + derivative;
+        let acceleration = self.saturate(unsaturated);
+
+        match self.anti_windup {
+            AntiWindup::None => self.accumulated_error = candidate_integral,
+            AntiWindup::Clamping => {
+                // Only freeze the integrator when the error drives the output deeper into saturation
+                let winding_up = (unsaturated > acceleration && self.velocity_error > 0.0)
+                    || (unsaturated < acceleration && self.velocity_error < 0.0);
+                if !winding_up {
+                    self.accumulated_error = candidate_integral;
+                }
+            }
+            AntiWindup::BackCalculation { tracking_gain } => {
+                self.accumulated_error = candidate_integral;
+                if self.ki != 0.0 {
+                    self.accumulated_error += tracking_gain * (acceleration - unsaturated) / self.ki * delta_time;
+                }
+            }
+        }
+
+        self.terms = PidTerms {
+            proportional,
+            integral: self.ki * self.accumulated_error + self.integral_bias,
+            derivative,
+        };
+        self.output = acceleration;
 
         Ok(acceleration)
     }
@@ -65,7 +233,319 @@ impl PIDController {
     pub fn reset(&mut self) {
         self.velocity_error = 0.0;
         self.previous_error = 0.0;
+        self.previous_velocity = 0.0;
+        self.filtered_derivative = 0.0;
         self.accumulated_error = 0.0;
-        self.previous_time = 0.0;
+        self.integral_bias = 0.0;
+        self.previous_time = None;
+        self.output = 0.0;
+        self.terms = PidTerms::default();
+    }
+
+    /// Restarts the timing while keeping the integrator, e.g. after a pause of the clock:
+    /// the next sample repeats the last output instead of integrating over the pause.
+    pub fn hold(&mut self) {
+        self.previous_time = None;
+        self.filtered_derivative = 0.0;
+    }
+
+    pub fn terms(&self) -> PidTerms {
+        self.terms
+    }
+
+    pub fn gains(&self) -> PidGains {
+        PidGains {
+            kp: self.kp,
+            ki: self.ki,
+            kd: self.kd,
+            output_limits: self.output_limits,
+        }
+    }
+
+    /// Applies new gains with bumpless transfer (see [`Self::transfer_gains`]). With a gain
+    /// schedule, Kp/Ki/Kd follow the schedule and only the output limits can be changed.
+    pub fn set_gains(&mut self, gains: PidGains) -> Result<(), String> {
+        gains.validate()?;
+
+        if self.gain_schedule.is_some() && (gains.kp, gains.ki, gains.kd) != (self.kp, self.ki, self.kd) {
+            return Err("kp, ki and kd follow the gain schedule".to_string());
+        }
+
+        self.transfer_gains(gains.kp, gains.ki, gains.kd);
+        self.output_limits = gains.output_limits;
+        Ok(())
+    }
+
+    /// Re-initializes the integrator so that the output for the last computed error and
+    /// derivative is the same before and after the change. With a zero Ki the integral
+    /// contribution is held as a constant bias instead, until Ki becomes non-zero again.
+    fn transfer_gains(&mut self, kp: f64, ki: f64, kd: f64) {
+        if (kp, ki, kd) == (self.kp, self.ki, self.kd) {
+            return;
+        }
+
+        let previous_output = self.kp * self.velocity_error
+            + self.ki * self.accumulated_error
+            + self.integral_bias
+            + self.kd * self.filtered_derivative;
+        let integral = previous_output - kp * self.velocity_error - kd * self.filtered_derivative;
+        if ki != 0.0 {
+            self.accumulated_error = integral / ki;
+            self.integral_bias = 0.0;
+        } else {
+            self.accumulated_error = 0.0;
+            self.integral_bias = integral;
+        }
+
+        self.kp = kp;
+        self.ki = ki;
+        self.kd = kd;
+    }
+
+    // Limits computed by a controller built on this one, e.g. the room left by a feed-forward term
+    pub(crate) fn set_output_limits(&mut self, output_limits: Option<(f64, f64)>) {
+        self.output_limits = output_limits;
+    }
+
+    fn saturate(&self, value: f64) -> f64 {
+        match self.output_limits {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 0.05;
+
+    // First-order longitudinal plant: dv/dt = gain * u - drag * v
+    struct Plant {
+        velocity: f64,
+        gain: f64,
+        drag: f64,
+    }
+
+    impl Plant {
+        fn new() -> Self {
+            Plant { velocity: 0.0, gain: 4.0, drag: 0.05 }
+        }
+
+        fn step(&mut self, acceleration: f64) {
+            self.velocity += (self.gain * acceleration - self.drag * self.velocity) * DT;
+        }
+    }
+
+    // Drives the plant towards `target` and returns (peak velocity, outputs)
+    fn run(pid: &mut PIDController, target: f64, steps: usize) -> (f64, Vec<f64>) {
+        let mut plant = Plant::new();
+        let mut peak = f64::MIN;
+        let mut outputs = Vec::with_capacity(steps);
+        for i in 1..=steps {
+            let acceleration = pid.compute(target, plant.velocity, i as f64 * DT).unwrap();
+            plant.step(acceleration);
+            peak = peak.max(plant.velocity);
+            outputs.push(acceleration);
+        }
+        (peak, outputs)
+    }
+
+    #[test]
+    fn test_output_is_saturated() {
+        let mut pid = PIDController::new(2.0, 0.5, 0.0).with_output_limits(-1.0, 1.0);
+        let (_, outputs) = run(&mut pid, 30.0, 400);
+
+        assert!(outputs.iter().all(|a| (-1.0..=1.0).contains(a)));
+        assert!(outputs.contains(&1.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "output limits must satisfy min < max")]
+    fn test_inverted_output_limits_are_rejected() {
+        let _ = PIDController::new(1.0, 0.0, 0.0).with_output_limits(1.0, -1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "output limits must satisfy min < max")]
+    fn test_nan_output_limits_are_rejected() {
+        let _ = PIDController::new(1.0, 0.0, 0.0).with_output_limits(f64::NAN, 1.0);
+    }
+
+    #[test]
+    fn test_anti_windup_reduces_overshoot() {
+        let target = 20.0;
+        let mut plain = PIDController::new(0.5, 0.2, 0.0).with_output_limits(-1.0, 1.0);
+        let (plain_peak, _) = run(&mut plain, target, 1200);
+
+        for anti_windup in [AntiWindup::Clamping, AntiWindup::BackCalculation { tracking_gain: 2.0 }] {
+            let mut pid = PIDController::new(0.5, 0.2, 0.0)
+                .with_output_limits(-1.0, 1.0)
+                .with_anti_windup(anti_windup);
+            let (peak, _) = run(&mut pid, target, 1200);
+
+            assert!(peak < plain_peak, "{:?}: peak {} not below {}", anti_windup, peak, plain_peak);
+            assert!(peak - target < 0.05 * target, "{:?}: overshoot {} too large", anti_windup, peak - target);
+        }
+    }
+
+    #[test]
+    fn test_anti_windup_still_converges() {
+        let mut pid = PIDController::new(0.5, 0.2, 0.0)
+            .with_output_limits(-1.0, 1.0)
+            .with_anti_windup(AntiWindup::Clamping);
+        let mut plant = Plant::new();
+        for i in 1..=4000 {
+            let acceleration = pid.compute(20.0, plant.velocity, i as f64 * DT).unwrap();
+            plant.step(acceleration);
+        }
+
+        assert!((plant.velocity - 20.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_derivative_on_measurement_has_no_setpoint_kick() {
+        let mut on_error = PIDController::new(0.0, 0.0, 1.0);
+        let mut on_measurement = PIDController::new(0.0, 0.0, 1.0)
+            .with_derivative_mode(DerivativeMode::OnMeasurement);
+
+        for pid in [&mut on_error, &mut on_measurement] {
+            pid.compute(10.0, 10.0, DT).unwrap();
+            pid.compute(10.0, 10.0, 2.0 * DT).unwrap();
+        }
+
+        // Setpoint jumps while the measured velocity stays constant
+        let kick_error = on_error.compute(20.0, 10.0, 3.0 * DT).unwrap();
+        let kick_measurement = on_measurement.compute(20.0, 10.0, 3.0 * DT).unwrap();
+
+        assert!((kick_error - 10.0 / DT).abs() < 1e-9);
+        assert_eq!(kick_measurement, 0.0);
+    }
+
+    #[test]
+    fn test_derivative_filter_attenuates_noise() {
+        let noisy = |i: usize| 10.0 + if i.is_multiple_of(2) { 0.2 } else { -0.2 };
+        let mut raw = PIDController::new(0.0, 0.0, 1.0).with_derivative_mode(DerivativeMode::OnMeasurement);
+        let mut filtered = PIDController::new(0.0, 0.0, 1.0)
+            .with_derivative_mode(DerivativeMode::OnMeasurement)
+            .with_derivative_filter(0.5);
+
+        let mut raw_peak: f64 = 0.0;
+        let mut filtered_peak: f64 = 0.0;
+        for i in 1..=200 {
+            let t = i as f64 * DT;
+            raw_peak = raw_peak.max(raw.compute(10.0, noisy(i), t).unwrap().abs());
+            filtered_peak = filtered_peak.max(filtered.compute(10.0, noisy(i), t).unwrap().abs());
+        }
+
+        assert!(filtered_peak < 0.2 * raw_peak, "filtered {} vs raw {}", filtered_peak, raw_peak);
+    }
+
+    #[test]
+    fn test_gain_change_is_bumpless() {
+        // Constant error of 10 km/h: only the integrator moves between samples
+        let mut pid = PIDController::new(0.5, 0.2, 0.05);
+        let mut last_output = 0.0;
+        for i in 1..=100 {
+            last_output = pid.compute(20.0, 10.0, i as f64 * DT).unwrap();
+        }
+
+        pid.set_gains(PidGains { kp: 1.0, ki: 0.4, kd: 0.0, output_limits: None }).unwrap();
+        let output = pid.compute(20.0, 10.0, 101.0 * DT).unwrap();
+
+        assert!((output - (last_output + 0.4 * 10.0 * DT)).abs() < 1e-9, "output jumped from {} to {}", last_output, output);
+    }
+
+    #[test]
+    fn test_gain_change_to_zero_ki_keeps_the_integral() {
+        let mut pid = PIDController::new(0.5, 0.2, 0.0);
+        let mut last_output = 0.0;
+        for i in 1..=100 {
+            last_output = pid.compute(20.0, 10.0, i as f64 * DT).unwrap();
+        }
+
+        // The integral is held while Ki is zero instead of dropping out of the output
+        pid.set_gains(PidGains { kp: 0.5, ki: 0.0, kd: 0.0, output_limits: None }).unwrap();
+        let output = pid.compute(20.0, 10.0, 101.0 * DT).unwrap();
+        assert!((output - last_output).abs() < 1e-9, "output jumped from {} to {}", last_output, output);
+
+        pid.set_gains(PidGains { kp: 0.5, ki: 0.2, kd: 0.0, output_limits: None }).unwrap();
+        let resumed = pid.compute(20.0, 10.0, 102.0 * DT).unwrap();
+        assert!((resumed - (output + 0.2 * 10.0 * DT)).abs() < 1e-9, "output jumped from {} to {}", output, resumed);
+    }
+
+    #[test]
+    fn test_scheduled_gains_are_bumpless() {
+        use crate::gain_schedule::Breakpoint;
+
+        let schedule = GainSchedule::new(vec![
+            Breakpoint { speed: 20.0, kp: 0.4, ki: 0.1, kd: 0.0 },
+            Breakpoint { speed: 120.0, kp: 0.1, ki: 0.02, kd: 0.0 },
+        ])
+        .unwrap();
+        let mut pid = PIDController::new(1.0, 1.0, 1.0).with_gain_schedule(schedule);
+        assert_eq!((pid.gains().kp, pid.gains().ki), (0.4, 0.1));
+
+        // Accelerating with a constant error of 10 km/h: only the integrator may move the output
+        pid.compute(30.0, 20.0, 0.0).unwrap();
+        let mut last_output = pid.compute(30.0, 20.0, DT).unwrap();
+        for i in 2..=110 {
+            let velocity = 18.0 + i as f64;
+            let output = pid.compute(velocity + 10.0, velocity, i as f64 * DT).unwrap();
+            let ki = pid.gains().ki;
+            assert!((output - (last_output + ki * 10.0 * DT)).abs() < 1e-9, "output jumped from {} to {}", last_output, output);
+            last_output = output;
+        }
+        assert!((pid.gains().kp - 0.1).abs() < 1e-12);
+
+        // Scheduled gains cannot be overridden, the output limits can
+        let gains = pid.gains();
+        assert!(pid.set_gains(PidGains { kp: 0.5, ..gains }).is_err());
+        assert!(pid.set_gains(PidGains { output_limits: Some((-0.5, 0.5)), ..gains }).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_gains_are_rejected() {
+        let mut pid = PIDController::new(0.5, 0.2, 0.05);
+
+        assert!(pid.set_gains(PidGains { kp: -1.0, ki: 0.0, kd: 0.0, output_limits: None }).is_err());
+        assert!(pid.set_gains(PidGains { kp: 1.0, ki: f64::NAN, kd: 0.0, output_limits: None }).is_err());
+        assert!(pid.set_gains(PidGains { kp: 1.0, ki: 0.0, kd: 0.0, output_limits: Some((1.0, -1.0)) }).is_err());
+        assert_eq!(pid.gains().kp, 0.5);
+    }
+
+    #[test]
+    fn test_non_positive_delta_time_is_rejected() {
+        let mut pid = PIDController::new(1.0, 0.0, 0.0);
+        pid.compute(10.0, 0.0, 1.0).unwrap();
+
+        assert!(pid.compute(10.0, 0.0, 1.0).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_clock_starting_at_zero_is_a_valid_first_sample() {
+        let mut pid = PIDController::new(1.0, 0.0, 0.0);
+        assert_eq!(pid.compute(10.0, 0.0, 0.0).unwrap(), 0.0);
+
+        // The second sample is integrated over [0, 0.1] instead of being taken as the first one
+        assert_eq!(pid.compute(10.0, 0.0, 0.1).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn test_hold_keeps_the_integrator() {
+        let mut pid = PIDController::new(0.0, 1.0, 0.0);
+        pid.compute(10.0, 0.0, 0.0).unwrap();
+        let before = pid.compute(10.0, 0.0, 1.0).unwrap();
+        assert!((before - 10.0).abs() < 1e-9);
+
+        // A 60 s pause neither winds the integrator up nor drops it
+        pid.hold();
+        assert_eq!(pid.compute(10.0, 0.0, 61.0).unwrap(), before);
+        let after = pid.compute(10.0, 0.0, 62.0).unwrap();
+        assert!((after - 20.0).abs() < 1e-9);
+
+        pid.reset();
+        assert_eq!(pid.compute(10.0, 0.0, 63.0).unwrap(), 0.0);
+    }
+}
@@ -0,0 +1,775 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use log::{error, info};
+use parquet::data_type::{BoolType, DoubleType};
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::writer::SerializedFileWriter;
+use parquet::record::RowAccessor;
+use parquet::schema::parser::parse_message_type;
+
+use crate::pid_controller::PidGains;
+
+// Buffers worth of rows kept while the files cannot be written, older rows are dropped
+const MAX_PENDING_BUFFERS: usize = 8;
+
+// Rows queued for the writer thread, further rows are dropped until it catches up
+const QUEUE_ROWS: usize = 4096;
+
+// The writer thread writes out the buffered rows at least this often
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+const COLUMNS: [&str; 10] = [
+    "wall_time",
+    "time",
+    "desired_velocity",
+    "current_velocity",
+    "acceleration",
+    "proportional",
+    "integral",
+    "derivative",
+    "engaged",
+    "following",
+];
+
+// Recordings from before the `following` column
+const LEGACY_COLUMNS: usize = 9;
+
+/// One control sample. Values that do not apply (e.g. the command while disengaged or the
+/// PID terms of a non-PID controller) are `NaN`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordRow {
+    /// Receive time in seconds since the Unix epoch
+    pub wall_time: f64,
+    /// Clock topic time in seconds
+    pub time: f64,
+    pub desired_velocity: f64,
+    pub current_velocity: f64,
+    pub acceleration: f64,
+    pub proportional: f64,
+    pub integral: f64,
+    pub derivative: f64,
+    pub engaged: bool,
+    /// The ACC tracks the spacing speed to a lead vehicle instead of the set speed
+    pub following: bool,
+}
+
+impl RecordRow {
+    fn from_values(values: [f64; 8], engaged: bool, following: bool) -> Self {
+        RecordRow {
+            wall_time: values[0],
+            time: values[1],
+            desired_velocity: values[2],
+            current_velocity: values[3],
+            acceleration: values[4],
+            proportional: values[5],
+            integral: values[6],
+            derivative: values[7],
+            engaged,
+            following,
+        }
+    }
+
+    fn values(&self) -> [f64; 8] {
+        [
+            self.wall_time,
+            self.time,
+            self.desired_velocity,
+            self.current_velocity,
+            self.acceleration,
+            self.proportional,
+            self.integral,
+            self.derivative,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordFormat {
+    Csv,
+    Parquet,
+}
+
+impl RecordFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            RecordFormat::Csv => "csv",
+            RecordFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub directory: PathBuf,
+    pub format: RecordFormat,
+    /// Rows kept in memory before they are written out
+    pub buffer_rows: usize,
+    /// Rows per file before the recorder rotates to a new one
+    pub max_rows_per_file: usize,
+}
+
+/// Header written to every file of a session.
+#[derive(Debug, Clone)]
+pub struct SessionMetadata {
+    pub controller: String,
+    pub gains: Option<PidGains>,
+    pub git_revision: String,
+    /// Session start in seconds since the Unix epoch
+    pub start_time: f64,
+}
+
+impl SessionMetadata {
+    fn entries(&self) -> Vec<(String, String)> {
+        vec![
+            ("controller".to_string(), self.controller.clone()),
+            ("gains".to_string(), serde_json::to_string(&self.gains).unwrap()),
+            ("git_revision".to_string(), self.git_revision.clone()),
+            ("start_time".to_string(), format!("{:.3}", self.start_time)),
+        ]
+    }
+}
+
+/// Running statistics over the engaged samples, kept in constant memory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecordSummary {
+    pub data_points: usize,
+    pub min_error: f64,
+    pub max_error: f64,
+    pub sum_error: f64,
+    pub min_acceleration: f64,
+    pub max_acceleration: f64,
+    pub sum_acceleration: f64,
+}
+
+impl RecordSummary {
+    fn add(&mut self, row: &RecordRow) {
+        if !row.engaged || row.acceleration.is_nan() {
+            return;
+        }
+        let error = row.desired_velocity - row.current_velocity;
+        if self.data_points == 0 {
+            self.min_error = error;
+            self.max_error = error;
+            self.min_acceleration = row.acceleration;
+            self.max_acceleration = row.acceleration;
+        }
+        self.data_points += 1;
+        self.min_error = self.min_error.min(error);
+        self.max_error = self.max_error.max(error);
+        self.sum_error += error;
+        self.min_acceleration = self.min_acceleration.min(row.acceleration);
+        self.max_acceleration = self.max_acceleration.max(row.acceleration);
+        self.sum_acceleration += row.acceleration;
+    }
+}
+
+trait RecordSink: Send {
+    fn write_rows(&mut self, rows: &[RecordRow]) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()>;
+
+    fn close(self: Box<Self>) -> io::Result<()>;
+}
+
+struct CsvSink {
+    writer: BufWriter<File>,
+}
+
+impl CsvSink {
+    fn create(path: &PathBuf, metadata: &SessionMetadata) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for (key, value) in metadata.entries() {
+            writeln!(writer, "# {}: {}", key, value)?;
+        }
+        writeln!(writer, "{}", COLUMNS.join(","))?;
+        Ok(CsvSink { writer })
+    }
+}
+
+impl RecordSink for CsvSink {
+    fn write_rows(&mut self, rows: &[RecordRow]) -> io::Result<()> {
+        for row in rows {
+            for value in row.values() {
+                write!(self.writer, "{},", value)?;
+            }
+            writeln!(self.writer, "{},{}", row.engaged as u8, row.following as u8)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn close(mut self: Box<Self>) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+struct ParquetSink {
+    writer: SerializedFileWriter<File>,
+}
+
+impl ParquetSink {
+    fn create(path: &PathBuf, metadata: &SessionMetadata) -> io::Result<Self> {
+        let fields: Vec<String> = COLUMNS[..8]
+            .iter()
+            .map(|name| format!("REQUIRED DOUBLE {};", name))
+            .collect();
+        let message = format!(
+            "message record {{ {} REQUIRED BOOLEAN engaged; REQUIRED BOOLEAN following; }}",
+            fields.join(" ")
+        );
+        let schema = parse_message_type(&message).map_err(io::Error::other)?;
+
+        let key_values = metadata
+            .entries()
+            .into_iter()
+            .map(|(key, value)| KeyValue::new(key, value))
+            .collect();
+        let properties = WriterProperties::builder()
+            .set_key_value_metadata(Some(key_values))
+            .build();
+
+        let writer = SerializedFileWriter::new(File::create(path)?, Arc::new(schema), Arc::new(properties))
+            .map_err(io::Error::other)?;
+        Ok(ParquetSink { writer })
+    }
+}
+
+impl RecordSink for ParquetSink {
+    // Every batch becomes one row group
+    fn write_rows(&mut self, rows: &[RecordRow]) -> io::Result<()> {
+        let mut row_group = self.writer.next_row_group().map_err(io::Error::other)?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column().map_err(io::Error::other)? {
+            if index < 8 {
+                let values: Vec<f64> = rows.iter().map(|row| row.values()[index]).collect();
+                column.typed::<DoubleType>().write_batch(&values, None, None).map_err(io::Error::other)?;
+            } else {
+                let values: Vec<bool> = rows.iter().map(|row| [row.engaged, row.following][index - 8]).collect();
+                column.typed::<BoolType>().write_batch(&values, None, None).map_err(io::Error::other)?;
+            }
+            column.close().map_err(io::Error::other)?;
+            index += 1;
+        }
+        row_group.close().map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    // Row groups go straight to the file
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn close(self: Box<Self>) -> io::Result<()> {
+        self.writer.close().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+/// Streams control samples to rotating CSV or Parquet files with bounded memory.
+///
+/// Rows are buffered up to `buffer_rows` and then appended to the current file, which is
+/// replaced by a new one once it holds `max_rows_per_file` rows. Without a configuration the
+/// recorder only maintains the [`RecordSummary`].
+pub struct Recorder {
+    config: Option<RecorderConfig>,
+    metadata: SessionMetadata,
+    buffer: Vec<RecordRow>,
+    sink: Option<Box<dyn RecordSink>>,
+    rows_in_file: usize,
+    file_index: usize,
+    files: Vec<PathBuf>,
+    summary: RecordSummary,
+    dropped_rows: usize,
+}
+
+impl Recorder {
+    pub fn new(config: RecorderConfig, metadata: SessionMetadata) -> Self {
+        Recorder {
+            buffer: Vec::with_capacity(config.buffer_rows),
+            config: Some(config),
+            metadata,
+            sink: None,
+            rows_in_file: 0,
+            file_index: 0,
+            files: Vec::new(),
+            summary: RecordSummary::default(),
+            dropped_rows: 0,
+        }
+    }
+
+    /// A recorder that keeps the summary but writes no files.
+    pub fn summary_only() -> Self {
+        Recorder {
+            config: None,
+            metadata: SessionMetadata {
+                controller: String::new(),
+                gains: None,
+                git_revision: String::new(),
+                start_time: 0.0,
+            },
+            buffer: Vec::new(),
+            sink: None,
+            rows_in_file: 0,
+            file_index: 0,
+            files: Vec::new(),
+            summary: RecordSummary::default(),
+            dropped_rows: 0,
+        }
+    }
+
+    pub fn summary(&self) -> RecordSummary {
+        self.summary
+    }
+
+    /// Rows dropped because they could not be written.
+    pub fn dropped_rows(&self) -> usize {
+        self.dropped_rows
+    }
+
+    /// Files written so far, in recording order.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn record(&mut self, row: RecordRow) -> io::Result<()> {
+        self.summary.add(&row);
+        let Some(config) = &self.config else {
+            return Ok(());
+        };
+
+        self.buffer.push(row);
+        if self.buffer.len() >= config.buffer_rows.max(1) {
+            let config = config.clone();
+            self.write_pending(&config)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered rows and flushes the current file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let Some(config) = self.config.clone() else {
+            return Ok(());
+        };
+
+        self.write_pending(&config)?;
+        if let Some(sink) = self.sink.as_mut() {
+            if let Err(e) = sink.flush() {
+                self.sink = None;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    // Writes the buffered rows, rotating files as needed. Written rows leave the buffer right
+    // away; on failure the others are kept for the next attempt, up to `MAX_PENDING_BUFFERS`
+    // buffers, and the oldest ones beyond that are dropped.
+    fn write_pending(&mut self, config: &RecorderConfig) -> io::Result<()> {
+        let result = self.write_buffer(config);
+        let capacity = config.buffer_rows.max(1) * MAX_PENDING_BUFFERS;
+        if result.is_err() && self.buffer.len() > capacity {
+            let dropped = self.buffer.len() - capacity;
+            self.buffer.drain(..dropped);
+            self.dropped_rows += dropped;
+            error!("Recorder dropped {} rows ({} in total)", dropped, self.dropped_rows);
+        }
+        result
+    }
+
+    fn write_buffer(&mut self, config: &RecorderConfig) -> io::Result<()> {
+        while !self.buffer.is_empty() {
+            if self.sink.is_none() || self.rows_in_file >= config.max_rows_per_file.max(1) {
+                self.rotate(config)?;
+            }
+            let room = config.max_rows_per_file.max(1) - self.rows_in_file;
+            let end = self.buffer.len().min(room);
+            if let Err(e) = self.sink.as_mut().unwrap().write_rows(&self.buffer[..end]) {
+                // The file may hold part of the rows, the next flush starts a new one
+                self.sink = None;
+                return Err(e);
+            }
+            self.rows_in_file += end;
+            self.buffer.drain(..end);
+        }
+        Ok(())
+    }
+
+    /// Flushes the remaining rows and finalizes the current file.
+    pub fn close(&mut self) -> io::Result<()> {
+        self.flush()?;
+        if let Some(sink) = self.sink.take() {
+            sink.close()?;
+        }
+        Ok(())
+    }
+
+    fn rotate(&mut self, config: &RecorderConfig) -> io::Result<()> {
+        if let Some(sink) = self.sink.take() {
+            sink.close()?;
+        }
+        std::fs::create_dir_all(&config.directory)?;
+
+        let path = config.directory.join(format!(
+            "session_{}_{:03}.{}",
+            self.metadata.start_time as u64,
+            self.file_index,
+            config.format.extension()
+        ));
+        let sink: Box<dyn RecordSink> = match config.format {
+            RecordFormat::Csv => Box::new(CsvSink::create(&path, &self.metadata)?),
+            RecordFormat::Parquet => Box::new(ParquetSink::create(&path, &self.metadata)?),
+        };
+        info!("Recording to {}", path.display());
+
+        self.sink = Some(sink);
+        self.files.push(path);
+        self.rows_in_file = 0;
+        self.file_index += 1;
+        Ok(())
+    }
+}
+
+/// Queues rows for a [`RecorderThread`] without ever blocking the caller.
+#[derive(Clone)]
+pub struct RowSender {
+    rows: SyncSender<RecordRow>,
+    dropped_rows: Arc<AtomicUsize>,
+}
+
+impl RowSender {
+    /// Queues `row`, or drops it while the queue is full.
+    pub fn send(&self, row: RecordRow) {
+        if let Err(TrySendError::Full(_)) = self.rows.try_send(row) {
+            self.dropped_rows.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Feeds a [`Recorder`] from a dedicated thread, so that the control task hands over its rows
+/// instead of waiting for the file I/O.
+///
+/// The thread writes out the buffered rows every `FLUSH_INTERVAL` and when the last
+/// [`RowSender`] is dropped.
+pub struct RecorderThread {
+    rows: RowSender,
+    thread: JoinHandle<()>,
+}
+
+impl RecorderThread {
+    pub fn spawn(recorder: Arc<Mutex<Recorder>>) -> Self {
+        let (rows, receiver) = sync_channel(QUEUE_ROWS);
+        let dropped_rows = Arc::new(AtomicUsize::new(0));
+        let queue_drops = dropped_rows.clone();
+        let thread = std::thread::spawn(move || {
+            Self::run(&recorder, receiver);
+            recorder.lock().unwrap().dropped_rows += queue_drops.load(Ordering::Relaxed);
+        });
+        RecorderThread { rows: RowSender { rows, dropped_rows }, thread }
+    }
+
+    fn run(recorder: &Mutex<Recorder>, receiver: Receiver<RecordRow>) {
+        loop {
+            let result = match receiver.recv_timeout(FLUSH_INTERVAL) {
+                Ok(row) => recorder.lock().unwrap().record(row),
+                Err(RecvTimeoutError::Timeout) => recorder.lock().unwrap().flush(),
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            if let Err(e) = result {
+                error!("Failed to record samples: {}", e);
+            }
+        }
+    }
+
+    pub fn sender(&self) -> RowSender {
+        self.rows.clone()
+    }
+
+    /// Waits until the queued rows reached the recorder, once the other senders are dropped.
+    pub fn join(self) {
+        drop(self.rows);
+        if self.thread.join().is_err() {
+            error!("Recorder thread panicked");
+        }
+    }
+}
+
+/// Reads back the rows of a file written by the [`Recorder`], based on its extension.
+pub fn read_rows(path: &Path) -> io::Result<Vec<RecordRow>> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("parquet") => read_parquet(path),
+        _ => read_csv(path),
+    }
+}
+
+fn read_csv(path: &Path) -> io::Result<Vec<RecordRow>> {
+    let mut rows = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.starts_with('#') || line.starts_with(COLUMNS[0]) || line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() != COLUMNS.len() && fields.len() != LEGACY_COLUMNS {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("malformed row '{}'", line)));
+        }
+        let mut values = [0.0; 8];
+        for (value, field) in values.iter_mut().zip(&fields) {
+            *value = field
+                .parse()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid value '{}'", field)))?;
+        }
+        let following = fields.get(9).is_some_and(|field| *field == "1");
+        rows.push(RecordRow::from_values(values, fields[8] == "1", following));
+    }
+    Ok(rows)
+}
+
+fn read_parquet(path: &Path) -> io::Result<Vec<RecordRow>> {
+    let reader = SerializedFileReader::new(File::open(path)?).map_err(io::Error::other)?;
+    let mut rows = Vec::new();
+    for row in reader.get_row_iter(None).map_err(io::Error::other)? {
+        let row = row.map_err(io::Error::other)?;
+        let mut values = [0.0; 8];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = row.get_double(index).map_err(io::Error::other)?;
+        }
+        let engaged = row.get_bool(8).map_err(io::Error::other)?;
+        let following = row.len() > LEGACY_COLUMNS && row.get_bool(9).map_err(io::Error::other)?;
+        rows.push(RecordRow::from_values(values, engaged, following));
+    }
+    Ok(rows)
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(i: usize) -> RecordRow {
+        RecordRow {
+            wall_time: 1000.0 + i as f64,
+            time: i as f64 * 0.1,
+            desired_velocity: 50.0,
+            current_velocity: 40.0 + i as f64,
+            acceleration: 0.5,
+            proportional: 0.4,
+            integral: 0.1,
+            derivative: f64::NAN,
+            engaged: !i.is_multiple_of(3),
+            following: i.is_multiple_of(4),
+        }
+    }
+
+    fn recorder(format: RecordFormat, name: &str) -> (Recorder, PathBuf) {
+        let directory = std::env::temp_dir().join(format!("recorder_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        let config = RecorderConfig {
+            directory: directory.clone(),
+            format,
+            buffer_rows: 4,
+            max_rows_per_file: 10,
+        };
+        let metadata = SessionMetadata {
+            controller: "pid".to_string(),
+            gains: Some(PidGains { kp: 0.125, ki: 0.015625, kd: 0.0125, output_limits: Some((-1.0, 1.0)) }),
+            git_revision: "abc1234".to_string(),
+            start_time: 1234.5,
+        };
+        (Recorder::new(config, metadata), directory)
+    }
+
+    fn files(directory: &PathBuf) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_csv_rotation_and_header() {
+        let (mut recorder, directory) = recorder(RecordFormat::Csv, "csv");
+        for i in 0..25 {
+            recorder.record(row(i)).unwrap();
+            assert!(recorder.buffer.len() < 4);
+        }
+        recorder.close().unwrap();
+
+        let files = files(&directory);
+        assert_eq!(files.len(), 3);
+        let content = std::fs::read_to_string(&files[0]).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "# controller: pid");
+        assert!(lines[1].starts_with("# gains: {\"kp\":0.125"));
+        assert_eq!(lines[2], "# git_revision: abc1234");
+        assert_eq!(lines[3], "# start_time: 1234.500");
+        assert_eq!(lines[4], COLUMNS.join(","));
+        assert_eq!(lines[5], "1000,0,50,40,0.5,0.4,0.1,NaN,0,1");
+        assert_eq!(lines.len(), 5 + 10);
+        assert_eq!(std::fs::read_to_string(&files[2]).unwrap().lines().count(), 5 + 5);
+        assert_eq!(recorder.files(), &files[..]);
+
+        let rows = read_rows(&files[1]).unwrap();
+        assert_eq!(rows.len(), 10);
+        assert_eq!(rows[0].current_velocity, 50.0);
+        assert!(rows[0].derivative.is_nan());
+        assert_eq!(rows[2].engaged, !12usize.is_multiple_of(3));
+        assert!(rows[2].following);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_parquet_rows_and_metadata() {
+        let (mut recorder, directory) = recorder(RecordFormat::Parquet, "parquet");
+        for i in 0..15 {
+            recorder.record(row(i)).unwrap();
+        }
+        recorder.close().unwrap();
+
+        let files = files(&directory);
+        assert_eq!(files.len(), 2);
+        let reader = SerializedFileReader::new(File::open(&files[0]).unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 10);
+        assert_eq!(metadata.schema_descr().num_columns(), COLUMNS.len());
+        let git_revision = metadata
+            .key_value_metadata()
+            .unwrap()
+            .iter()
+            .find(|kv| kv.key == "git_revision")
+            .and_then(|kv| kv.value.clone());
+        assert_eq!(git_revision.as_deref(), Some("abc1234"));
+
+        let rows = read_rows(&files[1]).unwrap();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[4].time, row(14).time);
+        assert_eq!(rows[4].engaged, row(14).engaged);
+        assert_eq!(rows[2].following, row(12).following);
+        assert_eq!(rows[4].following, row(14).following);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_reads_recordings_without_the_following_column() {
+        let path = std::env::temp_dir().join(format!("recorder_legacy_{}.csv", std::process::id()));
+        let header = COLUMNS[..LEGACY_COLUMNS].join(",");
+        std::fs::write(&path, format!("{}\n1000,0,50,40,0.5,0.4,0.1,NaN,1\n", header)).unwrap();
+
+        let rows = read_rows(&path).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].engaged);
+        assert!(!rows[0].following);
+
+        std::fs::write(&path, "1000,0,50,40,0.5,0.4,0.1\n").unwrap();
+        assert_eq!(read_rows(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    struct FailingSink;
+
+    impl RecordSink for FailingSink {
+        fn write_rows(&mut self, _rows: &[RecordRow]) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(self: Box<Self>) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_failed_writes_bound_the_buffer() {
+        let (mut recorder, directory) = recorder(RecordFormat::Csv, "failing");
+        recorder.sink = Some(Box::new(FailingSink));
+        for i in 0..40 {
+            assert_eq!(recorder.record(row(i)).is_err(), i >= 3);
+            recorder.sink = Some(Box::new(FailingSink));
+        }
+        assert_eq!(recorder.buffer.len(), 4 * MAX_PENDING_BUFFERS);
+        assert_eq!(recorder.dropped_rows(), 8);
+
+        // Once the files can be written again the kept rows are flushed
+        recorder.sink = None;
+        recorder.close().unwrap();
+        assert!(recorder.buffer.is_empty());
+        let rows: Vec<RecordRow> = files(&directory).iter().flat_map(|file| read_rows(file).unwrap()).collect();
+        assert_eq!(rows.len(), 32);
+        assert_eq!(rows[0].time, row(8).time);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_thread_writes_the_queued_rows() {
+        let (recorder, directory) = recorder(RecordFormat::Csv, "thread");
+        let recorder = Arc::new(Mutex::new(recorder));
+        let thread = RecorderThread::spawn(recorder.clone());
+        let rows = thread.sender();
+        for i in 0..6 {
+            rows.send(row(i));
+        }
+        drop(rows);
+        thread.join();
+
+        let mut recorder = recorder.lock().unwrap();
+        assert_eq!(recorder.summary().data_points, 4);
+        assert_eq!(recorder.dropped_rows(), 0);
+        recorder.close().unwrap();
+        assert_eq!(read_rows(&recorder.files()[0]).unwrap().len(), 6);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_summary_only_counts_engaged_samples() {
+        let mut recorder = Recorder::summary_only();
+        for i in 0..6 {
+            recorder.record(row(i)).unwrap();
+        }
+
+        let summary = recorder.summary();
+        assert_eq!(summary.data_points, 4);
+        assert_eq!(summary.max_error, 9.0);
+        assert_eq!(summary.min_error, 5.0);
+        assert!(recorder.buffer.is_empty());
+    }
+}
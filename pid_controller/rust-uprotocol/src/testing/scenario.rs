@@ -0,0 +1,350 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::path::Path;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+/// Timed publishing scenario of `up_pub schedule`, loaded from YAML or JSON.
+///
+/// Steps run one after the other, unless `at` starts them at a fixed time from the start
+/// of the scenario, e.g. to publish the velocity and the target speed concurrently.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Number of times the whole scenario runs
+    #[serde(default = "default_repeat")]
+    pub repeat: u32,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Topic name of the topic map or URI, as for `up_pub args`; no URI only waits
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// Fixed payload of every message
+    #[serde(default)]
+    pub payload: Option<String>,
+    /// Generated value of every message, instead of `payload`
+    #[serde(default)]
+    pub waveform: Option<Waveform>,
+    #[serde(default = "default_format")]
+    pub format: String,
+    /// Start in seconds from the start of the scenario (default: after the previous step)
+    #[serde(default)]
+    pub at: Option<f64>,
+    /// Messages per second
+    #[serde(default = "default_rate")]
+    pub rate: f64,
+    /// Number of messages (default: 1, or `duration` times `rate`)
+    #[serde(default)]
+    pub repeat: Option<u32>,
+    /// Length in seconds of the step, instead of `repeat`
+    #[serde(default)]
+    pub duration: Option<f64>,
+    /// Pause in seconds after the step
+    #[serde(default)]
+    pub wait: f64,
+}
+
+/// Values generated over the duration of a step, `t` being the time since its start.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Waveform {
+    /// `from` until `at` seconds, then `to`
+    Step { from: f64, to: f64, #[serde(default)] at: f64 },
+    /// Linear from `from` to `to` over the step
+    Ramp { from: f64, to: f64 },
+    /// `offset + amplitude * sin(2π t / period + phase)`
+    Sine { offset: f64, amplitude: f64, period: f64, #[serde(default)] phase: f64 },
+    /// Starts at `start` and moves by a uniform increment within `±step` per message,
+    /// staying within `[min, max]`; a `seed` makes it reproducible
+    RandomWalk { start: f64, step: f64, min: f64, max: f64, #[serde(default)] seed: Option<u64> },
+}
+
+/// One message of the scenario timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scheduled {
+    /// Seconds from the start of the scenario
+    pub time: f64,
+    pub uri: String,
+    pub payload: String,
+    pub format: String,
+}
+
+fn default_repeat() -> u32 {
+    1
+}
+
+fn default_format() -> String {
+    "text".to_string()
+}
+
+fn default_rate() -> f64 {
+    10.0
+}
+
+impl Scenario {
+    /// Loads a scenario from a `.yaml`, `.yml` or `.json` file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let scenario = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str::<Scenario>(&content).map_err(|e| e.to_string()),
+            Some("json") => serde_json::from_str::<Scenario>(&content).map_err(|e| e.to_string()),
+            _ => Err("unsupported file type, expected .yaml, .yml or .json".to_string()),
+        };
+        scenario
+            .and_then(|scenario| scenario.validate().map(|_| scenario))
+            .map_err(|e| format!("invalid scenario {}: {}", path.display(), e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.steps.is_empty() {
+            return Err("at least one step is required".to_string());
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            step.validate().map_err(|e| format!("step {}: {}", index + 1, e))?;
+        }
+        Ok(())
+    }
+
+    /// All messages of the scenario ordered by time.
+    pub fn timeline(&self) -> Vec<Scheduled> {
+        let mut timeline = Vec::new();
+        let mut offset = 0.0;
+        for _ in 0..self.repeat {
+            let mut end = offset;
+            let mut cursor = offset;
+            for step in &self.steps {
+                let start = step.at.map_or(cursor, |at| offset + at);
+                timeline.extend(step.messages(start));
+                cursor = start + step.length() + step.wait;
+                end = f64::max(end, cursor);
+            }
+            offset = end;
+        }
+        // Stable, so that messages at the same time keep the order of the steps
+        timeline.sort_by(|a, b| a.time.total_cmp(&b.time));
+        timeline
+    }
+}
+
+impl Step {
+    fn validate(&self) -> Result<(), String> {
+        if self.uri.is_some() && self.payload.is_some() == self.waveform.is_some() {
+            return Err("exactly one of payload and waveform is required".to_string());
+        }
+        if self.uri.is_none() && (self.payload.is_some() || self.waveform.is_some()) {
+            return Err("a payload or waveform requires a uri".to_string());
+        }
+        if !(self.rate.is_finite() && self.rate > 0.0) {
+            return Err(format!("rate must be positive (got {})", self.rate));
+        }
+        if self.repeat.is_some() && self.duration.is_some() {
+            return Err("repeat and duration are exclusive".to_string());
+        }
+        if let Some(duration) = self.duration {
+            if !(duration.is_finite() && duration >= 0.0) {
+                return Err(format!("duration must not be negative (got {})", duration));
+            }
+        }
+        if let Some(at) = self.at {
+            if !(at.is_finite() && at >= 0.0) {
+                return Err(format!("at must not be negative (got {})", at));
+            }
+        }
+        if !(self.wait.is_finite() && self.wait >= 0.0) {
+            return Err(format!("wait must not be negative (got {})", self.wait));
+        }
+        match &self.waveform {
+            Some(Waveform::Sine { period, .. }) if *period <= 0.0 => {
+                Err(format!("sine period must be positive (got {})", period))
+            }
+            Some(Waveform::RandomWalk { start, step, min, max, .. }) if !(min <= start && start <= max && *step >= 0.0) => {
+                Err("random walk requires min <= start <= max and a non-negative step".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn count(&self) -> u32 {
+        if self.uri.is_none() {
+            return 0;
+        }
+        match (self.repeat, self.duration) {
+            (Some(repeat), _) => repeat,
+            (None, Some(duration)) => (duration * self.rate).round().max(1.0) as u32,
+            (None, None) => 1,
+        }
+    }
+
+    // Time from the first to the end of the last message period
+    fn length(&self) -> f64 {
+        match (self.duration, self.count()) {
+            (Some(duration), _) => duration,
+            (None, 0) => 0.0,
+            (None, count) => count as f64 / self.rate,
+        }
+    }
+
+    fn messages(&self, start: f64) -> Vec<Scheduled> {
+        let Some(uri) = &self.uri else {
+            return Vec::new();
+        };
+        let count = self.count();
+        let mut generator = self.waveform.as_ref().map(WaveformGenerator::new);
+        // The last message of a ramp lands on its end value
+        let span = (count.saturating_sub(1) as f64 / self.rate).max(f64::EPSILON);
+
+        (0..count)
+            .map(|index| {
+                let t = index as f64 / self.rate;
+                let payload = match (&mut generator, &self.payload) {
+                    (Some(generator), _) => format!("{:.3}", generator.next(t, span)),
+                    (None, Some(payload)) => payload.clone(),
+                    (None, None) => String::new(),
+                };
+                Scheduled { time: start + t, uri: uri.clone(), payload, format: self.format.clone() }
+            })
+            .collect()
+    }
+}
+
+struct WaveformGenerator {
+    waveform: Waveform,
+    value: f64,
+    rng: StdRng,
+}
+
+impl WaveformGenerator {
+    fn new(waveform: &Waveform) -> Self {
+        let (value, rng) = match waveform {
+            Waveform::RandomWalk { start, seed, .. } => (
+                *start,
+                seed.map_or_else(|| StdRng::from_rng(&mut rand::rng()), StdRng::seed_from_u64),
+            ),
+            _ => (0.0, StdRng::seed_from_u64(0)),
+        };
+        WaveformGenerator { waveform: waveform.clone(), value, rng }
+    }
+
+    // Value at `t` seconds into a step whose last message is at `span` seconds
+    fn next(&mut self, t: f64, span: f64) -> f64 {
+        match self.waveform {
+            Waveform::Step { from, to, at } => if t < at { from } else { to },
+            Waveform::Ramp { from, to } => from + (to - from) * (t / span).min(1.0),
+            Waveform::Sine { offset, amplitude, period, phase } => {
+                offset + amplitude * (2.0 * std::f64::consts::PI * t / period + phase).sin()
+            }
+            Waveform::RandomWalk { step, min, max, .. } => {
+                if t > 0.0 && step > 0.0 {
+                    self.value = (self.value + self.rng.random_range(-step..=step)).clamp(min, max);
+                }
+                self.value
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+name: ramp and disturbance
+steps:
+  - uri: cc_engage
+    payload: "1"
+  - uri: cc_speed
+    rate: 2
+    duration: 2
+    waveform: { type: ramp, from: 50, to: 80 }
+    wait: 1
+  - uri: curr_speed
+    at: 0
+    repeat: 3
+    rate: 1
+    waveform: { type: step, from: 10, to: 20, at: 1 }
+  - uri: clock_status
+    payload: "0"
+"#;
+
+    #[test]
+    fn test_steps_run_in_sequence_unless_started_at_a_time() {
+        let scenario: Scenario = serde_yaml::from_str(SCENARIO).unwrap();
+        scenario.validate().unwrap();
+        let timeline = scenario.timeline();
+
+        let at = |uri: &str| -> Vec<(f64, String)> {
+            timeline.iter().filter(|m| m.uri == uri).map(|m| ((m.time * 1000.0).round() / 1000.0, m.payload.clone())).collect()
+        };
+        assert_eq!(at("cc_engage"), vec![(0.0, "1".to_string())]);
+        // Starts after the single engage message period, and ends on the ramp end value
+        assert_eq!(
+            at("cc_speed"),
+            vec![(0.1, "50.000".into()), (0.6, "60.000".into()), (1.1, "70.000".into()), (1.6, "80.000".into())]
+        );
+        assert_eq!(at("curr_speed"), vec![(0.0, "10.000".into()), (1.0, "20.000".into()), (2.0, "20.000".into())]);
+        // Follows the previous step, the velocity one ending at 3 s, regardless of the ramp
+        assert_eq!(at("clock_status"), vec![(3.0, "0".into())]);
+        assert!(timeline.windows(2).all(|pair| pair[0].time <= pair[1].time));
+    }
+
+    #[test]
+    fn test_seeded_random_walk_is_reproducible_and_bounded() {
+        let step = |seed| Step {
+            uri: Some("curr_speed".to_string()),
+            payload: None,
+            waveform: Some(Waveform::RandomWalk { start: 50.0, step: 2.0, min: 45.0, max: 55.0, seed: Some(seed) }),
+            format: default_format(),
+            at: None,
+            rate: 10.0,
+            repeat: Some(200),
+            duration: None,
+            wait: 0.0,
+        };
+
+        let values: Vec<f64> = step(7).messages(0.0).iter().map(|m| m.payload.parse().unwrap()).collect();
+        assert_eq!(values[0], 50.0);
+        assert!(values.iter().all(|v| (45.0..=55.0).contains(v)));
+        assert!(values.windows(2).any(|pair| pair[0] != pair[1]));
+        assert_eq!(step(7).messages(0.0), step(7).messages(0.0));
+        assert_ne!(step(7).messages(0.0), step(8).messages(0.0));
+    }
+
+    #[test]
+    fn test_invalid_steps_are_rejected() {
+        let invalid = [
+            "steps: [{ uri: cc_engage }]",
+            "steps: [{ uri: cc_engage, payload: '1', waveform: { type: ramp, from: 0, to: 1 } }]",
+            "steps: [{ uri: cc_engage, payload: '1', rate: 0 }]",
+            "steps: [{ uri: cc_engage, payload: '1', repeat: 2, duration: 1 }]",
+            "steps: [{ uri: cc_engage, waveform: { type: sine, offset: 0, amplitude: 1, period: 0 } }]",
+            "steps: []",
+        ];
+        for content in invalid {
+            let scenario: Scenario = serde_yaml::from_str(content).unwrap();
+            assert!(scenario.validate().is_err(), "{}", content);
+        }
+        assert!(serde_yaml::from_str::<Scenario>("steps: [{ uri: cc_engage, payload: '1', speed: 2 }]").is_err());
+    }
+}
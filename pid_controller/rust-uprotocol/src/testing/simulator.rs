@@ -14,18 +14,25 @@
 // limitations under the License.
 //
 
-use std::time::SystemTime;
-use tokio::time::{sleep, Duration};
-use rand::Rng;
+use std::sync::{Arc, Mutex};
+use tokio::time::{interval, Duration, MissedTickBehavior};
 
 use clap::Parser;
-use log::{info, error};
-use up_transport_zenoh::{UPTransportZenoh, zenoh_config};
-use up_rust::{LocalUriProvider, StaticUriProvider, UUri, UMessageBuilder, UTransport, UPayloadFormat};
-use zenoh::{Config};
+use log::{info, debug, error};
+use up_transport_zenoh::UPTransportZenoh;
+use up_rust::{LocalUriProvider, StaticUriProvider, UUri, UListener, UMessage, UMessageBuilder, UTransport};
+use cruise_control_messages::{
+    ActuationCommand, ClockStatus, CruiseControlPayload, Encoding, EngageStatus, TargetSpeed, VelocityStatus,
+};
+use pid_cruise_control::zenoh_peer::peer_config;
+use sdv_lab_topics as topics;
+
+use vehicle_model::{VehicleModel, VehicleParameters};
+
+mod vehicle_model;
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[clap(author, version, about = "Closed-loop longitudinal vehicle simulator for the cruise controller", long_about = None)]
 struct Args {
     #[clap(long, default_value = "127.0.0.1")]
     host: String,
@@ -33,125 +40,164 @@ struct Args {
     port: u16,
     #[clap(long, default_value = "CruiseControl")]
     role: String,
+    /// Simulation step in seconds of simulated time
     #[clap(long, default_value_t = 0.100)]
     delta: f64,
     #[clap(long, default_value = None)]
     router: Option<String>,
+    /// Simulated seconds per wall-clock second (e.g. 10 runs ten times faster than real time)
+    #[clap(long, default_value_t = 1.0)]
+    realtime_factor: f64,
+    /// Vehicle mass in kg
+    #[clap(long, default_value_t = 1600.0)]
+    mass: f64,
+    /// Road grade in percent, positive uphill
+    #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    grade: f64,
+    /// Initial vehicle speed in km/h
+    #[clap(long, default_value_t = 0.0)]
+    initial_speed: f64,
+    /// Target speed in km/h published together with the engage signal
+    #[clap(long, default_value_t = 50.0)]
+    target_speed: f64,
+    /// Period in simulated seconds at which target speed and engage are re-published
+    #[clap(long, default_value_t = 5.0)]
+    setpoint_period: f64,
+    /// Do not publish target speed and engage, e.g. when the AAOS cluster is running
+    #[clap(long)]
+    no_setpoint: bool,
+    /// Encoding of the published payloads
+    #[clap(long, value_enum, default_value_t = PayloadEncoding::Protobuf)]
+    payload_format: PayloadEncoding,
+    /// Vehicle id of a fleet: simulates that vehicle, on the authorities derived for it
+    #[clap(long)]
+    vehicle_id: Option<String>,
 }
 
-// Helper function to create a Zenoh configuration
-pub(crate) fn get_zenoh_config() -> zenoh_config::Config {
-    let args = Args::parse();
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum PayloadEncoding {
+    Protobuf,
+    Text,
+}
 
-    let zenoh_string = if let Some(router) = &args.router {
-        format!("{{ mode: 'peer', connect: {{ endpoints: [ 'tcp/{}:7447' ] }} }}", router)
-    } else {
-        "{ mode: 'peer' }".to_string()
-    };
+impl From<PayloadEncoding> for Encoding {
+    fn from(encoding: PayloadEncoding) -> Self {
+        match encoding {
+            PayloadEncoding::Protobuf => Encoding::Protobuf,
+            PayloadEncoding::Text => Encoding::Text,
+        }
+    }
+}
 
-    let zenoh_config = Config::from_json5(&zenoh_string).expect("Failed to load Zenoh config");
+// Keeps the latest actuation command published by the cruise controller
+struct ActuationListener {
+    command: Arc<Mutex<f64>>,
+}
+
+#[async_trait::async_trait]
+impl UListener for ActuationListener {
+    async fn on_receive(&self, message: UMessage) {
+        match ActuationCommand::decode_message(&message) {
+            Ok(actuation) => {
+                *self.command.lock().unwrap() = actuation.command;
+                debug!("Received actuation command '{:.4}'", actuation.command);
+            }
+            Err(e) => error!("Failed to parse actuation command payload: {}", e),
+        }
+    }
+}
+
+async fn publish<T: CruiseControlPayload>(transport: &UPTransportZenoh, uri: &UUri, value: T, encoding: Encoding, name: &str) {
+    let (payload, format) = value.encode(encoding);
+    let message = UMessageBuilder::publish(uri.clone())
+        .build_with_payload(payload, format)
+        .unwrap();
 
-    zenoh_config
+    if let Err(e) = transport.send(message).await {
+        error!("Failed to publish {}: {}", name, e);
+    } else {
+        debug!("Publishing {}: {}", name, value.value());
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     env_logger::init();
-    
-    info!("*** Started uProtocol Publisher");
 
-    // Create a uProtocol URI provider for the PID controller
+    let args = Args::parse();
+    if args.delta <= 0.0 || args.realtime_factor <= 0.0 {
+        return Err("--delta and --realtime-factor must be positive".into());
+    }
+
+    info!("*** Started uProtocol Vehicle Simulator");
+
+    // Create a uProtocol URI provider for the simulator
     // This defines the identity of this node in the uProtocol network
-    let uri_provider = StaticUriProvider::new("VehicleSimulator", 0, 2);
+    let authority = match &args.vehicle_id {
+        Some(vehicle_id) => topics::vehicle_authority(topics::SIMULATOR.authority, vehicle_id),
+        None => topics::SIMULATOR.authority.to_string(),
+    };
+    let uri_provider = StaticUriProvider::new(&authority, topics::UE_ID, topics::UE_VERSION_MAJOR);
     
     // Initialize uProtocol transport with Zenoh
     let transport = UPTransportZenoh::builder(uri_provider.get_authority())
         .expect("invalid authority name")
-        .with_config(get_zenoh_config())
+        .with_config(peer_config(args.router.as_ref().map(|router| format!("{}:7447", router)).as_deref())?)
         .build()
         .await?;
 
+    // Create URIs according to the SDV lab topic map, derived for the vehicle of a fleet
+    let topic_uri = |topic: &topics::Topic| match &args.vehicle_id {
+        Some(vehicle_id) => topic.vehicle_uri(vehicle_id),
+        None => Ok(topic.uri()),
+    };
+    let clock_uri = topic_uri(&topics::CLOCK_STATUS)?;
+    let velocity_uri = topic_uri(&topics::VELOCITY_STATUS)?;
+    let target_uri = topic_uri(&topics::TARGET_SPEED)?;
+    let engage_uri = topic_uri(&topics::ENGAGE)?;
+    let actuation_uri = topic_uri(&topics::ACTUATION)?;
 
-    // Create URIs for publishing according to the mapping table
-    let clock_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8002)?;      // vehicle/status/clock_status
-    let velocity_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8001)?;   // vehicle/status/velocity_status
-    let target_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8001)?;           // adas/cruise_control/target_speed
-    let engage_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8002)?;           // adas/cruise_control/engage
-
-    info!("uProtocol Publisher initialized with URIs:");
+    info!("uProtocol Vehicle Simulator initialized with URIs:");
     info!("  Clock: {}", String::from(&clock_uri));
     info!("  Velocity: {}", String::from(&velocity_uri));
     info!("  Target Speed: {}", String::from(&target_uri));
     info!("  Engage: {}", String::from(&engage_uri));
+    info!("  Actuation: {}", String::from(&actuation_uri));
 
-    #[allow(unused_mut)]
-    let mut engaged = 1;
+    let command = Arc::new(Mutex::new(0.0));
+    let listener = ActuationListener { command: Arc::clone(&command) };
+    transport.register_listener(&actuation_uri, None, Arc::new(listener)).await?;
 
-    loop {
-        let velocity = rand::rng().random_range(5.0..15.0);
-        let target = rand::rng().random_range(10.0..20.0);
-
-        // Getting system time as a timestamp in seconds
-        let current_time: f64 = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64();
-
-        // Publish current timestamp
-        let clock_payload = format!("{}", current_time);
-        let message = UMessageBuilder::publish(clock_uri.clone())
-            .build_with_payload(clock_payload.clone(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
-            .unwrap();
-        
-        if let Err(e) = transport.send(message).await {
-            error!("Failed to publish clock: {}", e);
-        } else {
-            info!("Publishing clock timestamp: {}", clock_payload);
-        }
+    let parameters = VehicleParameters {
+        mass: args.mass,
+        ..VehicleParameters::default()
+    };
+    let mut vehicle = VehicleModel::new(parameters, args.grade, args.initial_speed);
 
-        // Publish current velocity
-        let velocity_payload = format!("{}", velocity);
-        let message = UMessageBuilder::publish(velocity_uri.clone())
-            .build_with_payload(velocity_payload.clone(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
-            .unwrap();
-        
-        if let Err(e) = transport.send(message).await {
-            error!("Failed to publish velocity: {}", e);
-        } else {
-            info!("Publishing velocity: {}", velocity_payload);
-        }
+    let setpoint_steps = ((args.setpoint_period / args.delta).round() as u64).max(1);
+    let encoding = Encoding::from(args.payload_format);
+    let mut ticker = interval(Duration::from_secs_f64(args.delta / args.realtime_factor));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-        // Publish target speed
-        let target_payload = format!("{}", target);
-        let message = UMessageBuilder::publish(target_uri.clone())
-            .build_with_payload(target_payload.clone(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
-            .unwrap();
-        
-        if let Err(e) = transport.send(message).await {
-            error!("Failed to publish target speed: {}", e);
-        } else {
-            info!("Publishing target speed: {}", target_payload);
-        }
+    let mut step: u64 = 0;
+    loop {
+        ticker.tick().await;
 
-        // Publish engage status
-        let engage_payload = format!("{}", engaged);
-        let message = UMessageBuilder::publish(engage_uri.clone())
-            .build_with_payload(engage_payload.clone(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
-            .unwrap();
-        
-        if let Err(e) = transport.send(message).await {
-            error!("Failed to publish engage status: {}", e);
-        } else {
-            info!("Publishing engage status: {}", engage_payload);
-        }
+        let actuation = *command.lock().unwrap();
+        let velocity = vehicle.step(actuation, args.delta);
+        step += 1;
+        let sim_time = step as f64 * args.delta;
 
-        println!("Published uProtocol messages: time={:.4}, velocity={:.2}, target={:.2}, engaged={}", 
-                current_time, velocity, target, engaged);
+        // Same order as the CARLA bridge: clock first, then velocity
+        publish(&transport, &clock_uri, ClockStatus::from_value(sim_time), encoding, "clock").await;
+        publish(&transport, &velocity_uri, VelocityStatus::from_value(velocity), encoding, "velocity").await;
 
-        // Uncomment to toggle engagement for testing
-        // engaged = if engaged == 1 { 0 } else { 1 };
+        if !args.no_setpoint && (step - 1).is_multiple_of(setpoint_steps) {
+            publish(&transport, &target_uri, TargetSpeed::from_value(args.target_speed), encoding, "target speed").await;
+            publish(&transport, &engage_uri, EngageStatus::from_value(1.0), encoding, "engage status").await;
+        }
 
-        sleep(Duration::from_secs(2)).await;
+        info!("t={:.2}s command={:.3} velocity={:.2} km/h", sim_time, actuation, velocity);
     }
 }
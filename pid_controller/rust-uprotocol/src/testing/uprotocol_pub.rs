@@ -14,15 +14,27 @@
 // limitations under the License.
 //
 
-use std::str::FromStr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use clap::{Parser, Subcommand};
 use log::{info, error, warn};
-use up_transport_zenoh::{UPTransportZenoh, zenoh_config};
-use up_rust::{UUri, UMessageBuilder, UTransport, UPayloadFormat};
-use zenoh::Config;
+use up_transport_zenoh::UPTransportZenoh;
+use up_rust::{LocalUriProvider, StaticUriProvider, UStatus, UUri, UMessageBuilder, UTransport, UPayloadFormat};
+use up_rust::communication::{CallOptions, InMemoryRpcClient, Notifier, RpcClient, SimpleNotifier, UPayload};
+use bytes::Bytes;
+use cruise_control_messages::{
+    ActuationCommand, ClockStatus, CruiseControlPayload, Encoding, EngageStatus, TargetSpeed, VelocityStatus,
+};
+use pid_cruise_control::zenoh_peer::peer_config;
+use sdv_lab_topics::{self as topics, PayloadType};
+
+use scenario::Scenario;
+use uri_arg::{parse_resource_id, parse_uri, UriKind};
+
+mod scenario;
+mod uri_arg;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about = "uProtocol Publisher - Send messages to multiple URIs", long_about = None)]
@@ -70,13 +82,13 @@ cargo run --bin up_pub -- args \\
   --format text"
     )]
     Args {
-        #[clap(long, help = "Target URI (format: authority/ue_id/ue_version/resource_id)", action = clap::ArgAction::Append)]
+        #[clap(long, help = "Target URI (authority/ue_id/ue_version/resource_id with hex numbers) or topic name, e.g. curr_speed", action = clap::ArgAction::Append)]
         uri: Vec<String>,
         
         #[clap(long, help = "Payload data to send", action = clap::ArgAction::Append)]
         payload: Vec<String>,
         
-        #[clap(long, help = "Payload format: text, json, protobuf", action = clap::ArgAction::Append)]
+        #[clap(long, help = PAYLOAD_FORMATS, action = clap::ArgAction::Append)]
         format: Vec<String>,
     },
     
@@ -88,8 +100,71 @@ cargo run --bin up_pub -- args \\
     
     /// Interactive mode - publish multiple messages with prompts
     Interactive,
+
+    #[clap(
+        about = "Publish a timed YAML or JSON scenario",
+        long_about = "
+Publish a timed YAML or JSON scenario: fixed payloads or generated waveforms (step, ramp,
+sine, random walk), at a rate in Hz, for a number of messages or a duration
+
+Usage example:
+cargo run --bin up_pub -- schedule --path scenarios/acceleration.yaml"
+    )]
+    Schedule {
+        #[clap(long, help = "Path to the scenario file (.yaml, .yml or .json)")]
+        path: PathBuf,
+    },
+
+    #[clap(
+        about = "Invoke an RPC method and print the response or the error status",
+        long_about = "
+Invoke an RPC method and print the response or the error status
+
+Usage example (setTargetSpeed of the cruise-control-app, which expects a big-endian f32):
+cargo run --bin up_pub -- call \"up://cruise-control.app/C110/1/1\" --payload 80 --format f32"
+    )]
+    Call {
+        #[clap(help = "Method URI (authority/ue_id/ue_version/resource_id with hex numbers)")]
+        uri: String,
+
+        #[clap(long, help = "Request payload")]
+        payload: Option<String>,
+
+        #[clap(long, default_value = "text", help = PAYLOAD_FORMATS)]
+        format: String,
+
+        #[clap(long, default_value_t = 5000, help = "Time in milliseconds to wait for the response")]
+        timeout: u32,
+    },
+
+    #[clap(
+        about = "Send a notification to an entity",
+        long_about = "
+Send a notification to an entity, from the resource --resource-id of the publisher entity
+
+Usage example:
+cargo run --bin up_pub -- notify \"CruiseControl/0/2/0\" --payload '{\"reason\": \"test\"}' --format json"
+    )]
+    Notify {
+        #[clap(help = "Sink entity URI (authority/ue_id/ue_version/resource_id with hex numbers)")]
+        sink: String,
+
+        #[clap(long, help = "Notification payload")]
+        payload: Option<String>,
+
+        #[clap(long, default_value = "text", help = PAYLOAD_FORMATS)]
+        format: String,
+
+        #[clap(long, default_value = "0x8000", value_parser = |s: &str| parse_resource_id(s, UriKind::Topic), help = "Resource ID (hex, 0x8000-0xFFFE) of the notification origin")]
+        resource_id: u16,
+
+        #[clap(long, help = "TTL in milliseconds of the notification")]
+        ttl: Option<u32>,
+    },
 }
 
+const PAYLOAD_FORMATS: &str = "Payload format: text, json, protobuf, raw (hex bytes), f32 or f64 (big-endian number)";
+
 #[derive(Serialize, Deserialize, Debug)]
 struct MessageData {
     uri: String,
@@ -151,18 +226,69 @@ fn parse_arguments(uris: Vec<String>, payloads: Vec<String>, formats: Vec<String
     Ok(messages)
 }
 
-fn parse_payload_format(format_str: &str) -> UPayloadFormat {
-    match format_str.to_lowercase().as_str() {
-        "json" => UPayloadFormat::UPAYLOAD_FORMAT_JSON,
-        "protobuf" | "proto" => UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF,
-        "text" | _ => UPayloadFormat::UPAYLOAD_FORMAT_TEXT,
+// Encodes the payload given on the command line in `format`
+fn encode_payload(uri: &UUri, payload: &str, format: &str) -> Result<(Bytes, UPayloadFormat), String> {
+    match format.to_lowercase().as_str() {
+        "json" => Ok((Bytes::from(payload.to_string()), UPayloadFormat::UPAYLOAD_FORMAT_JSON)),
+        "protobuf" | "proto" => Ok((encode_protobuf(uri, payload)?, UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF)),
+        "raw" | "hex" => {
+            let hex: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+            if !hex.len().is_multiple_of(2) {
+                return Err(format!("Invalid hex payload '{}'", payload));
+            }
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| format!("Invalid hex payload '{}'", payload))?;
+            Ok((Bytes::from(bytes), UPayloadFormat::UPAYLOAD_FORMAT_RAW))
+        }
+        "f32" => {
+            let value: f32 = payload.trim().parse().map_err(|_| format!("Invalid f32 payload '{}'", payload))?;
+            Ok((Bytes::copy_from_slice(&value.to_be_bytes()), UPayloadFormat::UPAYLOAD_FORMAT_RAW))
+        }
+        "f64" => {
+            let value: f64 = payload.trim().parse().map_err(|_| format!("Invalid f64 payload '{}'", payload))?;
+            Ok((Bytes::copy_from_slice(&value.to_be_bytes()), UPayloadFormat::UPAYLOAD_FORMAT_RAW))
+        }
+        _ => Ok((Bytes::from(payload.to_string()), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)),
+    }
+}
+
+// Text and JSON payloads as they are, anything else as hex bytes
+fn describe_payload(payload: &UPayload) -> String {
+    let bytes = payload.payload();
+    match (payload.payload_format(), std::str::from_utf8(&bytes)) {
+        (UPayloadFormat::UPAYLOAD_FORMAT_TEXT | UPayloadFormat::UPAYLOAD_FORMAT_JSON, Ok(text)) => text.to_string(),
+        (format, _) => format!(
+            "{} ({:?})",
+            bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>(),
+            format
+        ),
     }
 }
 
-// Helper function to create a Zenoh configuration (copied from simulator.rs)
-fn get_zenoh_config(endpoint: &str) -> zenoh_config::Config {
-    let zenoh_string = format!("{{ mode: 'peer', connect: {{ endpoints: [ 'tcp/{}' ] }} }}", endpoint);
-    Config::from_json5(&zenoh_string).expect("Failed to load Zenoh config")
+// Encodes a text value as the protobuf message the topic map declares for the topic
+fn encode_protobuf(uri: &UUri, payload: &str) -> Result<Bytes, String> {
+    fn encode<T: CruiseControlPayload>(message: &str, payload: &str) -> Option<Result<Bytes, String>> {
+        let descriptor = T::descriptor();
+        (descriptor.full_name() == message).then(|| {
+            T::from_legacy_text(payload)
+                .map(|message| message.encode(Encoding::Protobuf).0)
+                .ok_or_else(|| format!("Invalid {} value '{}'", descriptor.name(), payload))
+        })
+    }
+
+    let message = match topics::find(uri).map(|topic| topic.payload) {
+        Some(PayloadType::Protobuf(message)) => message,
+        _ => return Err(format!("No protobuf message known for {}", String::from(uri))),
+    };
+    encode::<VelocityStatus>(message, payload)
+        .or_else(|| encode::<ClockStatus>(message, payload))
+        .or_else(|| encode::<TargetSpeed>(message, payload))
+        .or_else(|| encode::<EngageStatus>(message, payload))
+        .or_else(|| encode::<ActuationCommand>(message, payload))
+        .unwrap_or_else(|| Err(format!("Unsupported protobuf message {}", message)))
 }
 
 async fn create_transport(endpoint: &str, authority: &str, entity_id: u32, version_major: u8) -> Result<Arc<dyn UTransport>, Box<dyn std::error::Error>> {
@@ -176,7 +302,7 @@ async fn create_transport(endpoint: &str, authority: &str, entity_id: u32, versi
     let transport: Arc<dyn UTransport> = Arc::new(
         UPTransportZenoh::builder(authority)
             .expect("invalid authority name")
-            .with_config(get_zenoh_config(endpoint))
+            .with_config(peer_config(Some(endpoint))?)
             .build()
             .await?
     );
@@ -185,40 +311,13 @@ async fn create_transport(endpoint: &str, authority: &str, entity_id: u32, versi
 }
 
 async fn publish_message(transport: &Arc<dyn UTransport>, uri_str: &str, payload: &str, format: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Parse URI - support both full URI format and parts format
-    let uri = if uri_str.contains("://") {
-        UUri::from_str(uri_str)?
-    } else {
-        // Parse as authority/ue_id/ue_version/resource_id
-        let parts: Vec<&str> = uri_str.split('/').collect();
-        if parts.len() != 4 {
-            return Err(format!("Invalid URI format. Expected: authority/ue_id/ue_version/resource_id, got: {}", uri_str).into());
-        }
-        
-        let authority = parts[0];
-        let ue_id: u32 = parts[1].parse()?;
-        let ue_version: u8 = parts[2].parse()?;
-        
-        // Parse resource_id as hexadecimal if it starts with 0x, otherwise as decimal
-        let resource_id: u16 = if parts[3].starts_with("0x") || parts[3].starts_with("0X") {
-            u16::from_str_radix(&parts[3][2..], 16)?
-        } else {
-            // Try parsing as hex first (for values like 8001, 8002)
-            u16::from_str_radix(parts[3], 16).unwrap_or_else(|_| {
-                // If hex parsing fails, try decimal
-                parts[3].parse().unwrap_or(0)
-            })
-        };
-        
-        UUri::try_from_parts(authority, ue_id, ue_version, resource_id)?
-    };
-
-    let payload_format = parse_payload_format(format);
+    let uri = parse_uri(uri_str, UriKind::Topic)?;
     
     info!("Publishing to URI: {} with payload: {} (format: {})", String::from(&uri), payload, format);
     
+    let (payload_bytes, payload_format) = encode_payload(&uri, payload, format)?;
     let message = UMessageBuilder::publish(uri)
-        .build_with_payload(payload.to_string(), payload_format)?;
+        .build_with_payload(payload_bytes, payload_format)?;
     
     if let Err(e) = transport.send(message).await {
         error!("Failed to publish message: {}", e);
@@ -229,6 +328,62 @@ async fn publish_message(transport: &Arc<dyn UTransport>, uri_str: &str, payload
     Ok(())
 }
 
+async fn call_method(
+    transport: Arc<dyn UTransport>,
+    uri_provider: Arc<dyn LocalUriProvider>,
+    uri_str: &str,
+    payload: Option<&str>,
+    format: &str,
+    timeout: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let method = parse_uri(uri_str, UriKind::Method)?;
+    let request = payload
+        .map(|payload| encode_payload(&method, payload, format))
+        .transpose()?
+        .map(|(bytes, format)| UPayload::new(bytes, format));
+
+    info!("Invoking {} (timeout: {} ms)", String::from(&method), timeout);
+    let client = InMemoryRpcClient::new(transport, uri_provider).await?;
+    match client
+        .invoke_method(method, CallOptions::for_rpc_request(timeout, None, None, None), request)
+        .await
+    {
+        Ok(Some(response)) => println!("✓ Response: {}", describe_payload(&response)),
+        Ok(None) => println!("✓ Response without payload"),
+        Err(e) => {
+            let status = UStatus::from(e);
+            println!("✗ Failed: {:?} {}", status.get_code(), status.get_message());
+            return Err(format!("{} failed", uri_str).into());
+        }
+    }
+    Ok(())
+}
+
+async fn send_notification(
+    transport: Arc<dyn UTransport>,
+    uri_provider: Arc<dyn LocalUriProvider>,
+    sink_str: &str,
+    payload: Option<&str>,
+    format: &str,
+    resource_id: u16,
+    ttl: Option<u32>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let sink = parse_uri(sink_str, UriKind::Sink)?;
+    let notification = payload
+        .map(|payload| encode_payload(&sink, payload, format))
+        .transpose()?
+        .map(|(bytes, format)| UPayload::new(bytes, format));
+
+    let origin = uri_provider.get_resource_uri(resource_id);
+    info!("Notifying {} from {}", String::from(&sink), String::from(&origin));
+    let notifier = SimpleNotifier::new(transport, uri_provider);
+    notifier
+        .notify(resource_id, &sink, CallOptions::for_notification(ttl, None, None), notification)
+        .await?;
+    println!("✓ Notified {} from {}", sink_str, String::from(&origin));
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
@@ -240,6 +395,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create transport
     let transport = create_transport(&args.endpoint, &args.authority, args.entity_id, args.version_major).await?;
+    let uri_provider: Arc<dyn LocalUriProvider> =
+        Arc::new(StaticUriProvider::new(&args.authority, args.entity_id, args.version_major));
     
     match args.command {
         Commands::Args { uri, payload, format } => {
@@ -312,8 +469,68 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+
+        Commands::Schedule { path } => {
+            let scenario = Scenario::load(&path)?;
+            // Reject invalid URIs before anything is published
+            for step in &scenario.steps {
+                if let Some(uri) = &step.uri {
+                    parse_uri(uri, UriKind::Topic)?;
+                }
+            }
+            let timeline = scenario.timeline();
+            let duration = timeline.last().map_or(0.0, |message| message.time);
+
+            info!(
+                "Running scenario '{}': {} messages over {:.1} s",
+                scenario.name.as_deref().unwrap_or(&path.display().to_string()),
+                timeline.len(),
+                duration
+            );
+
+            // Deadlines from the start, so that publishing time does not add up
+            let start = tokio::time::Instant::now();
+            for msg in timeline {
+                tokio::time::sleep_until(start + tokio::time::Duration::from_secs_f64(msg.time)).await;
+                if let Err(e) = publish_message(&transport, &msg.uri, &msg.payload, &msg.format).await {
+                    error!("Failed to publish to {}: {}", msg.uri, e);
+                } else {
+                    println!("✓ [{:8.3} s] Published to {}: {} ({})", msg.time, msg.uri, msg.payload, msg.format);
+                }
+            }
+        }
+
+        Commands::Call { uri, payload, format, timeout } => {
+            call_method(transport, uri_provider, &uri, payload.as_deref(), &format, timeout).await?;
+        }
+
+        Commands::Notify { sink, payload, format, resource_id, ttl } => {
+            send_notification(transport, uri_provider, &sink, payload.as_deref(), &format, resource_id, ttl).await?;
+        }
     }
     
     info!("uProtocol Publisher finished");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encodes_raw_and_numeric_payloads() {
+        let uri = UUri::try_from_parts("cruise-control.app", 0xC110, 1, 1).unwrap();
+
+        let (bytes, format) = encode_payload(&uri, "80", "f32").unwrap();
+        assert_eq!(bytes.as_ref(), 80f32.to_be_bytes());
+        assert_eq!(format, UPayloadFormat::UPAYLOAD_FORMAT_RAW);
+
+        let (bytes, _) = encode_payload(&uri, "de ad be ef", "raw").unwrap();
+        assert_eq!(bytes.as_ref(), [0xde, 0xad, 0xbe, 0xef]);
+        assert!(encode_payload(&uri, "abc", "raw").is_err());
+
+        let (bytes, format) = encode_payload(&uri, "hello", "text").unwrap();
+        assert_eq!(bytes.as_ref(), b"hello");
+        assert_eq!(format, UPayloadFormat::UPAYLOAD_FORMAT_TEXT);
+    }
+}
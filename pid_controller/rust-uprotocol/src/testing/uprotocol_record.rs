@@ -0,0 +1,161 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use log::{error, info};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use up_rust::{UListener, UMessage, UTransport, UUri};
+use up_transport_zenoh::UPTransportZenoh;
+
+use pid_cruise_control::capture::{CaptureWriter, CapturedMessage};
+use pid_cruise_control::topic_filter::parse_filter;
+use pid_cruise_control::zenoh_peer::peer_config;
+
+#[derive(Parser, Debug)]
+#[clap(
+    author,
+    version,
+    about = "uProtocol Recorder - Capture the messages of matching topics to a file for up_replay",
+    long_about = "
+uProtocol Recorder - Capture the messages of matching topics to a file for up_replay
+
+Usage example:
+cargo run --bin up_record -- --output session.upcap
+cargo run --bin up_record -- --filter \"//EGOVehicle/0/2/FFFF\" --filter \"//AAOS/0/2/FFFF\" --duration 60"
+)]
+struct Args {
+    #[clap(long, default_value = "127.0.0.1:7447", help = "Zenoh router endpoint")]
+    endpoint: String,
+
+    #[clap(long, default_value = "Recorder", help = "Recorder authority name")]
+    authority: String,
+
+    #[clap(
+        long,
+        help = "Source filter: topic name, URI with wildcards or authority/ue_id/ue_version/resource_id; repeat for several (default: everything)",
+        action = clap::ArgAction::Append
+    )]
+    filter: Vec<String>,
+
+    #[clap(long, help = "Sink filter, e.g. to capture the notifications and RPC messages sent to one entity")]
+    sink: Option<String>,
+
+    #[clap(long, help = "Capture file to write (default: capture-<unix time>.upcap)")]
+    output: Option<PathBuf>,
+
+    #[clap(long, value_parser = parse_duration, help = "Stop after this many seconds")]
+    duration: Option<f64>,
+
+    #[clap(long, help = "Stop after this many messages")]
+    max_messages: Option<usize>,
+}
+
+// Forwards every message with its receive time to the writing task
+struct CaptureListener {
+    messages: UnboundedSender<CapturedMessage>,
+}
+
+#[async_trait::async_trait]
+impl UListener for CaptureListener {
+    async fn on_receive(&self, message: UMessage) {
+        let _ = self.messages.send(CapturedMessage { time: wall_time(), message });
+    }
+}
+
+// A positive, finite number of seconds
+fn parse_duration(s: &str) -> Result<f64, String> {
+    let seconds: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if seconds.is_finite() && seconds > 0.0 {
+        Ok(seconds)
+    } else {
+        Err(format!("expected a positive number of seconds, got {}", s))
+    }
+}
+
+fn wall_time() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logging
+    env_logger::init();
+
+    let args = Args::parse();
+
+    info!("*** Started uProtocol Recorder");
+
+    let filters = if args.filter.is_empty() {
+        vec![UUri::any()]
+    } else {
+        args.filter.iter().map(|filter| parse_filter(filter)).collect::<Result<Vec<_>, _>>()?
+    };
+    let sink = args.sink.as_deref().map(parse_filter).transpose()?;
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("capture-{}.upcap", wall_time() as u64)));
+    let mut writer = CaptureWriter::create(&output)?;
+
+    let transport: Arc<dyn UTransport> = Arc::new(
+        UPTransportZenoh::builder(args.authority.as_str())
+            .expect("invalid authority name")
+            .with_config(peer_config(Some(&args.endpoint))?)
+            .build()
+            .await?,
+    );
+
+    let (messages, mut messages_rx) = unbounded_channel();
+    for filter in &filters {
+        let listener = Arc::new(CaptureListener { messages: messages.clone() });
+        transport.register_listener(filter, sink.as_ref(), listener).await?;
+        info!("Subscribed to {}", String::from(filter));
+    }
+
+    println!("Recording to {} (CTRL-C to stop)...", output.display());
+    let stop = tokio::time::sleep(args.duration.map_or(Duration::MAX, Duration::from_secs_f64));
+    tokio::pin!(stop);
+    // Flushed regularly, so that a crash loses at most the last second
+    let mut flush = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            Some(captured) = messages_rx.recv() => {
+                if let Err(e) = writer.write(&captured) {
+                    error!("Failed to write message: {}", e);
+                }
+                if args.max_messages.is_some_and(|max| writer.len() >= max) {
+                    break;
+                }
+            }
+            _ = flush.tick() => {
+                if let Err(e) = writer.flush() {
+                    error!("Failed to flush {}: {}", output.display(), e);
+                }
+            }
+            _ = &mut stop => break,
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    let count = writer.len();
+    writer.finish()?;
+    println!("Recorded {} messages to {}", count, output.display());
+    info!("uProtocol Recorder finished");
+    Ok(())
+}
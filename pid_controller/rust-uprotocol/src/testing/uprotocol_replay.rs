@@ -0,0 +1,194 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use log::{error, info};
+use protobuf::MessageField;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use up_rust::{UTransport, UUID};
+use up_transport_zenoh::UPTransportZenoh;
+
+use pid_cruise_control::capture::CaptureReader;
+use pid_cruise_control::topic_filter::{matches_any, parse_filter};
+use pid_cruise_control::zenoh_peer::peer_config;
+use sdv_lab_topics as topics;
+
+#[derive(Parser, Debug)]
+#[clap(
+    author,
+    version,
+    about = "uProtocol Replayer - Re-publish a capture of up_record",
+    long_about = "
+uProtocol Replayer - Re-publish a capture of up_record with its original timing, at a speed
+factor or one message at a time
+
+Usage example:
+cargo run --bin up_replay -- --path session.upcap --list
+# Replay a CARLA session against a new pid_controller, without the recorded actuation
+cargo run --bin up_replay -- --path session.upcap --exclude cc_throttle --speed 2"
+)]
+struct Args {
+    #[clap(long, default_value = "127.0.0.1:7447", help = "Zenoh router endpoint")]
+    endpoint: String,
+
+    #[clap(long, default_value = "Replayer", help = "Replayer authority name")]
+    authority: String,
+
+    #[clap(long, help = "Capture file written by up_record")]
+    path: PathBuf,
+
+    #[clap(long, help = "Print the topics of the capture without publishing")]
+    list: bool,
+
+    #[clap(long, default_value_t = 1.0, help = "Speed factor of the replay (0 publishes as fast as possible)")]
+    speed: f64,
+
+    #[clap(long, help = "Publish one message per Enter key press")]
+    step: bool,
+
+    #[clap(long, default_value_t = 0.0, help = "Start this many seconds into the capture")]
+    start: f64,
+
+    #[clap(long, help = "Stop this many seconds into the capture")]
+    end: Option<f64>,
+
+    #[clap(long, help = "Only replay the messages of matching sources (repeatable)", action = clap::ArgAction::Append)]
+    filter: Vec<String>,
+
+    #[clap(long, help = "Skip the messages of matching sources, e.g. the outputs of the component under test (repeatable)", action = clap::ArgAction::Append)]
+    exclude: Vec<String>,
+
+    #[clap(long, help = "Keep the recorded message ids; by default new ids are generated so that TTLs count from the replay")]
+    keep_ids: bool,
+}
+
+// Messages and time span by source
+fn list(reader: &mut CaptureReader<std::io::BufReader<std::fs::File>>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut sources: BTreeMap<String, (usize, f64, f64)> = BTreeMap::new();
+    for position in 0..reader.len() {
+        let captured = reader.read(position)?;
+        let source = captured.message.source().map(String::from).unwrap_or_default();
+        let entry = sources.entry(source).or_insert((0, captured.time, captured.time));
+        entry.0 += 1;
+        entry.2 = captured.time;
+    }
+
+    let index = reader.index();
+    let span = match (index.first(), index.last()) {
+        (Some(first), Some(last)) => last.time - first.time,
+        _ => 0.0,
+    };
+    println!(
+        "{} messages over {:.3} s{}",
+        reader.len(),
+        span,
+        if reader.is_indexed() { "" } else { " (no index, the recording was interrupted)" }
+    );
+    println!("{:<26} {:<18} {:>8} {:>9}", "SOURCE", "TOPIC", "COUNT", "RATE Hz");
+    for (source, (count, first, last)) in sources {
+        let topic = source.parse().ok().and_then(|uri| topics::find(&uri)).map_or("-", |topic| topic.name);
+        let rate = if count > 1 && last > first { format!("{:.1}", (count - 1) as f64 / (last - first)) } else { "-".to_string() };
+        println!("{:<26} {:<18} {:>8} {:>9}", source, topic, count, rate);
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logging
+    env_logger::init();
+
+    let args = Args::parse();
+
+    info!("*** Started uProtocol Replayer");
+
+    if !(args.speed.is_finite() && args.speed >= 0.0) {
+        return Err("--speed must not be negative".into());
+    }
+    let filters = args.filter.iter().map(|filter| parse_filter(filter)).collect::<Result<Vec<_>, _>>()?;
+    let excludes = args.exclude.iter().map(|filter| parse_filter(filter)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut reader = CaptureReader::open(&args.path)?;
+    if !reader.is_indexed() {
+        info!("{} has no index, the recording was interrupted", args.path.display());
+    }
+    if args.list {
+        return list(&mut reader);
+    }
+    let Some(first) = reader.index().first().map(|entry| entry.time) else {
+        println!("{} is empty", args.path.display());
+        return Ok(());
+    };
+    let begin = reader.position_at(first + args.start);
+    let end = args.end.map_or(reader.len(), |end| reader.position_at(first + end));
+
+    let transport: Arc<dyn UTransport> = Arc::new(
+        UPTransportZenoh::builder(args.authority.as_str())
+            .expect("invalid authority name")
+            .with_config(peer_config(Some(&args.endpoint))?)
+            .build()
+            .await?,
+    );
+
+    println!("Replaying {} messages of {} (CTRL-C to stop)...", end.saturating_sub(begin), args.path.display());
+    let mut stdin = BufReader::new(tokio::io::stdin()).lines();
+    let replay_start = tokio::time::Instant::now();
+    let capture_start = first + args.start;
+    let mut published = 0;
+    for position in begin..end {
+        let mut captured = reader.read(position)?;
+        let Some(source) = captured.message.source().cloned() else {
+            continue;
+        };
+        if (!filters.is_empty() && !matches_any(&filters, &source)) || matches_any(&excludes, &source) {
+            continue;
+        }
+
+        if args.step {
+            println!("[{:8.3} s] Enter to publish to {}", captured.time - first, String::from(&source));
+            if stdin.next_line().await?.is_none() {
+                break;
+            }
+        } else if args.speed > 0.0 {
+            // Deadlines from the start, so that publishing time does not add up
+            let offset = ((captured.time - capture_start) / args.speed).max(0.0);
+            tokio::select! {
+                _ = tokio::time::sleep_until(replay_start + Duration::from_secs_f64(offset)) => {}
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+
+        if !args.keep_ids {
+            if let Some(attributes) = captured.message.attributes.as_mut() {
+                attributes.id = MessageField::some(UUID::build());
+            }
+        }
+        if let Err(e) = transport.send(captured.message).await {
+            error!("Failed to publish to {}: {}", String::from(&source), e);
+        } else {
+            published += 1;
+        }
+    }
+
+    println!("Replayed {} messages", published);
+    info!("uProtocol Replayer finished");
+    Ok(())
+}
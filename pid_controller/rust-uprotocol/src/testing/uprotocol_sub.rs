@@ -0,0 +1,438 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use log::{error, info};
+use protobuf::reflect::{MessageDescriptor, ReflectValueRef};
+use protobuf::well_known_types::any::Any;
+use protobuf::{Message, MessageDyn, MessageFull};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use up_rust::{UListener, UMessage, UPayloadFormat, UTransport, UUri};
+use up_transport_zenoh::UPTransportZenoh;
+
+use cruise_control_messages::{ActuationCommand, ClockStatus, EngageStatus, TargetSpeed, VelocityStatus};
+use pid_cruise_control::topic_filter::parse_filter;
+use pid_cruise_control::zenoh_peer::peer_config;
+use sdv_lab_topics::{self as topics, PayloadType};
+
+#[derive(Parser, Debug)]
+#[clap(
+    author,
+    version,
+    about = "uProtocol Subscriber - Print the messages of matching topics with rate and latency statistics",
+    long_about = "
+uProtocol Subscriber - Print the messages of matching topics with rate and latency statistics
+
+Usage example:
+cargo run --bin up_sub -- --filter curr_speed --filter \"//AAOS/0/2/FFFF\"
+cargo run --bin up_sub -- --filter \"//*/FFFF/FF/FFFF\" --output ndjson > capture.ndjson"
+)]
+struct Args {
+    #[clap(long, default_value = "127.0.0.1:7447", help = "Zenoh router endpoint")]
+    endpoint: String,
+
+    #[clap(long, default_value = "Subscriber", help = "Subscriber authority name")]
+    authority: String,
+
+    #[clap(
+        long,
+        help = "Source filter: topic name, URI with wildcards (authority *, entity FFFF, version FF, resource FFFF) \
+                or authority/ue_id/ue_version/resource_id; repeat for several (default: everything)",
+        action = clap::ArgAction::Append
+    )]
+    filter: Vec<String>,
+
+    #[clap(long, help = "Sink filter, e.g. to see the notifications and RPC messages sent to one entity")]
+    sink: Option<String>,
+
+    #[clap(long, value_enum, default_value_t = Output::Table, help = "Live table of the topics, or one JSON line per message")]
+    output: Output,
+
+    #[clap(long, default_value_t = 1000, help = "Refresh period in milliseconds of the table")]
+    refresh_ms: u64,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Output {
+    Table,
+    Ndjson,
+}
+
+/// A received message with its attributes, as printed.
+#[derive(Debug, Clone, Serialize)]
+struct Received {
+    /// Wall-clock time of reception in seconds since the epoch
+    received: f64,
+    topic: Option<&'static str>,
+    id: Option<String>,
+    #[serde(rename = "type")]
+    message_type: Option<String>,
+    source: Option<String>,
+    sink: Option<String>,
+    priority: Option<String>,
+    ttl: Option<u32>,
+    payload_format: Option<String>,
+    /// Reception time minus the creation time in the message id, in milliseconds
+    latency_ms: Option<f64>,
+    payload: Value,
+}
+
+impl Received {
+    fn new(message: &UMessage, received: f64) -> Self {
+        let source = message.source();
+        let format = message.payload_format().unwrap_or(UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED);
+        let payload = match &message.payload {
+            Some(payload) => decode_payload(source.and_then(topics::find), payload, format),
+            None => Value::Null,
+        };
+        Received {
+            received,
+            topic: source.and_then(topics::find).map(|topic| topic.name),
+            id: message.id().map(|id| id.to_hyphenated_string()),
+            message_type: message.type_().map(|value| enum_name(value, "UMESSAGE_TYPE_")),
+            source: source.map(String::from),
+            sink: message.sink().map(String::from),
+            priority: message.priority().map(|value| enum_name(value, "UPRIORITY_")),
+            ttl: message.ttl(),
+            payload_format: Some(enum_name(format, "UPAYLOAD_FORMAT_")),
+            latency_ms: message
+                .id()
+                .and_then(|id| id.get_time())
+                .map(|created| received * 1000.0 - created as f64),
+            payload,
+        }
+    }
+}
+
+// Protobuf enum value without its prefix, e.g. CS1 for UPRIORITY_CS1
+fn enum_name(value: impl std::fmt::Debug, prefix: &str) -> String {
+    let name = format!("{:?}", value);
+    name.strip_prefix(prefix).unwrap_or(&name).to_string()
+}
+
+// Protobuf payloads are decoded with the message the topic map declares for the topic
+fn decode_payload(topic: Option<&topics::Topic>, payload: &[u8], format: UPayloadFormat) -> Value {
+    let text = || String::from_utf8_lossy(payload).into_owned();
+    match format {
+        UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF | UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY => {
+            let descriptor = match topic.map(|topic| topic.payload) {
+                Some(PayloadType::Protobuf(name)) => message_descriptor(name),
+                _ => None,
+            };
+            let Some(descriptor) = descriptor else {
+                return json!({ "bytes": hex(payload) });
+            };
+            let bytes = if format == UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY {
+                match Any::parse_from_bytes(payload) {
+                    Ok(any) => any.value,
+                    Err(e) => return json!({ "error": e.to_string(), "bytes": hex(payload) }),
+                }
+            } else {
+                payload.to_vec()
+            };
+            match descriptor.parse_from_bytes(&bytes) {
+                Ok(message) => protobuf_json(&*message),
+                Err(e) => json!({ "error": e.to_string(), "bytes": hex(payload) }),
+            }
+        }
+        UPayloadFormat::UPAYLOAD_FORMAT_JSON => serde_json::from_slice(payload).unwrap_or_else(|_| Value::String(text())),
+        _ => match std::str::from_utf8(payload) {
+            Ok(text) => Value::String(text.to_string()),
+            Err(_) => json!({ "bytes": hex(payload) }),
+        },
+    }
+}
+
+fn message_descriptor(name: &str) -> Option<MessageDescriptor> {
+    [
+        VelocityStatus::descriptor(),
+        ClockStatus::descriptor(),
+        TargetSpeed::descriptor(),
+        EngageStatus::descriptor(),
+        ActuationCommand::descriptor(),
+    ]
+    .into_iter()
+    .find(|descriptor| descriptor.full_name() == name)
+}
+
+fn protobuf_json(message: &dyn MessageDyn) -> Value {
+    let mut fields = serde_json::Map::new();
+    for field in message.descriptor_dyn().fields() {
+        let value = if field.is_singular() {
+            reflect_json(field.get_singular_field_or_default(message))
+        } else {
+            // Repeated and map fields are not used by the cruise-control messages
+            Value::Null
+        };
+        fields.insert(field.name().to_string(), value);
+    }
+    Value::Object(fields)
+}
+
+fn reflect_json(value: ReflectValueRef) -> Value {
+    match value {
+        ReflectValueRef::U32(v) => json!(v),
+        ReflectValueRef::U64(v) => json!(v),
+        ReflectValueRef::I32(v) => json!(v),
+        ReflectValueRef::I64(v) => json!(v),
+        ReflectValueRef::F32(v) => json!(v),
+        ReflectValueRef::F64(v) => json!(v),
+        ReflectValueRef::Bool(v) => json!(v),
+        ReflectValueRef::String(v) => json!(v),
+        ReflectValueRef::Bytes(v) => json!(hex(v)),
+        ReflectValueRef::Enum(descriptor, v) => {
+            descriptor.value_by_number(v).map_or(json!(v), |value| json!(value.name()))
+        }
+        ReflectValueRef::Message(message) => protobuf_json(&*message),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Rate and latency of the messages of one topic.
+#[derive(Debug, Clone)]
+struct TopicStats {
+    count: u64,
+    first: f64,
+    last: f64,
+    latency_sum: f64,
+    latency_count: u64,
+    latency_min: f64,
+    latency_max: f64,
+    latest: Received,
+}
+
+impl TopicStats {
+    fn new(received: Received) -> Self {
+        let mut stats = TopicStats {
+            count: 0,
+            first: received.received,
+            last: received.received,
+            latency_sum: 0.0,
+            latency_count: 0,
+            latency_min: f64::INFINITY,
+            latency_max: f64::NEG_INFINITY,
+            latest: received.clone(),
+        };
+        stats.add(received);
+        stats
+    }
+
+    fn add(&mut self, received: Received) {
+        self.count += 1;
+        self.last = received.received;
+        if let Some(latency) = received.latency_ms {
+            self.latency_sum += latency;
+            self.latency_count += 1;
+            self.latency_min = self.latency_min.min(latency);
+            self.latency_max = self.latency_max.max(latency);
+        }
+        self.latest = received;
+    }
+
+    /// Messages per second between the first and the last message.
+    fn rate(&self) -> Option<f64> {
+        let span = self.last - self.first;
+        (self.count > 1 && span > 0.0).then(|| (self.count - 1) as f64 / span)
+    }
+
+    fn mean_latency(&self) -> Option<f64> {
+        (self.latency_count > 0).then(|| self.latency_sum / self.latency_count as f64)
+    }
+}
+
+/// Statistics by source URI.
+#[derive(Debug, Default)]
+struct Statistics {
+    topics: BTreeMap<String, TopicStats>,
+}
+
+impl Statistics {
+    fn add(&mut self, received: Received) {
+        let key = received.source.clone().unwrap_or_default();
+        match self.topics.get_mut(&key) {
+            Some(stats) => stats.add(received),
+            None => {
+                self.topics.insert(key, TopicStats::new(received));
+            }
+        }
+    }
+
+    fn table(&self) -> String {
+        let optional = |value: Option<f64>, precision: usize| {
+            value.map_or("-".to_string(), |value| format!("{:.*}", precision, value))
+        };
+        let mut table = format!(
+            "{:<26} {:<16} {:<12} {:<8} {:>7} {:>8} {:>8} {:>22} {:<10} {:<26} {:<36} {}\n",
+            "SOURCE", "TOPIC", "TYPE", "PRIORITY", "TTL", "COUNT", "RATE Hz", "LATENCY ms min/avg/max", "FORMAT", "SINK", "ID", "PAYLOAD"
+        );
+        for (source, stats) in &self.topics {
+            let latest = &stats.latest;
+            let latency = if stats.latency_count > 0 {
+                format!("{:.1}/{:.1}/{:.1}", stats.latency_min, stats.mean_latency().unwrap_or_default(), stats.latency_max)
+            } else {
+                "-".to_string()
+            };
+            table.push_str(&format!(
+                "{:<26} {:<16} {:<12} {:<8} {:>7} {:>8} {:>8} {:>22} {:<10} {:<26} {:<36} {}\n",
+                source,
+                latest.topic.unwrap_or("-"),
+                latest.message_type.as_deref().unwrap_or("-"),
+                latest.priority.as_deref().unwrap_or("-"),
+                latest.ttl.map_or("-".to_string(), |ttl| ttl.to_string()),
+                stats.count,
+                optional(stats.rate(), 1),
+                latency,
+                latest.payload_format.as_deref().unwrap_or("-"),
+                latest.sink.as_deref().unwrap_or("-"),
+                latest.id.as_deref().unwrap_or("-"),
+                latest.payload,
+            ));
+        }
+        table
+    }
+}
+
+// Forwards every message to the printing task
+struct SpyListener {
+    messages: UnboundedSender<UMessage>,
+}
+
+#[async_trait::async_trait]
+impl UListener for SpyListener {
+    async fn on_receive(&self, message: UMessage) {
+        let _ = self.messages.send(message);
+    }
+}
+
+fn wall_time() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Initialize logging
+    env_logger::init();
+
+    let args = Args::parse();
+
+    info!("*** Started uProtocol Subscriber");
+
+    let filters = if args.filter.is_empty() {
+        vec![UUri::any()]
+    } else {
+        args.filter.iter().map(|filter| parse_filter(filter)).collect::<Result<Vec<_>, _>>()?
+    };
+    let sink = args.sink.as_deref().map(parse_filter).transpose()?;
+
+    let transport: Arc<dyn UTransport> = Arc::new(
+        UPTransportZenoh::builder(args.authority.as_str())
+            .expect("invalid authority name")
+            .with_config(peer_config(Some(&args.endpoint))?)
+            .build()
+            .await?,
+    );
+
+    let (messages, mut messages_rx) = unbounded_channel();
+    for filter in &filters {
+        let listener = Arc::new(SpyListener { messages: messages.clone() });
+        transport.register_listener(filter, sink.as_ref(), listener).await?;
+        info!("Subscribed to {}", String::from(filter));
+    }
+
+    let mut statistics = Statistics::default();
+    let mut refresh = tokio::time::interval(Duration::from_millis(args.refresh_ms.max(100)));
+    loop {
+        tokio::select! {
+            Some(message) = messages_rx.recv() => {
+                let received = Received::new(&message, wall_time());
+                if args.output == Output::Ndjson {
+                    match serde_json::to_string(&received) {
+                        Ok(line) => println!("{}", line),
+                        Err(e) => error!("Failed to serialize message: {}", e),
+                    }
+                }
+                statistics.add(received);
+            }
+            _ = refresh.tick(), if args.output == Output::Table => {
+                // Clear the screen and redraw from the top left corner
+                print!("\x1b[2J\x1b[H{}", statistics.table());
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    // The statistics go to stderr in NDJSON mode, to keep stdout parseable
+    if args.output == Output::Ndjson {
+        eprint!("{}", statistics.table());
+    } else {
+        print!("{}", statistics.table());
+    }
+    info!("uProtocol Subscriber finished");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cruise_control_messages::{CruiseControlPayload, Encoding};
+    use up_rust::UMessageBuilder;
+
+    #[test]
+    fn test_payloads_are_decoded_by_format() {
+        let (payload, format) = VelocityStatus::from_value(42.5).encode(Encoding::Protobuf);
+        let message = UMessageBuilder::publish(topics::VELOCITY_STATUS.uri())
+            .with_ttl(500)
+            .build_with_payload(payload, format)
+            .unwrap();
+        let received = Received::new(&message, 0.0);
+        assert_eq!(received.topic, Some("curr_speed"));
+        assert_eq!(received.payload["velocity_kmh"], json!(42.5));
+        assert_eq!(received.payload_format.as_deref(), Some("PROTOBUF"));
+        assert_eq!(received.message_type.as_deref(), Some("PUBLISH"));
+        assert_eq!(received.ttl, Some(500));
+
+        let json_payload = decode_payload(None, br#"{"distance": 12.5}"#, UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+        assert_eq!(json_payload["distance"], json!(12.5));
+        assert_eq!(decode_payload(None, b"1", UPayloadFormat::UPAYLOAD_FORMAT_TEXT), json!("1"));
+        // Without a known message type, protobuf payloads are shown as bytes
+        assert_eq!(decode_payload(None, &[0x09, 0x01], UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF), json!({ "bytes": "0901" }));
+    }
+
+    #[test]
+    fn test_statistics_by_topic() {
+        let message = |uri: UUri| UMessageBuilder::publish(uri).build_with_payload("1", UPayloadFormat::UPAYLOAD_FORMAT_TEXT).unwrap();
+        let mut statistics = Statistics::default();
+        for index in 0..11 {
+            statistics.add(Received::new(&message(topics::VELOCITY_STATUS.uri()), 100.0 + index as f64 * 0.05));
+        }
+        statistics.add(Received::new(&message(topics::ENGAGE.uri()), 100.0));
+
+        let velocity = &statistics.topics[&String::from(&topics::VELOCITY_STATUS.uri())];
+        assert_eq!(velocity.count, 11);
+        assert!((velocity.rate().unwrap() - 20.0).abs() < 1e-6);
+        assert_eq!(statistics.topics[&String::from(&topics::ENGAGE.uri())].rate(), None);
+        assert_eq!(statistics.table().lines().count(), 3);
+    }
+}
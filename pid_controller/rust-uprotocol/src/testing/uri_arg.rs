@@ -0,0 +1,201 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use sdv_lab_topics as topics;
+use up_rust::UUri;
+
+/// What a URI given on the command line addresses, which decides the valid resource ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UriKind {
+    /// Topic to publish to
+    Topic,
+    /// RPC method to invoke
+    Method,
+    /// Entity receiving a notification
+    Sink,
+}
+
+impl UriKind {
+    fn resource_ids(self) -> RangeInclusive<u16> {
+        match self {
+            UriKind::Topic => 0x8000..=0xFFFE,
+            UriKind::Method => 0x0001..=0x7FFF,
+            UriKind::Sink => 0x0000..=0x0000,
+        }
+    }
+}
+
+impl fmt::Display for UriKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            UriKind::Topic => "topic",
+            UriKind::Method => "method",
+            UriKind::Sink => "notification sink",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Parses a URI given on the command line: a topic name of the topic map, a URI
+/// (`up://authority/ue_id/ue_version/resource_id` or `//authority/...`) or the
+/// `authority/ue_id/ue_version/resource_id` shorthand.
+///
+/// The numbers of the shorthand are hexadecimal, as in the URI string form, with an optional
+/// `0x` prefix: `AAOS/0/2/8001` is resource `0x8001`. Decimal numbers are never guessed, so
+/// `32769` is rejected as out of range instead of silently addressing another resource.
+/// The URI must not contain wildcards and its resource id must fit `kind`.
+pub fn parse_uri(arg: &str, kind: UriKind) -> Result<UUri, String> {
+    let uri = if let Some(topic) = topics::by_name(arg) {
+        topic.uri()
+    } else if arg.starts_with("up:") || arg.starts_with("//") {
+        UUri::from_str(arg).map_err(|e| format!("Invalid URI '{}': {}", arg, e))?
+    } else {
+        parse_shorthand(arg)?
+    };
+    validate(arg, &uri, kind)?;
+    Ok(uri)
+}
+
+fn parse_shorthand(arg: &str) -> Result<UUri, String> {
+    let parts: Vec<&str> = arg.split('/').collect();
+    let [authority, ue_id, ue_version, resource_id] = parts[..] else {
+        return Err(format!(
+            "Invalid URI '{}': expected a topic name, up://authority/ue_id/ue_version/resource_id \
+             or authority/ue_id/ue_version/resource_id",
+            arg
+        ));
+    };
+    if authority.is_empty() {
+        return Err(format!("Invalid URI '{}': the authority is empty", arg));
+    }
+
+    let invalid = |e: String| format!("Invalid URI '{}': {}", arg, e);
+    let ue_id = parse_hex("ue_id", ue_id, u32::MAX as u64).map_err(invalid)? as u32;
+    let ue_version = parse_hex("ue_version", ue_version, u8::MAX as u64).map_err(invalid)? as u8;
+    let resource_id = parse_hex("resource_id", resource_id, u16::MAX as u64).map_err(invalid)? as u16;
+    UUri::try_from_parts(authority, ue_id, ue_version, resource_id)
+        .map_err(|e| format!("Invalid URI '{}': {}", arg, e))
+}
+
+/// Parses a resource id given on its own, e.g. the origin of a notification: hexadecimal as in
+/// the URIs, and fitting `kind`.
+pub fn parse_resource_id(arg: &str, kind: UriKind) -> Result<u16, String> {
+    let resource_id = parse_hex("resource_id", arg, u16::MAX as u64)? as u16;
+    let range = kind.resource_ids();
+    if !range.contains(&resource_id) {
+        return Err(format!(
+            "resource_id {:#06X} is not a {} resource ({:#06X}-{:#06X})",
+            resource_id,
+            kind,
+            range.start(),
+            range.end()
+        ));
+    }
+    Ok(resource_id)
+}
+
+// Hexadecimal number, with an optional 0x prefix
+fn parse_hex(field: &str, value: &str, max: u64) -> Result<u64, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("{} '{}' is not a hexadecimal number", field, value));
+    }
+    match u64::from_str_radix(digits, 16) {
+        Ok(number) if number <= max => Ok(number),
+        _ => Err(format!("{} '{}' is out of range (hexadecimal, at most {:#X})", field, value, max)),
+    }
+}
+
+fn validate(arg: &str, uri: &UUri, kind: UriKind) -> Result<(), String> {
+    if uri.authority_name == "*" {
+        return Err(format!("Invalid URI '{}': wildcard authority", arg));
+    }
+    if uri.ue_id & 0xFFFF == 0xFFFF {
+        return Err(format!("Invalid URI '{}': wildcard ue_id", arg));
+    }
+    if uri.ue_version_major == 0xFF {
+        return Err(format!("Invalid URI '{}': wildcard ue_version", arg));
+    }
+    let range = kind.resource_ids();
+    let resource_id = uri.resource_id;
+    if resource_id > u16::MAX as u32 || !range.contains(&(resource_id as u16)) {
+        return Err(format!(
+            "Invalid URI '{}': resource_id {:#06X} is not a {} resource ({:#06X}-{:#06X})",
+            arg,
+            resource_id,
+            kind,
+            range.start(),
+            range.end()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shorthand_round_trips_with_uri_strings() {
+        for shorthand in ["AAOS/0/2/8001", "EGOVehicle/0/2/8002", "cruise-control.app/C110/1/8000", "CruiseControl/0/2/FFFE"] {
+            let uri = parse_uri(shorthand, UriKind::Topic).unwrap();
+            assert_eq!(uri, UUri::from_str(&format!("//{}", shorthand)).unwrap());
+            assert_eq!(uri, UUri::from_str(&String::from(&uri)).unwrap());
+            assert_eq!(parse_uri(&String::from(&uri), UriKind::Topic).unwrap(), uri);
+        }
+        assert_eq!(
+            parse_uri("cruise-control.app/0xC110/0x1/0x1", UriKind::Method).unwrap(),
+            UUri::from_str("up://cruise-control.app/C110/1/1").unwrap()
+        );
+        assert_eq!(parse_uri("curr_speed", UriKind::Topic).unwrap(), topics::VELOCITY_STATUS.uri());
+    }
+
+    #[test]
+    fn test_invalid_uris_are_rejected() {
+        // Decimal 32769 (0x8001) is not guessed
+        assert!(parse_uri("AAOS/0/2/32769", UriKind::Topic).unwrap_err().contains("out of range"));
+        assert!(parse_uri("AAOS/0/2/80g1", UriKind::Topic).unwrap_err().contains("not a hexadecimal"));
+        assert!(parse_uri("AAOS/0/2", UriKind::Topic).is_err());
+        assert!(parse_uri("/0/2/8001", UriKind::Topic).is_err());
+        assert!(parse_uri("AAOS/0/100/8001", UriKind::Topic).unwrap_err().contains("ue_version"));
+        assert!(parse_uri("*/0/2/8001", UriKind::Topic).unwrap_err().contains("wildcard"));
+        assert!(parse_uri("AAOS/FFFF/2/8001", UriKind::Topic).unwrap_err().contains("wildcard"));
+
+        // Resource ids must match what the URI addresses
+        assert!(parse_uri("AAOS/0/2/FFFF", UriKind::Topic).is_err());
+        assert!(parse_uri("AAOS/0/2/1", UriKind::Topic).unwrap_err().contains("not a topic"));
+        assert!(parse_uri("CruiseControl/0/2/1", UriKind::Method).is_ok());
+        assert!(parse_uri("CruiseControl/0/2/8001", UriKind::Method).is_err());
+        assert!(parse_uri("CruiseControl/0/2/0", UriKind::Sink).is_ok());
+        assert!(parse_uri("CruiseControl/0/2/1", UriKind::Sink).is_err());
+    }
+
+    #[test]
+    fn test_resource_ids_are_hexadecimal() {
+        assert_eq!(parse_resource_id("8001", UriKind::Topic), Ok(0x8001));
+        assert_eq!(parse_resource_id("0xFFFE", UriKind::Topic), Ok(0xFFFE));
+        assert!(parse_resource_id("32769", UriKind::Topic).unwrap_err().contains("out of range"));
+        assert!(parse_resource_id("0x7FFF", UriKind::Topic).unwrap_err().contains("not a topic"));
+        assert!(parse_resource_id("FFFF", UriKind::Topic).is_err());
+        assert!(parse_resource_id("", UriKind::Topic).is_err());
+    }
+}
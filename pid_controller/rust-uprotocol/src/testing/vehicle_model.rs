@@ -0,0 +1,153 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+const GRAVITY: f64 = 9.81;
+const AIR_DENSITY: f64 = 1.225;
+
+/// Physical parameters of the longitudinal vehicle model.
+#[derive(Debug, Clone, Copy)]
+pub struct VehicleParameters {
+    /// Vehicle mass in kg
+    pub mass: f64,
+    /// Aerodynamic drag coefficient
+    pub drag_coefficient: f64,
+    /// Frontal area in m²
+    pub frontal_area: f64,
+    /// Rolling resistance coefficient
+    pub rolling_resistance: f64,
+    /// Maximum tractive force at the wheels in N (throttle map at low speed)
+    pub max_drive_force: f64,
+    /// Maximum power at the wheels in W (throttle map at high speed)
+    pub max_drive_power: f64,
+    /// Maximum braking force in N
+    pub max_brake_force: f64,
+    /// Time constant of the powertrain and brake response in s
+    pub actuator_time_constant: f64,
+}
+
+impl Default for VehicleParameters {
+    // Mid-size passenger car
+    fn default() -> Self {
+        VehicleParameters {
+            mass: 1600.0,
+            drag_coefficient: 0.28,
+            frontal_area: 2.3,
+            rolling_resistance: 0.012,
+            max_drive_force: 5500.0,
+            max_drive_power: 120_000.0,
+            max_brake_force: 12_000.0,
+            actuator_time_constant: 0.3,
+        }
+    }
+}
+
+/// Longitudinal point-mass vehicle driven by the normalized cruise control command
+/// (`0..1` throttle, `-1..0` brake), the same mapping the ego vehicle applies in CARLA.
+pub struct VehicleModel {
+    parameters: VehicleParameters,
+    /// Road grade in percent, positive uphill
+    grade: f64,
+    /// Speed in m/s
+    speed: f64,
+    /// Command after the actuator lag
+    applied_command: f64,
+}
+
+impl VehicleModel {
+    pub fn new(parameters: VehicleParameters, grade: f64, initial_speed_kmh: f64) -> Self {
+        VehicleModel {
+            parameters,
+            grade,
+            speed: initial_speed_kmh.max(0.0) / 3.6,
+            applied_command: 0.0,
+        }
+    }
+
+    pub fn speed_kmh(&self) -> f64 {
+        self.speed * 3.6
+    }
+
+    fn drive_force(&self, throttle: f64) -> f64 {
+        let power_limited = self.parameters.max_drive_power / self.speed.max(1.0);
+        throttle * self.parameters.max_drive_force.min(power_limited)
+    }
+
+    fn resistance_force(&self) -> f64 {
+        let p = &self.parameters;
+        let slope = (self.grade / 100.0).atan();
+        let aero = 0.5 * AIR_DENSITY * p.drag_coefficient * p.frontal_area * self.speed * self.speed;
+        let rolling = p.rolling_resistance * p.mass * GRAVITY * slope.cos();
+        let gravity = p.mass * GRAVITY * slope.sin();
+        aero + rolling + gravity
+    }
+
+    /// Advances the model by `delta_time` seconds under `command` and returns the speed in km/h.
+    pub fn step(&mut self, command: f64, delta_time: f64) -> f64 {
+        let command = command.clamp(-1.0, 1.0);
+        let tau = self.parameters.actuator_time_constant;
+        self.applied_command += (command - self.applied_command) * (delta_time / (tau + delta_time));
+
+        let throttle = self.applied_command.max(0.0);
+        let brake = (-self.applied_command).max(0.0);
+        let force = self.drive_force(throttle)
+            - brake * self.parameters.max_brake_force
+            - self.resistance_force();
+
+        // Brakes and resistances can stop the vehicle but never push it backwards
+        self.speed = (self.speed + force / self.parameters.mass * delta_time).max(0.0);
+
+        self.speed_kmh()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f64 = 0.05;
+
+    fn run(vehicle: &mut VehicleModel, command: f64, seconds: f64) -> f64 {
+        let mut speed = vehicle.speed_kmh();
+        for _ in 0..(seconds / DT) as usize {
+            speed = vehicle.step(command, DT);
+        }
+        speed
+    }
+
+    #[test]
+    fn test_coasting_decelerates_to_standstill() {
+        let mut vehicle = VehicleModel::new(VehicleParameters::default(), 0.0, 100.0);
+
+        assert!(run(&mut vehicle, 0.0, 10.0) < 100.0);
+        assert_eq!(run(&mut vehicle, -1.0, 30.0), 0.0);
+    }
+
+    #[test]
+    fn test_full_throttle_is_power_limited() {
+        let mut vehicle = VehicleModel::new(VehicleParameters::default(), 0.0, 0.0);
+        let top_speed = run(&mut vehicle, 1.0, 300.0);
+
+        assert!((150.0..250.0).contains(&top_speed), "top speed {}", top_speed);
+    }
+
+    #[test]
+    fn test_grade_needs_more_throttle() {
+        let mut flat = VehicleModel::new(VehicleParameters::default(), 0.0, 80.0);
+        let mut uphill = VehicleModel::new(VehicleParameters::default(), 6.0, 80.0);
+
+        assert!(run(&mut uphill, 0.2, 20.0) < run(&mut flat, 0.2, 20.0));
+    }
+}
@@ -0,0 +1,148 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::time::Instant;
+
+/// Source of the time the controller integrates over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    /// `elapsed_seconds` of the simulation, received on the clock topic
+    Simulation,
+    /// Monotonic clock of the controller host, for a simulation that does not run in real time
+    /// or a vehicle without a clock topic
+    Wall,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimeBaseConfig {
+    pub source: TimeSource,
+    /// Steps longer than this in seconds are treated as a pause, over which the integrator is held
+    pub max_step: f64,
+}
+
+impl Default for TimeBaseConfig {
+    fn default() -> Self {
+        TimeBaseConfig {
+            source: TimeSource::Simulation,
+            max_step: 1.0,
+        }
+    }
+}
+
+/// How the time base moved since the previous control sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockStep {
+    /// First sample, or the first after a restart
+    Start,
+    /// Regular step of the given seconds
+    Advance(f64),
+    /// The clock did not advance, e.g. a paused simulation or two velocity samples per tick
+    Stalled,
+    /// The clock moved back, e.g. a CARLA world reload
+    Backward { from: f64, to: f64 },
+    /// The clock jumped ahead by more than `max_step` seconds
+    Gap(f64),
+}
+
+/// Turns the received clock into a monotonic time base for the controller.
+///
+/// The controllers require strictly increasing sample times. `step` classifies each new time so
+/// that the caller can reset the controller on a backward jump, hold the integrator over a gap and
+/// skip samples on which the clock did not advance.
+#[derive(Debug, Clone)]
+pub struct TimeBase {
+    config: TimeBaseConfig,
+    origin: Instant,
+    last: Option<f64>,
+}
+
+impl TimeBase {
+    pub fn new(config: TimeBaseConfig) -> Self {
+        TimeBase {
+            config,
+            origin: Instant::now(),
+            last: None,
+        }
+    }
+
+    pub fn source(&self) -> TimeSource {
+        self.config.source
+    }
+
+    /// Time of a sample in seconds, from the clock topic or from the wall clock.
+    pub fn sample_time(&self, clock_time: f64) -> f64 {
+        match self.config.source {
+            TimeSource::Simulation => clock_time,
+            TimeSource::Wall => self.origin.elapsed().as_secs_f64(),
+        }
+    }
+
+    /// Classifies `time` against the previous sample and makes it the new reference, unless the
+    /// clock stalled.
+    pub fn step(&mut self, time: f64) -> ClockStep {
+        let step = match self.last {
+            None => ClockStep::Start,
+            Some(last) if time < last => ClockStep::Backward { from: last, to: time },
+            Some(last) if time == last => ClockStep::Stalled,
+            Some(last) if time - last > self.config.max_step => ClockStep::Gap(time - last),
+            Some(last) => ClockStep::Advance(time - last),
+        };
+        if step != ClockStep::Stalled {
+            self.last = Some(time);
+        }
+        step
+    }
+
+    /// Forgets the previous sample, e.g. while the controller is disengaged.
+    pub fn restart(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_steps() {
+        let mut time_base = TimeBase::new(TimeBaseConfig::default());
+        assert_eq!(time_base.step(0.0), ClockStep::Start);
+        assert_eq!(time_base.step(0.05), ClockStep::Advance(0.05));
+        assert_eq!(time_base.step(0.05), ClockStep::Stalled);
+        assert_eq!(time_base.step(0.1), ClockStep::Advance(0.1 - 0.05));
+        assert_eq!(time_base.step(5.0), ClockStep::Gap(5.0 - 0.1));
+        assert_eq!(time_base.step(0.02), ClockStep::Backward { from: 5.0, to: 0.02 });
+        assert_eq!(time_base.step(0.07), ClockStep::Advance(0.07 - 0.02));
+
+        time_base.restart();
+        assert_eq!(time_base.step(100.0), ClockStep::Start);
+    }
+
+    #[test]
+    fn test_wall_time_ignores_clock_topic() {
+        let time_base = TimeBase::new(TimeBaseConfig {
+            source: TimeSource::Wall,
+            ..TimeBaseConfig::default()
+        });
+        let first = time_base.sample_time(1000.0);
+        let second = time_base.sample_time(0.0);
+        assert!(first < 1.0);
+        assert!(second >= first);
+
+        let simulation = TimeBase::new(TimeBaseConfig::default());
+        assert_eq!(simulation.sample_time(12.5), 12.5);
+    }
+}
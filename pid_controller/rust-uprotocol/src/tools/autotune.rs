@@ -0,0 +1,289 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use clap::Parser;
+use log::{debug, error, info, warn};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use up_rust::{LocalUriProvider, StaticUriProvider, UListener, UMessage, UMessageBuilder, UTransport, UUri};
+use up_transport_zenoh::UPTransportZenoh;
+
+use cruise_control_messages::{ActuationCommand, ClockStatus, CruiseControlPayload, Encoding, VelocityStatus};
+use pid_cruise_control::autotune::{schedule_toml, RelayConfig, RelayExperiment, TuningRule, UltimateGains};
+use pid_cruise_control::zenoh_peer::peer_config;
+use sdv_lab_topics as topics;
+
+/// Identifies the ultimate gain and period of the vehicle with a relay-feedback experiment
+/// and writes the proposed gains as a gain schedule for the pid_controller
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(long, default_value = None)]
+    router: Option<String>,
+    /// Setpoint in km/h of one experiment; repeat for a gain schedule over several speeds
+    #[clap(long = "speed", required = true)]
+    speeds: Vec<f64>,
+    /// The command switches between bias + amplitude and bias - amplitude
+    #[clap(long, default_value_t = 0.3)]
+    amplitude: f64,
+    /// Initial command bias; the high command has to be able to reach the setpoint
+    #[clap(long, default_value_t = 0.3, allow_hyphen_values = true)]
+    bias: f64,
+    /// Velocity band in km/h around the setpoint in which the relay does not switch
+    #[clap(long, default_value_t = 0.5)]
+    hysteresis: f64,
+    /// Oscillation periods discarded before measuring
+    #[clap(long, default_value_t = 3)]
+    settle_cycles: usize,
+    /// Oscillation periods averaged into the result
+    #[clap(long, default_value_t = 4)]
+    measure_cycles: usize,
+    /// Wall-clock seconds before an experiment is aborted
+    #[clap(long, default_value_t = 300.0, value_parser = parse_timeout)]
+    timeout: f64,
+    /// Rule used for the gains written to the output file
+    #[clap(long, value_enum, default_value_t = Rule::ZieglerNichols)]
+    rule: Rule,
+    /// Gain schedule file to write, loadable with `pid_controller --gain-schedule`
+    #[clap(long, default_value = "gain_schedule.autotune.toml")]
+    output: PathBuf,
+    /// Encoding of the published actuation command
+    #[clap(long, value_enum, default_value_t = PayloadEncoding::Protobuf)]
+    payload_format: PayloadEncoding,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Rule {
+    ZieglerNichols,
+    TyreusLuyben,
+    PessenIntegral,
+    SomeOvershoot,
+    NoOvershoot,
+}
+
+impl From<Rule> for TuningRule {
+    fn from(rule: Rule) -> Self {
+        match rule {
+            Rule::ZieglerNichols => TuningRule::ZieglerNichols,
+            Rule::TyreusLuyben => TuningRule::TyreusLuyben,
+            Rule::PessenIntegral => TuningRule::PessenIntegral,
+            Rule::SomeOvershoot => TuningRule::SomeOvershoot,
+            Rule::NoOvershoot => TuningRule::NoOvershoot,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum PayloadEncoding {
+    Protobuf,
+    Text,
+}
+
+impl From<PayloadEncoding> for Encoding {
+    fn from(encoding: PayloadEncoding) -> Self {
+        match encoding {
+            PayloadEncoding::Protobuf => Encoding::Protobuf,
+            PayloadEncoding::Text => Encoding::Text,
+        }
+    }
+}
+
+// A positive, finite number of seconds
+fn parse_timeout(s: &str) -> Result<f64, String> {
+    let seconds: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if seconds.is_finite() && seconds > 0.0 {
+        Ok(seconds)
+    } else {
+        Err(format!("expected a positive number of seconds, got {}", s))
+    }
+}
+
+async fn publish_command(transport: &Arc<dyn UTransport>, encoding: Encoding, command: f64) {
+    let (payload, format) = ActuationCommand::from_value(command).encode(encoding);
+    let message = UMessageBuilder::publish(topics::ACTUATION.uri())
+        .build_with_payload(payload, format)
+        .unwrap();
+    if let Err(e) = transport.send(message).await {
+        error!("Failed to publish actuation command: {}", e);
+    }
+}
+
+// Keeps the latest simulation time
+struct ClockListener {
+    time: Arc<Mutex<f64>>,
+}
+
+#[async_trait::async_trait]
+impl UListener for ClockListener {
+    async fn on_receive(&self, message: UMessage) {
+        match ClockStatus::decode_message(&message) {
+            Ok(clock) => *self.time.lock().unwrap() = clock.elapsed_seconds,
+            Err(e) => error!("Failed to parse clock payload: {}", e),
+        }
+    }
+}
+
+// Steps the running experiment on every velocity sample and hands it back once done
+struct VelocityListener {
+    experiment: Arc<Mutex<Option<RelayExperiment>>>,
+    time: Arc<Mutex<f64>>,
+    finished: UnboundedSender<RelayExperiment>,
+    transport: Arc<dyn UTransport>,
+    encoding: Encoding,
+}
+
+#[async_trait::async_trait]
+impl UListener for VelocityListener {
+    async fn on_receive(&self, message: UMessage) {
+        let velocity = match VelocityStatus::decode_message(&message) {
+            Ok(velocity) => velocity.velocity_kmh,
+            Err(e) => {
+                error!("Failed to parse velocity payload: {}", e);
+                return;
+            }
+        };
+        let time = *self.time.lock().unwrap();
+
+        let command = {
+            let mut experiment = self.experiment.lock().unwrap();
+            let Some(running) = experiment.as_mut() else {
+                return;
+            };
+            let command = running.step(velocity, time);
+            debug!("t={:.2}s velocity={:.2} km/h command={:.3} cycles={}", time, velocity, command, running.cycles());
+            if running.is_done() {
+                let _ = self.finished.send(experiment.take().unwrap());
+            }
+            command
+        };
+        publish_command(&self.transport, self.encoding, command).await;
+    }
+}
+
+fn print_result(speed: f64, ultimate: &UltimateGains) {
+    println!(
+        "{} km/h => Ku={:.6}, Tu={:.3} s (oscillation ±{:.3} km/h around command {:.4})",
+        speed, ultimate.ku, ultimate.tu, ultimate.amplitude, ultimate.bias
+    );
+    for rule in TuningRule::ALL {
+        let (kp, ki, kd) = rule.gains(ultimate);
+        println!("    {:<16} Kp={:.6}, Ki={:.6}, Kd={:.6}", rule.name(), kp, ki, kd);
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+
+    let args = Args::parse();
+    let config = RelayConfig {
+        amplitude: args.amplitude,
+        bias: args.bias,
+        hysteresis: args.hysteresis,
+        settle_cycles: args.settle_cycles,
+        measure_cycles: args.measure_cycles,
+    };
+    config.validate()?;
+    if args.speeds.iter().any(|speed| !speed.is_finite() || *speed <= 0.0) {
+        return Err("--speed must be positive".into());
+    }
+    let mut speeds = args.speeds.clone();
+    speeds.sort_by(f64::total_cmp);
+    if let Some(speed) = speeds.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(format!("--speed {} is given more than once", speed[0]).into());
+    }
+
+    // The experiment stands in for the cruise controller, which must not run at the same time
+    let component = topics::PID_CONTROLLER;
+    let uri_provider = StaticUriProvider::new(component.authority, topics::UE_ID, topics::UE_VERSION_MAJOR);
+    let transport: Arc<dyn UTransport> = Arc::new(
+        UPTransportZenoh::builder(uri_provider.get_authority())
+            .expect("invalid authority name")
+            .with_config(peer_config(args.router.as_ref().map(|router| format!("{}:7447", router)).as_deref())?)
+            .build()
+            .await?,
+    );
+    let encoding = Encoding::from(args.payload_format);
+
+    let time = Arc::new(Mutex::new(0.0));
+    let experiment = Arc::new(Mutex::new(None));
+    let (finished, mut finished_rx) = unbounded_channel();
+    let velocity_uri: UUri = topics::VELOCITY_STATUS.uri();
+    transport
+        .register_listener(&topics::CLOCK_STATUS.uri(), None, Arc::new(ClockListener { time: Arc::clone(&time) }))
+        .await?;
+    let listener = VelocityListener {
+        experiment: Arc::clone(&experiment),
+        time: Arc::clone(&time),
+        finished,
+        transport: Arc::clone(&transport),
+        encoding,
+    };
+    transport.register_listener(&velocity_uri, None, Arc::new(listener)).await?;
+
+    println!("Relay auto-tuning (stop the pid_controller while it runs, CTRL-C to abort)...");
+    let mut results = Vec::new();
+    for speed in &args.speeds {
+        info!("Relay experiment around {} km/h [amplitude: {}, bias: {}]", speed, config.amplitude, config.bias);
+        *experiment.lock().unwrap() = Some(RelayExperiment::new(config, *speed));
+
+        let outcome = tokio::select! {
+            outcome = tokio::time::timeout(Duration::from_secs_f64(args.timeout), finished_rx.recv()) => outcome,
+            _ = tokio::signal::ctrl_c() => {
+                experiment.lock().unwrap().take();
+                publish_command(&transport, encoding, 0.0).await;
+                return Err("aborted".into());
+            }
+        };
+        let done = match outcome {
+            Ok(Some(done)) => done,
+            _ => {
+                let cycles = experiment.lock().unwrap().take().map_or(0, |running| running.cycles());
+                publish_command(&transport, encoding, 0.0).await;
+                return Err(format!(
+                    "no sustained oscillation around {} km/h within {} s ({} cycles); check that velocity and clock are published and adjust --bias/--amplitude",
+                    speed, args.timeout, cycles
+                )
+                .into());
+            }
+        };
+
+        match done.result() {
+            Some(ultimate) => {
+                print_result(*speed, &ultimate);
+                results.push((*speed, ultimate));
+            }
+            None => warn!("Oscillation around {} km/h stayed within the hysteresis band, skipped", speed),
+        }
+    }
+    // Coast once done
+    publish_command(&transport, encoding, 0.0).await;
+
+    if results.is_empty() {
+        return Err("no experiment succeeded".into());
+    }
+    std::fs::write(&args.output, schedule_toml(&results, args.rule.into()))?;
+    println!(
+        "Gains ({}) written to {}, run: pid_controller --gain-schedule {}",
+        TuningRule::from(args.rule).name(),
+        args.output.display(),
+        args.output.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use pid_cruise_control::metrics::{MetricsConfig, MetricsReport};
+use pid_cruise_control::recorder::read_rows;
+
+/// Evaluates recorded control sessions and optionally gates them against a baseline report
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// CSV or Parquet files of one session, in recording order
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+    /// Write the JSON report to this file
+    #[clap(long)]
+    json: Option<PathBuf>,
+    /// Baseline JSON report to compare against
+    #[clap(long)]
+    baseline: Option<PathBuf>,
+    /// Allowed relative degradation of each metric before it counts as a regression
+    #[clap(long, default_value_t = 0.1)]
+    tolerance: f64,
+    /// Settling band as a fraction of the step size
+    #[clap(long, default_value_t = 0.02)]
+    settling_fraction: f64,
+    /// Minimum settling band in km/h
+    #[clap(long, default_value_t = 0.5)]
+    settling_min_band: f64,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let mut rows = Vec::new();
+    for file in &args.files {
+        match read_rows(file) {
+            Ok(file_rows) => rows.extend(file_rows),
+            Err(e) => {
+                eprintln!("Failed to read {}: {}", file.display(), e);
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    let config = MetricsConfig {
+        settling_fraction: args.settling_fraction,
+        settling_min_band: args.settling_min_band,
+        ..MetricsConfig::default()
+    };
+    let report = MetricsReport::from_rows(&rows, &config);
+    print!("{}", report);
+
+    if let Some(path) = &args.json {
+        if let Err(e) = std::fs::write(path, report.to_json()) {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+            return ExitCode::from(2);
+        }
+    }
+
+    if let Some(path) = &args.baseline {
+        let baseline = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| MetricsReport::from_json(&json));
+        let baseline = match baseline {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                eprintln!("Failed to load baseline {}: {}", path.display(), e);
+                return ExitCode::from(2);
+            }
+        };
+        let regressions = report.regressions(&baseline, args.tolerance);
+        if !regressions.is_empty() {
+            println!("\n{} regression(s) against {}:", regressions.len(), path.display());
+            for regression in &regressions {
+                println!("  {}", regression);
+            }
+            return ExitCode::FAILURE;
+        }
+        println!("\nNo regressions against {}", path.display());
+    }
+
+    ExitCode::SUCCESS
+}
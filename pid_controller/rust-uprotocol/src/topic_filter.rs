@@ -0,0 +1,61 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::str::FromStr;
+use up_rust::UUri;
+use sdv_lab_topics as topics;
+
+/// Parses a topic filter: a topic name of the topic map, a URI, or
+/// `authority/ue_id/ue_version/resource_id` with hex numbers. `*` alone matches everything;
+/// otherwise the wildcards are `*` for the authority, `FFFF` for the entity and the resource
+/// and `FF` for the version.
+pub fn parse_filter(filter: &str) -> Result<UUri, String> {
+    if filter == "*" {
+        return Ok(UUri::any());
+    }
+    if let Some(topic) = topics::by_name(filter) {
+        return Ok(topic.uri());
+    }
+    let uri = if filter.starts_with("//") || filter.starts_with("up:") {
+        filter.to_string()
+    } else {
+        format!("//{}", filter)
+    };
+    UUri::from_str(&uri).map_err(|e| format!("Invalid filter '{}': {}", filter, e))
+}
+
+/// Whether `uri` matches one of `filters`.
+pub fn matches_any(filters: &[UUri], uri: &UUri) -> bool {
+    filters.iter().any(|filter| filter.matches(uri))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_accept_names_uris_and_parts() {
+        assert_eq!(parse_filter("curr_speed").unwrap(), topics::VELOCITY_STATUS.uri());
+        assert_eq!(parse_filter("//EGOVehicle/0/2/8001").unwrap(), topics::VELOCITY_STATUS.uri());
+        assert_eq!(parse_filter("EGOVehicle/0/2/8001").unwrap(), topics::VELOCITY_STATUS.uri());
+
+        let filters = [parse_filter("//*/0/2/FFFF").unwrap()];
+        assert!(matches_any(&filters, &topics::ACTUATION.uri()));
+        assert!(parse_filter("*").unwrap().matches(&topics::TARGET_SPEED.uri()));
+        assert!(!matches_any(&[parse_filter("cc_speed").unwrap()], &topics::ENGAGE.uri()));
+        assert!(parse_filter("no_such_topic").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use log::{info, error, warn};
+use up_rust::{LocalUriProvider, UAttributes, UPayloadFormat, UTransport};
+use up_rust::communication::{
+    CallOptions, InMemoryRpcServer, Publisher, RegistrationError, RequestHandler, RpcServer,
+    ServiceInvocationError, SimplePublisher, UPayload,
+};
+
+use crate::longitudinal_controller::LongitudinalController;
+use crate::pid_controller::PidGains;
+
+pub const RESOURCE_ID_GET_GAINS: u16 = 0x0001;
+pub const RESOURCE_ID_SET_GAINS: u16 = 0x0002;
+pub const RESOURCE_ID_GAINS_STATUS: u16 = sdv_lab_topics::GAINS_STATUS.resource_id;
+
+/// Partial gain update accepted by the `SetGains` endpoint; missing fields keep their value
+/// and `"output_limits": null` removes the limits.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GainsUpdate {
+    kp: Option<f64>,
+    ki: Option<f64>,
+    kd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "present")]
+    output_limits: Option<Option<(f64, f64)>>,
+}
+
+// Distinguishes a field set to `null` (`Some(None)`) from a missing one (`None`)
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl GainsUpdate {
+    fn apply_to(&self, gains: PidGains) -> PidGains {
+        PidGains {
+            kp: self.kp.unwrap_or(gains.kp),
+            ki: self.ki.unwrap_or(gains.ki),
+            kd: self.kd.unwrap_or(gains.kd),
+            output_limits: self.output_limits.unwrap_or(gains.output_limits),
+        }
+    }
+}
+
+fn gains_payload(gains: &PidGains) -> UPayload {
+    UPayload::new(
+        serde_json::to_vec(gains).unwrap(),
+        UPayloadFormat::UPAYLOAD_FORMAT_JSON,
+    )
+}
+
+/// Exposes the controller gains as uProtocol RPC endpoints and announces every change on
+/// the gains status topic.
+pub struct TuningService<C: LongitudinalController> {
+    controller: Arc<Mutex<C>>,
+    rpc_server: InMemoryRpcServer,
+    publisher: Arc<SimplePublisher>,
+    status_ttl_ms: u32,
+    // Registered endpoints, unregistered on stop
+    endpoints: Mutex<Vec<(u16, Arc<dyn RequestHandler>)>>,
+}
+
+impl<C: LongitudinalController> TuningService<C> {
+    pub fn new(
+        controller: Arc<Mutex<C>>,
+        transport: Arc<dyn UTransport>,
+        uri_provider: Arc<dyn LocalUriProvider>,
+        status_ttl_ms: u32,
+    ) -> Self {
+        let rpc_server = InMemoryRpcServer::new(transport.clone(), uri_provider.clone());
+        let publisher = Arc::new(SimplePublisher::new(transport, uri_provider));
+
+        TuningService {
+            controller,
+            rpc_server,
+            publisher,
+            status_ttl_ms,
+            endpoints: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub async fn start(&self) -> Result<(), RegistrationError> {
+        let Some(gains) = self.controller.lock().unwrap().gains() else {
+            info!("Controller has no tunable gains, tuning service not started");
+            return Ok(());
+        };
+
+        let endpoints: [(u16, Arc<dyn RequestHandler>); 2] = [
+            (RESOURCE_ID_GET_GAINS, Arc::new(GetGains(self.controller.clone()))),
+            (
+                RESOURCE_ID_SET_GAINS,
+                Arc::new(SetGains {
+                    controller: self.controller.clone(),
+                    publisher: self.publisher.clone(),
+                    status_ttl_ms: self.status_ttl_ms,
+                }),
+            ),
+        ];
+        for (resource_id, handler) in endpoints {
+            self.rpc_server
+                .register_endpoint(None, resource_id, handler.clone())
+                .await?;
+            self.endpoints.lock().unwrap().push((resource_id, handler));
+        }
+
+        info!("Tuning service registered [getGains: 0x{:04X}, setGains: 0x{:04X}]", RESOURCE_ID_GET_GAINS, RESOURCE_ID_SET_GAINS);
+        publish_gains(&self.publisher, self.status_ttl_ms, &gains).await;
+        Ok(())
+    }
+
+    /// Unregisters the endpoints, so that no request is served once the controller stops.
+    pub async fn stop(&self) {
+        let endpoints: Vec<(u16, Arc<dyn RequestHandler>)> = self.endpoints.lock().unwrap().drain(..).collect();
+        for (resource_id, handler) in endpoints {
+            if let Err(e) = self.rpc_server.unregister_endpoint(None, resource_id, handler).await {
+                warn!("Failed to unregister endpoint 0x{:04X}: {}", resource_id, e);
+            }
+        }
+    }
+}
+
+async fn publish_gains(publisher: &SimplePublisher, status_ttl_ms: u32, gains: &PidGains) {
+    if let Err(e) = publisher
+        .publish(
+            RESOURCE_ID_GAINS_STATUS,
+            CallOptions::for_publish(Some(status_ttl_ms), None, None),
+            Some(gains_payload(gains)),
+        )
+        .await
+    {
+        error!("Failed to publish gains status: {}", e);
+    }
+}
+
+// The handler for incoming requests to read the current gains.
+struct GetGains<C: LongitudinalController>(Arc<Mutex<C>>);
+
+#[async_trait::async_trait]
+impl<C: LongitudinalController> RequestHandler for GetGains<C> {
+    async fn handle_request(
+        &self,
+        _resource_id: u16,
+        _message_attributes: &UAttributes,
+        _request_payload: Option<UPayload>,
+    ) -> Result<Option<UPayload>, ServiceInvocationError> {
+        let gains = self.0.lock().unwrap().gains().ok_or_else(|| {
+            ServiceInvocationError::Unimplemented("Controller has no tunable gains".to_string())
+        })?;
+        Ok(Some(gains_payload(&gains)))
+    }
+}
+
+// The handler for incoming requests to change the gains.
+struct SetGains<C: LongitudinalController> {
+    controller: Arc<Mutex<C>>,
+    publisher: Arc<SimplePublisher>,
+    status_ttl_ms: u32,
+}
+
+#[async_trait::async_trait]
+impl<C: LongitudinalController> RequestHandler for SetGains<C> {
+    async fn handle_request(
+        &self,
+        _resource_id: u16,
+        message_attributes: &UAttributes,
+        request_payload: Option<UPayload>,
+    ) -> Result<Option<UPayload>, ServiceInvocationError> {
+        let source = message_attributes.source.as_ref().ok_or_else(|| {
+            error!("Received SetGains request without source");
+            ServiceInvocationError::InvalidArgument("Request has no source".to_string())
+        })?;
+        let Some(payload) = request_payload else {
+            error!("Received empty payload for SetGains request");
+            return Err(ServiceInvocationError::InvalidArgument(
+                "Payload cannot be empty".to_string(),
+            ));
+        };
+
+        let update: GainsUpdate = serde_json::from_slice(&payload.payload()).map_err(|e| {
+            error!("Failed to parse payload: {}", e);
+            ServiceInvocationError::InvalidArgument(format!("Invalid payload format: {}", e))
+        })?;
+
+        let gains = {
+            let mut controller = self.controller.lock().unwrap();
+            let current = controller.gains().ok_or_else(|| {
+                ServiceInvocationError::Unimplemented("Controller has no tunable gains".to_string())
+            })?;
+            let gains = update.apply_to(current);
+            controller.set_gains(gains).map_err(|e| {
+                error!("Rejected gains {:?}: {}", gains, e);
+                ServiceInvocationError::InvalidArgument(e)
+            })?;
+            gains
+        };
+
+        info!(
+            "Gains updated to Kp={}, Ki={}, Kd={}, limits={:?} [source: {}]",
+            gains.kp,
+            gains.ki,
+            gains.kd,
+            gains.output_limits,
+            source
+        );
+        publish_gains(&self.publisher, self.status_ttl_ms, &gains).await;
+
+        Ok(Some(gains_payload(&gains)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use up_rust::communication::{InMemoryRpcClient, RpcClient};
+    use up_rust::{StaticUriProvider, UUri};
+    use sdv_lab_topics as topics;
+    use crate::loopback_transport::LoopbackTransport;
+    use crate::pid_controller::PIDController;
+
+    struct Fixture {
+        transport: Arc<LoopbackTransport>,
+        controller: Arc<Mutex<PIDController>>,
+        server: Arc<StaticUriProvider>,
+        client: InMemoryRpcClient,
+        _service: TuningService<PIDController>,
+    }
+
+    impl Fixture {
+        async fn new() -> Self {
+            let transport = Arc::new(LoopbackTransport::new());
+            let controller = Arc::new(Mutex::new(PIDController::new(0.1, 0.01, 0.0).with_output_limits(-1.0, 1.0)));
+            let server = Arc::new(StaticUriProvider::new(topics::PID_CONTROLLER.authority, topics::UE_ID, topics::UE_VERSION_MAJOR));
+            let service = TuningService::new(controller.clone(), transport.clone(), server.clone(), 1000);
+            service.start().await.unwrap();
+            let client_uri = Arc::new(StaticUriProvider::new("tuning-client", 0x1234, 1));
+            let client = InMemoryRpcClient::new(transport.clone(), client_uri).await.unwrap();
+            Fixture { transport, controller, server, client, _service: service }
+        }
+
+        async fn call(&self, resource_id: u16, request: Option<&str>) -> Result<PidGains, ServiceInvocationError> {
+            let payload = request.map(|request| UPayload::new(request.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON));
+            let response = self
+                .client
+                .invoke_method(
+                    self.server.get_resource_uri(resource_id),
+                    CallOptions::for_rpc_request(1000, None, None, None),
+                    payload,
+                )
+                .await?;
+            Ok(serde_json::from_slice(&response.unwrap().payload()).unwrap())
+        }
+
+        fn published_gains(&self) -> Vec<PidGains> {
+            let status: UUri = self.server.get_resource_uri(RESOURCE_ID_GAINS_STATUS);
+            self.transport
+                .published_to(&status)
+                .iter()
+                .map(|message| serde_json::from_slice(message.payload.as_ref().unwrap()).unwrap())
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_updates_keep_the_other_gains() {
+        let fixture = Fixture::new().await;
+        let initial = fixture.call(RESOURCE_ID_GET_GAINS, None).await.unwrap();
+        assert_eq!(initial, PidGains { kp: 0.1, ki: 0.01, kd: 0.0, output_limits: Some((-1.0, 1.0)) });
+        assert_eq!(fixture.published_gains(), vec![initial]);
+
+        let updated = fixture.call(RESOURCE_ID_SET_GAINS, Some(r#"{"kp": 0.2}"#)).await.unwrap();
+        assert_eq!(updated, PidGains { kp: 0.2, ..initial });
+        assert_eq!(fixture.call(RESOURCE_ID_GET_GAINS, None).await.unwrap(), updated);
+        assert_eq!(fixture.published_gains(), vec![initial, updated]);
+    }
+
+    #[tokio::test]
+    async fn test_null_output_limits_clear_them() {
+        let fixture = Fixture::new().await;
+
+        let updated = fixture.call(RESOURCE_ID_SET_GAINS, Some(r#"{"output_limits": [-0.5, 0.5]}"#)).await.unwrap();
+        assert_eq!(updated.output_limits, Some((-0.5, 0.5)));
+        let updated = fixture.call(RESOURCE_ID_SET_GAINS, Some(r#"{"ki": 0.02}"#)).await.unwrap();
+        assert_eq!(updated.output_limits, Some((-0.5, 0.5)));
+        let updated = fixture.call(RESOURCE_ID_SET_GAINS, Some(r#"{"output_limits": null}"#)).await.unwrap();
+        assert_eq!(updated.output_limits, None);
+        assert_eq!(fixture.controller.lock().unwrap().gains().output_limits, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_requests_are_rejected() {
+        let fixture = Fixture::new().await;
+        let initial = fixture.controller.lock().unwrap().gains();
+
+        for request in [Some(r#"{"kp": -1.0}"#), Some(r#"{"output_limits": [1.0, -1.0]}"#), Some("kp=1"), None] {
+            let result = fixture.call(RESOURCE_ID_SET_GAINS, request).await;
+            assert!(matches!(result, Err(ServiceInvocationError::InvalidArgument(_))), "{:?}: {:?}", request, result);
+        }
+        assert_eq!(fixture.controller.lock().unwrap().gains(), initial);
+        // Only the status published on start
+        assert_eq!(fixture.published_gains().len(), 1);
+
+        let handler = SetGains {
+            controller: fixture.controller.clone(),
+            publisher: Arc::new(SimplePublisher::new(fixture.transport.clone(), fixture.server.clone())),
+            status_ttl_ms: 1000,
+        };
+        let payload = UPayload::new(r#"{"kp": 0.3}"#.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+        let result = handler.handle_request(RESOURCE_ID_SET_GAINS, &UAttributes::default(), Some(payload)).await;
+        assert!(matches!(result, Err(ServiceInvocationError::InvalidArgument(_))));
+        assert_eq!(fixture.controller.lock().unwrap().gains(), initial);
+    }
+
+    #[tokio::test]
+    async fn test_vehicles_of_a_fleet_share_the_transport() {
+        // Like a fleet: one transport, one service per vehicle authority
+        let transport = Arc::new(LoopbackTransport::new());
+        let mut fleet = Vec::new();
+        for vehicle in ["hero", "hero2"] {
+            let controller = Arc::new(Mutex::new(PIDController::new(0.1, 0.01, 0.0)));
+            let authority = topics::vehicle_authority(topics::PID_CONTROLLER.authority, vehicle);
+            let server = Arc::new(StaticUriProvider::new(&authority, topics::UE_ID, topics::UE_VERSION_MAJOR));
+            let service = TuningService::new(controller.clone(), transport.clone(), server.clone(), 1000);
+            service.start().await.unwrap();
+            fleet.push((controller, server, service));
+        }
+        let client_uri = Arc::new(StaticUriProvider::new("tuning-client", 0x1234, 1));
+        let client = InMemoryRpcClient::new(transport.clone(), client_uri).await.unwrap();
+
+        let method = fleet[1].1.get_resource_uri(RESOURCE_ID_SET_GAINS);
+        assert_eq!(method.authority_name, "CruiseControl-hero2");
+        let payload = UPayload::new(r#"{"kp": 0.3}"#.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+        client
+            .invoke_method(method, CallOptions::for_rpc_request(1000, None, None, None), Some(payload))
+            .await
+            .unwrap();
+
+        assert_eq!(fleet[0].0.lock().unwrap().gains().kp, 0.1);
+        assert_eq!(fleet[1].0.lock().unwrap().gains().kp, 0.3);
+        let status = fleet[1].1.get_resource_uri(RESOURCE_ID_GAINS_STATUS);
+        assert_eq!(transport.published_to(&status).len(), 2);
+    }
+}
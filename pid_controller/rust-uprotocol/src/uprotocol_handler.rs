@@ -14,626 +14,537 @@
 // limitations under the License.
 //
 
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
-use serde::{Deserialize, Serialize};
-use serde_json;
-use log::{info, debug, error};
-use up_rust::{UUri, UListener, UMessage, UMessageBuilder, UTransport, UPayloadFormat};
-use up_transport_zenoh::UPTransportZenoh;
-
-use crate::pid_controller::PIDController;
-
-#[derive(Debug, Serialize, Deserialize)]
-struct VelocityStatus {
-    velocity: f64,
+use std::time::Instant;
+use log::{info, error, warn};
+use serde::de::DeserializeOwned;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+use up_rust::{LocalUriProvider, UStatus, UUri, UUriError, UListener, UMessage, UPayloadFormat, UTransport};
+use up_rust::communication::{CallOptions, Publisher, SimplePublisher, UPayload};
+use cruise_control_messages::{
+    ClockStatus, CruiseControlPayload, Encoding, EngageStatus, TargetSpeed, VelocityStatus,
+};
+use sdv_lab_topics as topics;
+
+use crate::acc::AccConfig;
+use crate::control_loop::{self, ControlInput, ControlLoop, ControllerState, LoopConfig, LoopMode, VelocityInput};
+use crate::health::{HealthConfig, HealthStatus, RESOURCE_ID_HEALTH};
+use crate::longitudinal_controller::LongitudinalController;
+use crate::recorder::{Recorder, RecorderThread};
+use crate::time_base::TimeBaseConfig;
+use crate::watchdog::{message_ttl, Freshness, WatchdogConfig};
+
+// Inputs queued for the control task before the listeners wait for it
+const INPUT_QUEUE: usize = 64;
+
+/// URIs of the topics the handler subscribes and publishes to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicUris {
+    pub velocity: UUri,
+    pub clock: UUri,
+    pub target_speed: UUri,
+    pub engage: UUri,
+    pub actuation: UUri,
+    pub fault: UUri,
+    pub acc_status: UUri,
+    pub obstacle: UUri,
+    pub radar: UUri,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ClockStatus {
-    time: f64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct TargetSpeed {
-    speed: f64,
+impl Default for TopicUris {
+    fn default() -> Self {
+        TopicUris {
+            velocity: topics::VELOCITY_STATUS.uri(),
+            clock: topics::CLOCK_STATUS.uri(),
+            target_speed: topics::TARGET_SPEED.uri(),
+            engage: topics::ENGAGE.uri(),
+            actuation: topics::ACTUATION.uri(),
+            fault: topics::FAULT.uri(),
+            acc_status: topics::ACC_STATUS.uri(),
+            obstacle: topics::OBSTACLE_DETECTION.uri(),
+            radar: topics::RADAR.uri(),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct EngageStatus {
-    engaged: u8,
+impl TopicUris {
+    /// The topics of the vehicle `vehicle_id` of a fleet: every authority gets the vehicle
+    /// suffix, e.g. `//EGOVehicle-hero2/0/2/8001` for the velocity of `hero2`.
+    pub fn for_vehicle(&self, vehicle_id: &str) -> Result<TopicUris, UUriError> {
+        let derive = |uri: &UUri| {
+            UUri::try_from_parts(
+                &topics::vehicle_authority(&uri.authority_name, vehicle_id),
+                uri.ue_id,
+                uri.uentity_major_version(),
+                uri.resource_id(),
+            )
+        };
+        Ok(TopicUris {
+            velocity: derive(&self.velocity)?,
+            clock: derive(&self.clock)?,
+            target_speed: derive(&self.target_speed)?,
+            engage: derive(&self.engage)?,
+            actuation: derive(&self.actuation)?,
+            fault: derive(&self.fault)?,
+            acc_status: derive(&self.acc_status)?,
+            obstacle: derive(&self.obstacle)?,
+            radar: derive(&self.radar)?,
+        })
+    }
 }
 
-pub struct UProtocolHandler {
-    controller: Arc<Mutex<PIDController>>,
-    transport: Arc<UPTransportZenoh>,
-    
-    // uProtocol URIs
-    velocity_uri: UUri,
-    clock_uri: UUri,
-    engage_uri: UUri,
-    target_speed_uri: UUri,
-    actuation_uri: UUri,
+pub struct UProtocolHandler<C: LongitudinalController> {
+    controller: Arc<Mutex<C>>,
+    transport: Arc<dyn UTransport>,
     
-    // State variables
-    current_velocity: Arc<Mutex<f64>>,
-    desired_velocity: Arc<Mutex<f64>>,
-    current_time: Arc<Mutex<f64>>,
-    previous_time: Arc<Mutex<f64>>,
-    is_engaged: Arc<Mutex<u8>>,
-    pid_active: Arc<Mutex<bool>>,
+    // uProtocol URIs, derived for the vehicle of a fleet if one is set
+    topics: TopicUris,
+    vehicle: Option<String>,
+
+    // Control task settings, and the controller health published periodically if configured
+    loop_config: LoopConfig,
+    health_publisher: Option<(HealthConfig, Arc<dyn LocalUriProvider>)>,
     
-    // Results storage
-    results: Arc<Mutex<HashMap<String, Vec<f64>>>>,
+    // Results storage, fed by the writer thread while the control task runs
+    recorder: Arc<Mutex<Recorder>>,
+    recorder_thread: Mutex<Option<RecorderThread>>,
+
+    // The listeners forward decoded inputs to the control task, which owns the controller
+    // state and publishes it on the watch channel after every change
+    inputs: mpsc::Sender<ControlInput>,
+    pending: Mutex<Option<(mpsc::Receiver<ControlInput>, watch::Sender<ControllerState>)>>,
+    state: watch::Receiver<ControllerState>,
+    task: Mutex<Option<JoinHandle<()>>>,
+
+    // Registered listeners, unregistered on stop, and the token stopping the background tasks
+    listeners: Mutex<Vec<(UUri, Arc<dyn UListener>)>>,
+    shutdown: CancellationToken,
 }
 
-impl UProtocolHandler {
+impl<C: LongitudinalController> UProtocolHandler<C> {
     pub fn new(
-        controller: PIDController,
-        transport: UPTransportZenoh,
+        controller: C,
+        transport: Arc<dyn UTransport>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut results = HashMap::new();
-        results.insert("desired_velocity".to_string(), Vec::new());
-        results.insert("current_velocity".to_string(), Vec::new());
-        results.insert("current_time".to_string(), Vec::new());
-        results.insert("acceleration".to_string(), Vec::new());
-
-        // Create URIs for different services
-        let velocity_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8001)?;
-        let clock_uri = UUri::try_from_parts("EGOVehicle", 0, 2, 0x8002)?;
-        let engage_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8002)?;
-        let target_speed_uri = UUri::try_from_parts("AAOS", 0, 2, 0x8001)?;
-        let actuation_uri = UUri::try_from_parts("CruiseControl", 0, 2, 0x8001)?;
+        let (inputs, receiver) = mpsc::channel(INPUT_QUEUE);
+        let (state_tx, state) = watch::channel(ControllerState::new(Freshness::new(&WatchdogConfig::default())));
 
         Ok(UProtocolHandler {
             controller: Arc::new(Mutex::new(controller)),
-            transport: Arc::new(transport),
-            velocity_uri,
-            clock_uri,
-            engage_uri,
-            target_speed_uri,
-            actuation_uri,
-            current_velocity: Arc::new(Mutex::new(0.0)),
-            desired_velocity: Arc::new(Mutex::new(0.0)),
-            current_time: Arc::new(Mutex::new(0.0)),
-            previous_time: Arc::new(Mutex::new(0.0)),
-            is_engaged: Arc::new(Mutex::new(0)),
-            pid_active: Arc::new(Mutex::new(false)),
-            results: Arc::new(Mutex::new(results)),
+            transport,
+            topics: TopicUris::default(),
+            vehicle: None,
+            loop_config: LoopConfig::default(),
+            health_publisher: None,
+            recorder: Arc::new(Mutex::new(Recorder::summary_only())),
+            recorder_thread: Mutex::new(None),
+            inputs,
+            pending: Mutex::new(Some((receiver, state_tx))),
+            state,
+            task: Mutex::new(None),
+            listeners: Mutex::new(Vec::new()),
+            shutdown: CancellationToken::new(),
         })
     }
 
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Starting UProtocolHandler subscribers...");
+    /// Subscribes and publishes on `topics` instead of the SDV lab topic map.
+    pub fn with_topics(mut self, topics: TopicUris) -> Self {
+        self.topics = topics;
+        self
+    }
 
-        // Register listeners for each subscription
-        self.setup_clock_subscriber().await?;
-        self.setup_velocity_subscriber().await?;
-        self.setup_target_subscriber().await?;
-        self.setup_engage_subscriber().await?;
+    /// Controls the vehicle `vehicle_id` of a fleet: subscribes and publishes on the topics
+    /// derived with `TopicUris::for_vehicle` and prefixes the log messages with the id.
+    pub fn with_vehicle(mut self, vehicle_id: &str) -> Self {
+        self.vehicle = Some(vehicle_id.to_string());
+        self
+    }
 
-        Ok(())
+    /// Streams every control sample to `recorder` instead of only keeping the summary.
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Arc::new(Mutex::new(recorder));
+        self
     }
-    
-    async fn setup_clock_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let current_time_arc = Arc::clone(&self.current_time);
-        let transport = Arc::clone(&self.transport);
-        let clock_uri = self.clock_uri.clone();
-        
-        let listener = ClockListener::new(current_time_arc);
-        transport.register_listener(&clock_uri, None, Arc::new(listener)).await?;
-        
-        info!("Timestamp subscriber registered");
-        Ok(())
+
+    /// Publishes the actuation command with `encoding`, e.g. `Encoding::Text` for subscribers
+    /// that predate the protobuf payloads.
+    pub fn with_actuation_encoding(mut self, encoding: Encoding) -> Self {
+        self.loop_config.actuation_encoding = encoding;
+        self
     }
-    
-    async fn setup_velocity_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let current_velocity = Arc::clone(&self.current_velocity);
-        let transport = Arc::clone(&self.transport);
-        let velocity_uri = self.velocity_uri.clone();
-        
-        // Clone all necessary data for publish_acc
-        let desired_velocity = Arc::clone(&self.desired_velocity);
-        let current_time = Arc::clone(&self.current_time);
-        let previous_time = Arc::clone(&self.previous_time);
-        let pid_active = Arc::clone(&self.pid_active);
-        let controller = Arc::clone(&self.controller);
-        let results = Arc::clone(&self.results);
-        let actuation_uri = self.actuation_uri.clone();
-        let transport_for_publish = Arc::clone(&self.transport);
-        
-        let listener = VelocityListener::new(
-            current_velocity,
-            desired_velocity,
-            current_time,
-            previous_time,
-            pid_active,
-            controller,
-            results,
-            actuation_uri,
-            transport_for_publish,
-        );
-        
-        transport.register_listener(&velocity_uri, None, Arc::new(listener)).await?;
-        
-        info!("Velocity subscriber registered");
-        Ok(())
+
+    /// Takes the controller time from `config.source` instead of the simulation clock topic,
+    /// and treats clock steps longer than `config.max_step` as pauses.
+    pub fn with_time_base(mut self, config: TimeBaseConfig) -> Self {
+        self.loop_config.time_base = config;
+        self
     }
 
-    async fn setup_target_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let desired_velocity = Arc::clone(&self.desired_velocity);
-        let transport = Arc::clone(&self.transport);
-        let target_speed_uri = self.target_speed_uri.clone();
-        
-        let listener = TargetSpeedListener::new(desired_velocity);
-        transport.register_listener(&target_speed_uri, None, Arc::new(listener)).await?;
-        
-        info!("Target Speed subscriber registered");
-        Ok(())
+    /// Computes a command every period of `LoopMode::FixedRate` from the latest inputs,
+    /// instead of on every velocity sample. Use it with the wall clock time base: the
+    /// simulation clock only advances on clock ticks.
+    pub fn with_loop_mode(mut self, mode: LoopMode) -> Self {
+        self.loop_config.mode = mode;
+        self
     }
-    
-    async fn setup_engage_subscriber(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let is_engaged = Arc::clone(&self.is_engaged);
-        let pid_active = Arc::clone(&self.pid_active);
-        let controller = Arc::clone(&self.controller);
-        let transport = Arc::clone(&self.transport);
-        let engage_uri = self.engage_uri.clone();
-        
-        let listener = EngageListener::new(is_engaged, pid_active, controller);
-        transport.register_listener(&engage_uri, None, Arc::new(listener)).await?;
-        
-        info!("Engage subscriber registered");
-        Ok(())
+
+    /// Feeds the fixed-rate loop with `input` between two velocity samples.
+    pub fn with_velocity_input(mut self, input: VelocityInput) -> Self {
+        self.loop_config.velocity_input = input;
+        self
     }
 
-    // Static method for PID computation and publishing
-    async fn publish_acc(
-        desired_velocity: &Arc<Mutex<f64>>,
-        current_velocity: &Arc<Mutex<f64>>,
-        current_time: &Arc<Mutex<f64>>,
-        previous_time: &Arc<Mutex<f64>>,
-        pid_active: &Arc<Mutex<bool>>,
-        controller: &Arc<Mutex<PIDController>>,
-        transport: &Arc<UPTransportZenoh>,
-        actuation_uri: UUri,
-        results: &Arc<Mutex<HashMap<String, Vec<f64>>>>,
-    ) {
-        // Check if PID is active
-        let is_active = {
-            let active = pid_active.lock().unwrap();
-            *active
-        };
-        
-        if !is_active {
-            return;
-        }
+    /// Watches the freshness of the velocity and clock inputs: when one goes stale while
+    /// engaged, the controller publishes `config.safe_command`, disengages and publishes a
+    /// fault event. Engaging is refused while an input is stale.
+    pub fn with_watchdog(mut self, config: WatchdogConfig) -> Self {
+        self.loop_config.watchdog = Some(config);
+        self
+    }
 
-        let (desired_vel, current_vel, curr_time) = {
-            let desired = desired_velocity.lock().unwrap();
-            let current = current_velocity.lock().unwrap();
-            let time = current_time.lock().unwrap();
-            (*desired, *current, *time)
-        };
+    /// Follows a slower lead vehicle seen by the obstacle detector or the radar, keeping
+    /// `config.time_gap`, and publishes the active mode on the ACC status topic.
+    pub fn with_acc(mut self, config: AccConfig) -> Self {
+        self.loop_config.acc = Some(config);
+        self
+    }
 
-        // Compute acceleration using PID controller
-        let acceleration = {
-            let mut pid = controller.lock().unwrap();
-            match pid.compute(desired_vel, current_vel, curr_time) {
-                Ok(acc) => acc,
-                Err(e) => {
-                    error!("PID computation failed: {}", e);
-                    return;
-                }
-            }
-        };
+    /// Publishes the controller health every `config.interval` from the entity of
+    /// `uri_provider`, e.g. for the AAOS cluster and monitoring tools.
+    pub fn with_health(mut self, config: HealthConfig, uri_provider: Arc<dyn LocalUriProvider>) -> Self {
+        self.health_publisher = Some((config, uri_provider));
+        self
+    }
 
-        // Create and publish uProtocol message
-        let actuation_cmd_payload = format!("{}", acceleration);
-        let message = UMessageBuilder::publish(actuation_uri)
-            .build_with_payload(actuation_cmd_payload.clone(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
-            .unwrap();
-        
-        if let Err(e) = transport.send(message).await {
-            error!("Failed to publish acceleration: {}", e);
-        } else {
-            debug!("Publishing Acceleration: {}", actuation_cmd_payload);
+    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let topics = self.topics()?;
+        match &self.vehicle {
+            Some(vehicle) => info!("Starting UProtocolHandler subscribers of vehicle {}...", vehicle),
+            None => info!("Starting UProtocolHandler subscribers..."),
         }
 
-        // Store results for later analysis
-        {
-            let mut results_guard = results.lock().unwrap();
-            results_guard.get_mut("desired_velocity").unwrap().push(desired_vel);
-            results_guard.get_mut("current_velocity").unwrap().push(current_vel);
-            results_guard.get_mut("current_time").unwrap().push(curr_time);
-            results_guard.get_mut("acceleration").unwrap().push(acceleration);
+        // The control task runs first, so that no input is queued without a consumer
+        self.setup_control_loop(&topics)?;
+
+        // Register listeners for each subscription
+        self.subscribe(Subscription::Clock, &topics.clock).await?;
+        info!("Timestamp subscriber registered");
+        self.subscribe(Subscription::Velocity, &topics.velocity).await?;
+        info!("Velocity subscriber registered");
+        self.subscribe(Subscription::TargetSpeed, &topics.target_speed).await?;
+        info!("Target Speed subscriber registered");
+        self.subscribe(Subscription::Engage, &topics.engage).await?;
+        info!("Engage subscriber registered");
+        if let Some(config) = self.loop_config.acc {
+            self.subscribe(Subscription::Obstacle, &topics.obstacle).await?;
+            self.subscribe(Subscription::Radar, &topics.radar).await?;
+            info!(
+                "Obstacle and radar subscribers registered [time gap: {} s, standstill distance: {} m]",
+                config.time_gap, config.standstill_distance
+            );
         }
 
-        // Calculate and log delta time
-        let (_prev_time, delta_time) = {
-            let mut prev = previous_time.lock().unwrap();
-            let delta = if *prev > 0.0 { curr_time - *prev } else { 0.0 };
-            *prev = curr_time;
-            (*prev, delta)
-        };
-        
-        if delta_time > 0.0 {
-            debug!("Delta time: {} seconds", delta_time);
+        if let Some((config, uri_provider)) = &self.health_publisher {
+            self.setup_health_publisher(*config, Arc::clone(uri_provider));
         }
+
+        Ok(())
     }
 
-    // Activation method
-    fn activate_pid(
-        pid_active: &Arc<Mutex<bool>>,
-        controller: &Arc<Mutex<PIDController>>,
-    ) {
-        {
-            let mut active = pid_active.lock().unwrap();
-            *active = true;
+    /// Stops the controller: unregisters the listeners, stops the control task, which
+    /// disengages and publishes a zero actuation command so that the vehicle does not keep the
+    /// last one, stops the health publisher and flushes the recorded results.
+    pub async fn stop(&self) {
+        let listeners: Vec<(UUri, Arc<dyn UListener>)> = self.listeners.lock().unwrap().drain(..).collect();
+        for (uri, listener) in listeners {
+            if let Err(e) = self.transport.unregister_listener(&uri, None, listener).await {
+                warn!("Failed to unregister listener of {}: {}", String::from(&uri), e);
+            }
         }
-        {
-            let mut pid = controller.lock().unwrap();
-            pid.reset();
+
+        self.shutdown.cancel();
+        let task = self.task.lock().unwrap().take();
+        match task {
+            Some(task) => {
+                if let Err(e) = task.await {
+                    error!("Control loop failed: {}", e);
+                }
+            }
+            None => {
+                // Never started: there is no task to publish the zero command
+                if let Ok(topics) = self.topics() {
+                    control_loop::publish_actuation(
+                        &self.transport,
+                        topics.actuation,
+                        self.loop_config.actuation_encoding,
+                        0.0,
+                    )
+                    .await;
+                }
+            }
         }
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        info!("[INFO] PID controller ACTIVATED at {}", timestamp);
+        info!("Controller stopped, zero actuation command published");
+
+        self.store_results();
     }
 
-    // Deactivation method
-    fn deactivate_pid(
-        pid_active: &Arc<Mutex<bool>>,
-        controller: &Arc<Mutex<PIDController>>,
-    ) {
-        {
-            let mut active = pid_active.lock().unwrap();
-            *active = false;
+    /// Waits until the control task processed every input received so far, e.g. in tests.
+    pub async fn sync(&self) {
+        let (reply, done) = oneshot::channel();
+        if self.task.lock().unwrap().is_none() || self.inputs.send(ControlInput::Sync(reply)).await.is_err() {
+            return;
         }
-        {
-            let mut pid = controller.lock().unwrap();
-            pid.reset();
+        let _ = done.await;
+    }
+
+    /// Observes the controller state, updated by the control task after every input.
+    pub fn watch_state(&self) -> watch::Receiver<ControllerState> {
+        self.state.clone()
+    }
+
+    async fn register(&self, uri: &UUri, listener: Arc<dyn UListener>) -> Result<(), UStatus> {
+        self.transport.register_listener(uri, None, Arc::clone(&listener)).await?;
+        self.listeners.lock().unwrap().push((uri.clone(), listener));
+        Ok(())
+    }
+
+    async fn subscribe(&self, subscription: Subscription, uri: &UUri) -> Result<(), UStatus> {
+        let listener = InputListener {
+            subscription,
+            inputs: self.inputs.clone(),
+        };
+        self.register(uri, Arc::new(listener)).await
+    }
+
+    fn topics(&self) -> Result<TopicUris, UUriError> {
+        match &self.vehicle {
+            Some(vehicle) => self.topics.for_vehicle(vehicle),
+            None => Ok(self.topics.clone()),
         }
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        info!("[INFO] PID controller DEACTIVATED at {}", timestamp);
     }
-    
-    pub fn store_results(&self) {
-        let results = self.results.lock().unwrap();
-        
-        // Create logs directory if it doesn't exist
-        if let Err(e) = std::fs::create_dir_all("logs") {
-            error!("Failed to create logs directory: {}", e);
-            return;
+
+    fn setup_control_loop(&self, topics: &TopicUris) -> Result<(), Box<dyn std::error::Error>> {
+        let Some((inputs, state_tx)) = self.pending.lock().unwrap().take() else {
+            return Err("UProtocolHandler already started".into());
+        };
+        let recorder_thread = RecorderThread::spawn(Arc::clone(&self.recorder));
+        let mut control = ControlLoop::new(
+            Arc::clone(&self.controller),
+            Arc::clone(&self.transport),
+            topics.clone(),
+            self.loop_config,
+            recorder_thread.sender(),
+            state_tx,
+        );
+        if let Some(vehicle) = &self.vehicle {
+            control = control.with_vehicle(vehicle);
         }
-        
-        // Store each result type in separate files
-        for (key, values) in results.iter() {
-            let filename = format!("logs/{}.log", key);
-            let content = values.iter()
-                .map(|v| v.to_string())
-                .collect::<Vec<String>>()
-                .join("\n");
-            
-            if let Err(e) = std::fs::write(&filename, content) {
-                error!("Failed to write {}: {}", filename, e);
-            } else {
-                info!("Results saved to {}", filename);
+        let task = tokio::spawn(control.run(inputs, self.shutdown.clone()));
+        *self.task.lock().unwrap() = Some(task);
+        *self.recorder_thread.lock().unwrap() = Some(recorder_thread);
+        Ok(())
+    }
+
+    fn setup_health_publisher(&self, config: HealthConfig, uri_provider: Arc<dyn LocalUriProvider>) {
+        let publisher = SimplePublisher::new(Arc::clone(&self.transport), uri_provider);
+        let state = self.state.clone();
+        let shutdown = self.shutdown.clone();
+        let started = Instant::now();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(config.interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            let mut sequence = 0;
+
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                sequence += 1;
+                let status = HealthStatus::from_state(&state.borrow(), sequence, started, Instant::now(), control_loop::wall_time());
+
+                let payload = UPayload::new(serde_json::to_vec(&status).unwrap(), UPayloadFormat::UPAYLOAD_FORMAT_JSON);
+                if let Err(e) = publisher
+                    .publish(RESOURCE_ID_HEALTH, CallOptions::for_publish(Some(config.ttl_ms), None, None), Some(payload))
+                    .await
+                {
+                    error!("Failed to publish health status: {}", e);
+                }
             }
-        }
+        });
+
+        info!(
+            "Health publisher started [resource: 0x{:04X}, interval: {} ms, TTL: {} ms]",
+            RESOURCE_ID_HEALTH,
+            config.interval.as_millis(),
+            config.ttl_ms
+        );
+    }
 
-        // Also save as JSON for compatibility
-        if let Ok(json) = serde_json::to_string(&*results) {
-            std::fs::write("logs/pid_results.json", json).unwrap_or_else(|e| {
-                error!("Failed to write JSON results: {}", e);
-            });
+    fn store_results(&self) {
+        // The control task is done, the thread writes what it queued and exits
+        if let Some(recorder_thread) = self.recorder_thread.lock().unwrap().take() {
+            recorder_thread.join();
+        }
+        if let Err(e) = self.recorder.lock().unwrap().close() {
+            error!("Failed to store results: {}", e);
         }
     }
     
+    // Files written by the recorder, e.g. for the metrics report
+    pub fn recorded_files(&self) -> Vec<PathBuf> {
+        self.recorder.lock().unwrap().files().to_vec()
+    }
+
     pub fn show_results(&self) {
-        let results = self.results.lock().unwrap();
+        let (summary, dropped_rows) = {
+            let recorder = self.recorder.lock().unwrap();
+            (recorder.summary(), recorder.dropped_rows())
+        };
         
-        info!("PID Controller Results Summary:");
+        match &self.vehicle {
+            Some(vehicle) => info!("PID Controller Results Summary of vehicle {}:", vehicle),
+            None => info!("PID Controller Results Summary:"),
+        }
+        info!("Total data points: {}", summary.data_points);
         
-        if let (Some(desired), Some(current), Some(acceleration)) = (
-            results.get("desired_velocity"),
-            results.get("current_velocity"), 
-            results.get("acceleration")
-        ) {
-            let data_points = desired.len().min(current.len()).min(acceleration.len());
-            info!("Total data points: {}", data_points);
+        if summary.data_points > 0 {
+            let avg_error = summary.sum_error / summary.data_points as f64;
+            let avg_acc = summary.sum_acceleration / summary.data_points as f64;
             
-            if data_points > 0 {
-                let mut min_error = f64::MAX;
-                let mut max_error = f64::MIN;
-                let mut sum_error = 0.0;
-                
-                for i in 0..data_points {
-                    let error = desired[i] - current[i];
-                    min_error = min_error.min(error);
-                    max_error = max_error.max(error);
-                    sum_error += error;
-                }
-                
-                let avg_error = sum_error / data_points as f64;
-                
-                info!("Min error: {:.4}", min_error);
-                info!("Max error: {:.4}", max_error);
-                info!("Avg error: {:.4}", avg_error);
-                
-                if let Some(acc_values) = results.get("acceleration") {
-                    if !acc_values.is_empty() {
-                        let min_acc = acc_values.iter().fold(f64::INFINITY, |a, &b| a.min(b));
-                        let max_acc = acc_values.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
-                        let avg_acc = acc_values.iter().sum::<f64>() / acc_values.len() as f64;
-                        
-                        info!("Acceleration - Min: {:.4}, Max: {:.4}, Avg: {:.4}", min_acc, max_acc, avg_acc);
-                    }
-                }
-            }
+            info!("Min error: {:.4}", summary.min_error);
+            info!("Max error: {:.4}", summary.max_error);
+            info!("Avg error: {:.4}", avg_error);
+            info!("Acceleration - Min: {:.4}, Max: {:.4}, Avg: {:.4}", summary.min_acceleration, summary.max_acceleration, avg_acc);
         } else {
             info!("No data points available");
         }
+        if dropped_rows > 0 {
+            warn!("Recorder dropped {} rows that could not be written", dropped_rows);
+        }
+
+        if let Some(timing) = self.state.borrow().timing {
+            info!(
+                "Control loop - Period: {:.1} ms, Steps: {}, Missed deadlines: {}, Jitter avg: {:.3} ms, max: {:.3} ms, Longest step: {:.3} ms",
+                timing.period * 1000.0,
+                timing.steps,
+                timing.missed_deadlines,
+                timing.mean_jitter * 1000.0,
+                timing.max_jitter * 1000.0,
+                timing.max_step_duration * 1000.0
+            );
+        }
+    }
+
+    // Shared handle on the controller, e.g. for the tuning service
+    pub fn controller(&self) -> Arc<Mutex<C>> {
+        Arc::clone(&self.controller)
     }
 
     // Additional helper method to get current PID status
     #[allow(dead_code)]    
     pub fn is_active(&self) -> bool {
-        let active = self.pid_active.lock().unwrap();
-        *active
+        self.state.borrow().engaged
     }
 
     // Get current state for debugging
     #[allow(dead_code)]    
     pub fn get_state(&self) -> (f64, f64, f64, bool) {
-        let current_vel = *self.current_velocity.lock().unwrap();
-        let desired_vel = *self.desired_velocity.lock().unwrap();
-        let current_time = *self.current_time.lock().unwrap();
-        let is_active = *self.pid_active.lock().unwrap();
-        
-        (current_vel, desired_vel, current_time, is_active)
+        let state = self.state.borrow();
+        (state.current_velocity, state.desired_velocity, state.clock_time, state.engaged)
     }
 }
 
-// Listener implementations
-struct ClockListener {
-    current_time: Arc<Mutex<f64>>,
+#[derive(Clone, Copy)]
+enum Subscription {
+    Clock,
+    Velocity,
+    TargetSpeed,
+    Engage,
+    Obstacle,
+    Radar,
 }
 
-impl ClockListener {
-    fn new(current_time: Arc<Mutex<f64>>) -> Self {
-        Self { current_time }
-    }
+// Decodes the messages of one subscription and forwards them to the control task
+struct InputListener {
+    subscription: Subscription,
+    inputs: mpsc::Sender<ControlInput>,
 }
 
-#[async_trait::async_trait]
-impl UListener for ClockListener {
-    async fn on_receive(&self, message: UMessage) {
-        if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            // Try to parse as text first (new format)
-            let time_value = if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<f64>() {
-                    Ok(time) => time,
-                    Err(_) => {
-                        // Fall back to JSON format for backward compatibility
-                        if let Ok(clock_status) = serde_json::from_slice::<ClockStatus>(&bytes) {
-                            clock_status.time
-                        } else {
-                            error!("[ERROR] Timestamp processing failed as JSON");
-                            return;
-                        }
-                    }
+impl InputListener {
+    fn decode(&self, message: &UMessage) -> Option<ControlInput> {
+        let received_at = Instant::now();
+        let ttl = message_ttl(message);
+
+        match self.subscription {
+            Subscription::Clock => match ClockStatus::decode_message(message) {
+                Ok(clock_status) => Some(ControlInput::Clock {
+                    elapsed_seconds: clock_status.elapsed_seconds,
+                    received_at,
+                    ttl,
+                }),
+                Err(e) => {
+                    error!("Timestamp processing failed: {}", e);
+                    None
                 }
-            } else {
-                error!("[ERROR] Timestamp processing failed as UTF-8");
-                return;
-            };
-            
-            {
-                let mut clock = self.current_time.lock().unwrap();
-                *clock = time_value;
-            }
-            debug!("Received current clock '{:.4}' seconds", time_value);
-        }
-    }
-}
-
-struct VelocityListener {
-    current_velocity: Arc<Mutex<f64>>,
-    desired_velocity: Arc<Mutex<f64>>,
-    current_time: Arc<Mutex<f64>>,
-    previous_time: Arc<Mutex<f64>>,
-    pid_active: Arc<Mutex<bool>>,
-    controller: Arc<Mutex<PIDController>>,
-    results: Arc<Mutex<HashMap<String, Vec<f64>>>>,
-    actuation_uri: UUri,
-    transport: Arc<UPTransportZenoh>,
-}
-
-impl VelocityListener {
-    fn new(
-        current_velocity: Arc<Mutex<f64>>,
-        desired_velocity: Arc<Mutex<f64>>,
-        current_time: Arc<Mutex<f64>>,
-        previous_time: Arc<Mutex<f64>>,
-        pid_active: Arc<Mutex<bool>>,
-        controller: Arc<Mutex<PIDController>>,
-        results: Arc<Mutex<HashMap<String, Vec<f64>>>>,
-        actuation_uri: UUri,
-        transport: Arc<UPTransportZenoh>,
-    ) -> Self {
-        Self {
-            current_velocity,
-            desired_velocity,
-            current_time,
-            previous_time,
-            pid_active,
-            controller,
-            results,
-            actuation_uri,
-            transport,
-        }
-    }
-}
-
-#[async_trait::async_trait]
-impl UListener for VelocityListener {
-    async fn on_receive(&self, message: UMessage) {
-        if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            // Try to parse as text first (new format)
-            let velocity_value = if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<f64>() {
-                    Ok(velocity) => velocity,
-                    Err(_) => {
-                        // Fall back to JSON format for backward compatibility
-                        if let Ok(velocity_status) = serde_json::from_slice::<VelocityStatus>(&bytes) {
-                            velocity_status.velocity
-                        } else {
-                            error!("Failed to parse velocity payload");
-                            return;
-                        }
-                    }
+            },
+            Subscription::Velocity => match VelocityStatus::decode_message(message) {
+                Ok(velocity_status) => Some(ControlInput::Velocity {
+                    velocity_kmh: velocity_status.velocity_kmh,
+                    received_at,
+                    ttl,
+                }),
+                Err(e) => {
+                    error!("Failed to parse velocity payload: {}", e);
+                    None
                 }
-            } else {
-                error!("Failed to parse velocity payload as UTF-8");
-                return;
-            };
-            
-            {
-                let mut vel = self.current_velocity.lock().unwrap();
-                *vel = velocity_value;
-            }
-            debug!("Received current velocity '{:.2}'", velocity_value);
-            
-            // Trigger PID computation
-            UProtocolHandler::publish_acc(
-                &self.desired_velocity,
-                &self.current_velocity,
-                &self.current_time,
-                &self.previous_time,
-                &self.pid_active,
-                &self.controller,
-                &self.transport,
-                self.actuation_uri.clone(),
-                &self.results,
-            ).await;
-        }
-    }
-}
-
-struct TargetSpeedListener {
-    desired_velocity: Arc<Mutex<f64>>,
-}
-
-impl TargetSpeedListener {
-    fn new(desired_velocity: Arc<Mutex<f64>>) -> Self {
-        Self { desired_velocity }
-    }
-}
-
-#[async_trait::async_trait]
-impl UListener for TargetSpeedListener {
-    async fn on_receive(&self, message: UMessage) {
-        if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            let speed_value = if let Ok(target_speed) = serde_json::from_slice::<TargetSpeed>(&bytes) {
-                target_speed.speed
-            } else if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<f64>() {
-                    Ok(speed) => speed,
-                    Err(_) => {
-                        error!("Failed to parse target speed: {}", payload_str);
-                        return;
-                    }
+            },
+            Subscription::TargetSpeed => match TargetSpeed::decode_message(message) {
+                Ok(target_speed) => Some(ControlInput::TargetSpeed(target_speed.speed_kmh)),
+                Err(e) => {
+                    error!("Failed to parse target speed: {}", e);
+                    None
                 }
-            } else {
-                error!("Failed to parse target speed payload");
-                return;
-            };
-            
-            {
-                let mut vel = self.desired_velocity.lock().unwrap();
-                *vel = speed_value;
-            }
-            info!("Received desired velocity '{:.2}'", speed_value);
+            },
+            Subscription::Engage => match EngageStatus::decode_message(message) {
+                Ok(engage_status) => Some(ControlInput::Engage(engage_status.engaged)),
+                Err(e) => {
+                    error!("Failed to parse engage status payload: {}", e);
+                    None
+                }
+            },
+            Subscription::Obstacle => decode_sensor(message, "obstacle").map(ControlInput::Obstacle),
+            Subscription::Radar => decode_sensor(message, "radar").map(ControlInput::Radar),
         }
     }
 }
 
-struct EngageListener {
-    is_engaged: Arc<Mutex<u8>>,
-    pid_active: Arc<Mutex<bool>>,
-    controller: Arc<Mutex<PIDController>>,
-}
-
-impl EngageListener {
-    fn new(
-        is_engaged: Arc<Mutex<u8>>,
-        pid_active: Arc<Mutex<bool>>,
-        controller: Arc<Mutex<PIDController>>,
-    ) -> Self {
-        Self {
-            is_engaged,
-            pid_active,
-            controller,
+// Obstacle events and radar measurements are JSON payloads
+fn decode_sensor<T: DeserializeOwned>(message: &UMessage, sensor: &str) -> Option<T> {
+    let Some(payload) = message.payload.as_ref() else {
+        error!("Received sensor message without payload");
+        return None;
+    };
+    match serde_json::from_slice(payload) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            error!("Failed to parse {} payload: {}", sensor, e);
+            None
         }
     }
 }
 
 #[async_trait::async_trait]
-impl UListener for EngageListener {
+impl UListener for InputListener {
     async fn on_receive(&self, message: UMessage) {
-        if let Some(payload) = message.payload {
-            let bytes = &payload[..];
-            
-            // Try to parse as text first (new format)
-            let engaged_value = if let Ok(payload_str) = std::str::from_utf8(&bytes) {
-                match payload_str.trim().parse::<u8>() {
-                    Ok(engaged) => engaged,
-                    Err(_) => {
-                        // Fall back to JSON format for backward compatibility
-                        if let Ok(engage_status) = serde_json::from_slice::<EngageStatus>(&bytes) {
-                            engage_status.engaged
-                        } else {
-                            error!("Failed to parse engage status payload");
-                            return;
-                        }
-                    }
-                }
-            } else {
-                error!("Failed to parse engage status payload as UTF-8");
-                return;
-            };
-            
-            let _was_engaged;
-            {
-                let mut engaged_state = self.is_engaged.lock().unwrap();
-                _was_engaged = *engaged_state;
-                *engaged_state = engaged_value;
-            }
-            
-            info!("Received engage status: {}", engaged_value);
-            
-            // Handle activation/deactivation
-            let enable = engaged_value != 0;
-            let was_active = {
-                let active = self.pid_active.lock().unwrap();
-                *active
-            };
-            
-            if enable && !was_active {
-                UProtocolHandler::activate_pid(&self.pid_active, &self.controller);
-            } else if !enable && was_active {
-                UProtocolHandler::deactivate_pid(&self.pid_active, &self.controller);
+        if let Some(input) = self.decode(&message) {
+            if self.inputs.send(input).await.is_err() {
+                warn!("Control loop stopped, input dropped");
             }
         }
     }
@@ -0,0 +1,251 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fmt;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use up_rust::UMessage;
+
+/// Freshness limits of the controller inputs and the safe state taken when they are exceeded.
+#[derive(Debug, Clone, Copy)]
+pub struct WatchdogConfig {
+    /// Maximum age of the last velocity sample
+    pub velocity_timeout: Duration,
+    /// Maximum age of the last clock tick
+    pub clock_timeout: Duration,
+    /// Period at which the inputs are checked
+    pub check_interval: Duration,
+    /// Actuation command published when an input goes stale, in [-1, 1]:
+    /// `0.0` coasts, a small negative value brakes gently
+    pub safe_command: f64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        WatchdogConfig {
+            velocity_timeout: Duration::from_millis(500),
+            clock_timeout: Duration::from_millis(500),
+            check_interval: Duration::from_millis(50),
+            safe_command: 0.0,
+        }
+    }
+}
+
+/// Inputs whose freshness is tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Velocity,
+    Clock,
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Velocity => write!(f, "velocity"),
+            Input::Clock => write!(f, "clock"),
+        }
+    }
+}
+
+/// Receive time and expiry of the last sample of one input.
+///
+/// A sample is valid for the configured timeout after it was received, or until its uProtocol
+/// TTL runs out if that comes first. An input that never received a sample is stale.
+#[derive(Debug, Clone, Copy)]
+pub struct InputFreshness {
+    timeout: Duration,
+    last_received: Option<Instant>,
+    valid_until: Option<Instant>,
+}
+
+impl InputFreshness {
+    pub fn new(timeout: Duration) -> Self {
+        InputFreshness {
+            timeout,
+            last_received: None,
+            valid_until: None,
+        }
+    }
+
+    /// Records a sample received at `now`; a `ttl` of `None` or zero means the publisher set no expiry.
+    pub fn received(&mut self, now: Instant, ttl: Option<Duration>) {
+        let lifetime = match ttl {
+            Some(ttl) if !ttl.is_zero() => ttl.min(self.timeout),
+            _ => self.timeout,
+        };
+        self.last_received = Some(now);
+        self.valid_until = Some(now + lifetime);
+    }
+
+    pub fn is_stale(&self, now: Instant) -> bool {
+        self.valid_until.is_none_or(|valid_until| now > valid_until)
+    }
+
+    /// Time since the last sample, `None` if there was none.
+    pub fn age(&self, now: Instant) -> Option<Duration> {
+        self.last_received.map(|received| now.saturating_duration_since(received))
+    }
+}
+
+/// TTL of `message`, which bounds how long its sample stays fresh.
+pub fn message_ttl(message: &UMessage) -> Option<Duration> {
+    message.ttl().map(|ttl| Duration::from_millis(ttl as u64))
+}
+
+/// Freshness of all controller inputs, updated by the control loop.
+#[derive(Debug, Clone, Copy)]
+pub struct Freshness {
+    velocity: InputFreshness,
+    clock: InputFreshness,
+    clock_required: bool,
+}
+
+impl Freshness {
+    pub fn new(config: &WatchdogConfig) -> Self {
+        Freshness {
+            velocity: InputFreshness::new(config.velocity_timeout),
+            clock: InputFreshness::new(config.clock_timeout),
+            clock_required: true,
+        }
+    }
+
+    /// Whether a stale `input` counts; the velocity is always required.
+    pub fn set_required(&mut self, input: Input, required: bool) {
+        if input == Input::Clock {
+            self.clock_required = required;
+        }
+    }
+
+    /// Records a sample of `input` received at `now`, with the TTL of its message.
+    pub fn received(&mut self, input: Input, now: Instant, ttl: Option<Duration>) {
+        self.input_mut(input).received(now, ttl);
+    }
+
+    pub fn input(&self, input: Input) -> &InputFreshness {
+        match input {
+            Input::Velocity => &self.velocity,
+            Input::Clock => &self.clock,
+        }
+    }
+
+    fn input_mut(&mut self, input: Input) -> &mut InputFreshness {
+        match input {
+            Input::Velocity => &mut self.velocity,
+            Input::Clock => &mut self.clock,
+        }
+    }
+
+    pub fn stale_inputs(&self, now: Instant) -> Vec<Input> {
+        [Input::Velocity, Input::Clock]
+            .into_iter()
+            .filter(|input| *input != Input::Clock || self.clock_required)
+            .filter(|input| self.input(*input).is_stale(now))
+            .collect()
+    }
+}
+
+/// Payload of the fault topic, published as JSON when inputs go stale and again when they
+/// are all fresh.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaultEvent {
+    /// Kind of fault, `stale_input`
+    pub fault: String,
+    /// Whether the fault is raised (`true`) or cleared (`false`)
+    pub active: bool,
+    /// Inputs that were stale
+    pub inputs: Vec<String>,
+    /// Age of the last sample of each stale input in seconds, `null` if none was received
+    pub ages: Vec<Option<f64>>,
+    /// Actuation command published when the fault was raised
+    pub safe_command: f64,
+    /// Wall-clock time of the event in seconds since the Unix epoch
+    pub timestamp: f64,
+}
+
+impl FaultEvent {
+    pub fn stale_input(
+        freshness: &Freshness,
+        stale: &[Input],
+        now: Instant,
+        active: bool,
+        safe_command: f64,
+        timestamp: f64,
+    ) -> Self {
+        FaultEvent {
+            fault: "stale_input".to_string(),
+            active,
+            inputs: stale.iter().map(|input| input.to_string()).collect(),
+            ages: stale
+                .iter()
+                .map(|input| freshness.input(*input).age(now).map(|age| age.as_secs_f64()))
+                .collect(),
+            safe_command,
+            timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_and_ttl() {
+        let start = Instant::now();
+        let mut freshness = InputFreshness::new(Duration::from_millis(500));
+        assert!(freshness.is_stale(start));
+        assert_eq!(freshness.age(start), None);
+
+        freshness.received(start, None);
+        assert!(!freshness.is_stale(start + Duration::from_millis(500)));
+        assert!(freshness.is_stale(start + Duration::from_millis(501)));
+        assert_eq!(freshness.age(start + Duration::from_millis(200)), Some(Duration::from_millis(200)));
+
+        // A shorter TTL expires the sample early, a longer one or zero does not extend it
+        freshness.received(start, Some(Duration::from_millis(100)));
+        assert!(freshness.is_stale(start + Duration::from_millis(101)));
+        freshness.received(start, Some(Duration::from_secs(10)));
+        assert!(freshness.is_stale(start + Duration::from_millis(501)));
+        freshness.received(start, Some(Duration::ZERO));
+        assert!(!freshness.is_stale(start + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_stale_inputs() {
+        let config = WatchdogConfig {
+            velocity_timeout: Duration::from_millis(100),
+            clock_timeout: Duration::from_millis(300),
+            ..WatchdogConfig::default()
+        };
+        let start = Instant::now();
+        let mut freshness = Freshness::new(&config);
+        assert_eq!(freshness.stale_inputs(start), vec![Input::Velocity, Input::Clock]);
+
+        freshness.velocity.received(start, None);
+        freshness.clock.received(start, None);
+        assert!(freshness.stale_inputs(start + Duration::from_millis(100)).is_empty());
+        assert_eq!(freshness.stale_inputs(start + Duration::from_millis(200)), vec![Input::Velocity]);
+
+        let now = start + Duration::from_millis(400);
+        let stale = freshness.stale_inputs(now);
+        let event = FaultEvent::stale_input(&freshness, &stale, now, true, -0.2, 0.0);
+        assert_eq!(event.inputs, vec!["velocity", "clock"]);
+        assert_eq!(event.ages, vec![Some(0.4), Some(0.4)]);
+
+        freshness.set_required(Input::Clock, false);
+        assert_eq!(freshness.stale_inputs(now), vec![Input::Velocity]);
+    }
+}
@@ -0,0 +1,27 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use zenoh::Config;
+
+/// Zenoh configuration of a peer, connected to the router at `endpoint` (`host:port`) if one
+/// is given.
+pub fn peer_config(endpoint: Option<&str>) -> Result<Config, String> {
+    let zenoh_string = match endpoint {
+        Some(endpoint) => format!("{{ mode: 'peer', connect: {{ endpoints: [ 'tcp/{}' ] }} }}", endpoint),
+        None => "{ mode: 'peer' }".to_string(),
+    };
+    Config::from_json5(&zenoh_string).map_err(|e| format!("failed to load Zenoh config: {}", e))
+}
@@ -0,0 +1,512 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+use std::time::Duration;
+use cruise_control_messages::{
+    ActuationCommand, ClockStatus, CruiseControlPayload, Encoding, EngageStatus, TargetSpeed, VelocityStatus,
+};
+use sdv_lab_topics as topics;
+use up_rust::{StaticUriProvider, UMessageBuilder, UPayloadFormat, UTransport, UUri};
+
+use pid_cruise_control::acc::AccConfig;
+use pid_cruise_control::control_loop::LoopMode;
+use pid_cruise_control::health::{ControllerMode, HealthConfig, HealthStatus};
+use pid_cruise_control::loopback_transport::LoopbackTransport;
+use pid_cruise_control::pid_controller::PIDController;
+use pid_cruise_control::time_base::{TimeBaseConfig, TimeSource};
+use pid_cruise_control::uprotocol_handler::{TopicUris, UProtocolHandler};
+use pid_cruise_control::watchdog::{FaultEvent, WatchdogConfig};
+
+const KP: f64 = 0.125;
+const KI: f64 = KP / 8.0;
+const KD: f64 = KP / 10.0;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Harness {
+    transport: Arc<LoopbackTransport>,
+    handler: UProtocolHandler<PIDController>,
+    velocity_uri: UUri,
+    clock_uri: UUri,
+    engage_uri: UUri,
+    target_speed_uri: UUri,
+    actuation_uri: UUri,
+}
+
+impl Harness {
+    async fn new() -> Self {
+        Self::build(|handler| handler).await
+    }
+
+    // Starts a handler over a fresh loopback transport, after `configure` applied its settings
+    async fn build(
+        configure: impl FnOnce(UProtocolHandler<PIDController>) -> UProtocolHandler<PIDController>,
+    ) -> Self {
+        let transport = Arc::new(LoopbackTransport::new());
+        let handler = configure(UProtocolHandler::new(PIDController::new(KP, KI, KD), transport.clone()).unwrap());
+        handler.start().await.unwrap();
+
+        Harness {
+            transport,
+            handler,
+            velocity_uri: topics::VELOCITY_STATUS.uri(),
+            clock_uri: topics::CLOCK_STATUS.uri(),
+            engage_uri: topics::ENGAGE.uri(),
+            target_speed_uri: topics::TARGET_SPEED.uri(),
+            actuation_uri: topics::ACTUATION.uri(),
+        }
+    }
+
+    // Polls until the background tasks (watchdog, health, fixed-rate loop) made `condition`
+    // true, so that a slow runner only makes the test take longer
+    async fn wait_for(&self, what: &str, condition: impl Fn(&Self) -> bool) {
+        let poll = async {
+            while !condition(self) {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        };
+        if tokio::time::timeout(WAIT_TIMEOUT, poll).await.is_err() {
+            panic!("timed out waiting for {}", what);
+        }
+    }
+
+    // Publishes a legacy text payload
+    async fn publish(&self, uri: &UUri, payload: &str) {
+        let message = UMessageBuilder::publish(uri.clone())
+            .build_with_payload(payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .unwrap();
+        self.transport.send(message).await.unwrap();
+        self.handler.sync().await;
+    }
+
+    async fn publish_protobuf<T: CruiseControlPayload>(&self, uri: &UUri, value: T) {
+        let (payload, format) = value.encode(Encoding::Protobuf);
+        let message = UMessageBuilder::publish(uri.clone())
+            .build_with_payload(payload, format)
+            .unwrap();
+        self.transport.send(message).await.unwrap();
+        self.handler.sync().await;
+    }
+
+    async fn publish_json(&self, uri: &UUri, payload: &str) {
+        let message = UMessageBuilder::publish(uri.clone())
+            .build_with_payload(payload.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_JSON)
+            .unwrap();
+        self.transport.send(message).await.unwrap();
+        self.handler.sync().await;
+    }
+
+    // Publishes a clock tick followed by a velocity sample, like the ego vehicle does
+    async fn sample(&self, time: f64, velocity: f64) {
+        self.publish(&self.clock_uri, &time.to_string()).await;
+        self.publish(&self.velocity_uri, &velocity.to_string()).await;
+    }
+
+    fn fault_events(&self) -> Vec<FaultEvent> {
+        self.transport
+            .published_to(&topics::FAULT.uri())
+            .iter()
+            .map(|message| serde_json::from_slice(message.payload.as_ref().unwrap()).unwrap())
+            .collect()
+    }
+
+    fn acc_modes(&self) -> Vec<String> {
+        self.transport
+            .published_to(&topics::ACC_STATUS.uri())
+            .iter()
+            .map(|message| {
+                let status: serde_json::Value = serde_json::from_slice(message.payload.as_ref().unwrap()).unwrap();
+                status["mode"].as_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    fn health_statuses(&self) -> Vec<HealthStatus> {
+        self.transport
+            .published_to(&topics::HEALTH.uri())
+            .iter()
+            .map(|message| serde_json::from_slice(message.payload.as_ref().unwrap()).unwrap())
+            .collect()
+    }
+
+    fn actuation_values(&self) -> Vec<f64> {
+        self.transport
+            .published_to(&self.actuation_uri)
+            .iter()
+            .map(|message| ActuationCommand::decode_message(message).unwrap().command)
+            .collect()
+    }
+}
+
+fn uri_provider() -> Arc<StaticUriProvider> {
+    Arc::new(StaticUriProvider::new(topics::PID_CONTROLLER.authority, topics::UE_ID, topics::UE_VERSION_MAJOR))
+}
+
+#[tokio::test]
+async fn test_no_actuation_while_disengaged() {
+    let harness = Harness::new().await;
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+
+    harness.sample(1.0, 30.0).await;
+    harness.sample(1.1, 31.0).await;
+
+    assert!(!harness.handler.is_active());
+    assert!(harness.actuation_values().is_empty());
+}
+
+#[tokio::test]
+async fn test_actuation_matches_controller_output() {
+    let harness = Harness::new().await;
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+    harness.publish(&harness.engage_uri, "1").await;
+
+    let samples = [(1.0, 30.0), (1.1, 30.5), (1.2, 31.2), (1.3, 32.0)];
+    for (time, velocity) in samples {
+        harness.sample(time, velocity).await;
+    }
+
+    let mut reference = PIDController::new(KP, KI, KD);
+    let expected: Vec<f64> = samples
+        .iter()
+        .map(|(time, velocity)| reference.compute(50.0, *velocity, *time).unwrap())
+        .collect();
+
+    assert_eq!(harness.actuation_values(), expected);
+    assert_eq!(harness.handler.get_state(), (32.0, 50.0, 1.3, true));
+}
+
+#[tokio::test]
+async fn test_engage_state_machine() {
+    let harness = Harness::new().await;
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+    assert!(!harness.handler.is_active());
+
+    harness.publish(&harness.engage_uri, "1").await;
+    assert!(harness.handler.is_active());
+    harness.sample(1.0, 30.0).await;
+    harness.sample(1.1, 30.0).await;
+    assert_eq!(harness.actuation_values().len(), 2);
+
+    // Repeated engage keeps the controller state
+    harness.publish(&harness.engage_uri, "1").await;
+    harness.sample(1.2, 30.0).await;
+    assert!(harness.actuation_values()[2] > 0.0);
+
+    harness.publish(&harness.engage_uri, "0").await;
+    assert!(!harness.handler.is_active());
+    harness.sample(1.3, 30.0).await;
+    assert_eq!(harness.actuation_values().len(), 3);
+
+    // Re-engaging starts from a reset controller: the first sample only initializes it
+    harness.transport.clear_sent_messages();
+    harness.publish(&harness.engage_uri, "1").await;
+    harness.sample(5.0, 30.0).await;
+    assert_eq!(harness.actuation_values(), vec![0.0]);
+}
+
+#[tokio::test]
+async fn test_malformed_payloads_are_ignored() {
+    let harness = Harness::new().await;
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+    harness.publish(&harness.engage_uri, "1").await;
+    harness.sample(1.0, 30.0).await;
+
+    harness.publish(&harness.velocity_uri, "fast").await;
+    harness.publish(&harness.target_speed_uri, "{\"speed\": \"high\"}").await;
+    harness.publish(&harness.engage_uri, "maybe").await;
+
+    assert!(harness.handler.is_active());
+    assert_eq!(harness.handler.get_state(), (30.0, 50.0, 1.0, true));
+    assert_eq!(harness.actuation_values().len(), 1);
+}
+
+#[tokio::test]
+async fn test_protobuf_payloads() {
+    let harness = Harness::new().await;
+    harness.publish_protobuf(&harness.target_speed_uri, TargetSpeed::from_value(50.0)).await;
+    harness.publish_protobuf(&harness.engage_uri, EngageStatus::from_value(1.0)).await;
+    harness.publish_protobuf(&harness.clock_uri, ClockStatus::from_value(1.0)).await;
+    harness.publish_protobuf(&harness.velocity_uri, VelocityStatus::from_value(30.0)).await;
+
+    assert_eq!(harness.handler.get_state(), (30.0, 50.0, 1.0, true));
+    let published = harness.transport.published_to(&harness.actuation_uri);
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].payload_format(), Some(UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF));
+}
+
+#[tokio::test]
+async fn test_legacy_text_actuation() {
+    let harness = Harness::build(|handler| handler.with_actuation_encoding(Encoding::Text)).await;
+    harness.publish(&harness.target_speed_uri, "{\"speed\": 50.0}").await;
+    harness.publish(&harness.engage_uri, "1").await;
+    harness.sample(1.0, 30.0).await;
+
+    let published = harness.transport.published_to(&harness.actuation_uri);
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].payload_format(), Some(UPayloadFormat::UPAYLOAD_FORMAT_TEXT));
+    let text = std::str::from_utf8(published[0].payload.as_ref().unwrap()).unwrap();
+    assert_eq!(text.parse::<f64>().unwrap(), harness.actuation_values()[0]);
+}
+
+#[tokio::test]
+async fn test_stale_input_falls_back_to_safe_command() {
+    let harness = Harness::build(|handler| {
+        handler.with_watchdog(WatchdogConfig {
+            velocity_timeout: Duration::from_millis(100),
+            clock_timeout: Duration::from_millis(100),
+            check_interval: Duration::from_millis(10),
+            safe_command: -0.2,
+        })
+    })
+    .await;
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+
+    // Engaging is refused until the inputs are fresh
+    harness.publish(&harness.engage_uri, "1").await;
+    assert!(!harness.handler.is_active());
+
+    harness.sample(1.0, 30.0).await;
+    harness.publish(&harness.engage_uri, "1").await;
+    assert!(harness.handler.is_active());
+    harness.sample(1.1, 30.0).await;
+    assert!(harness.fault_events().is_empty());
+
+    // Inputs stop: the controller publishes the safe command, disengages and raises a fault
+    harness.wait_for("the stale input fault", |harness| !harness.fault_events().is_empty()).await;
+    assert!(!harness.handler.is_active());
+    assert_eq!(harness.actuation_values().last(), Some(&-0.2));
+    let faults = harness.fault_events();
+    assert_eq!(faults.len(), 1);
+    assert!(faults[0].active);
+    assert_eq!(faults[0].inputs, vec!["velocity", "clock"]);
+    assert!(faults[0].ages.iter().all(|age| age.unwrap() > 0.1));
+
+    // A stale engage is refused, fresh inputs clear the fault and engaging works again
+    harness.publish(&harness.engage_uri, "1").await;
+    assert!(!harness.handler.is_active());
+    harness.sample(2.0, 30.0).await;
+    harness.wait_for("the cleared fault", |harness| harness.fault_events().len() > 1).await;
+    let faults = harness.fault_events();
+    assert_eq!(faults.len(), 2);
+    assert!(!faults[1].active);
+    harness.publish(&harness.engage_uri, "1").await;
+    assert!(harness.handler.is_active());
+}
+
+#[tokio::test]
+async fn test_clock_jumps_and_resets() {
+    let harness = Harness::new().await;
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+    harness.publish(&harness.engage_uri, "1").await;
+
+    // A simulation starting at t = 0 is a valid first sample
+    harness.sample(0.0, 30.0).await;
+    harness.sample(0.1, 30.0).await;
+    assert_eq!(harness.actuation_values().len(), 2);
+    let last = harness.actuation_values()[1];
+    assert!(last > 0.0);
+
+    // A clock that does not advance publishes nothing
+    harness.sample(0.1, 30.0).await;
+    assert_eq!(harness.actuation_values().len(), 2);
+
+    // A pause holds the integrator: the first sample after it repeats the last command
+    harness.sample(30.0, 30.0).await;
+    assert_eq!(harness.actuation_values()[2], last);
+
+    // A restarted simulation resets the controller instead of failing on a negative delta
+    harness.sample(0.0, 30.0).await;
+    harness.sample(0.1, 30.0).await;
+    assert!(harness.handler.is_active());
+    let mut reference = PIDController::new(KP, KI, KD);
+    let expected = [reference.compute(50.0, 30.0, 0.0).unwrap(), reference.compute(50.0, 30.0, 0.1).unwrap()];
+    assert_eq!(harness.actuation_values()[3..], expected[..]);
+}
+
+#[tokio::test]
+async fn test_acc_follows_slower_lead_vehicle() {
+    let harness = Harness::build(|handler| handler.with_acc(AccConfig::default())).await;
+    harness.publish(&harness.target_speed_uri, "100.0").await;
+    harness.publish(&harness.engage_uri, "1").await;
+
+    harness.sample(1.0, 72.0).await;
+    harness.sample(1.1, 72.0).await;
+    assert_eq!(harness.acc_modes(), vec!["speed"]);
+    assert!(harness.actuation_values()[1] > 0.0);
+
+    // A lead vehicle at 31 m is closer than the 41 m time gap at 72 km/h: the controller brakes
+    harness.publish_json(&topics::OBSTACLE_DETECTION.uri(), r#"{"distance": 31.0}"#).await;
+    harness.sample(1.2, 72.0).await;
+    assert_eq!(harness.acc_modes(), vec!["speed", "spacing"]);
+    assert!(harness.actuation_values()[2] < 0.0);
+
+    // Once the lead vehicle is out of sight, the set speed is tracked again
+    harness.sample(2.0, 72.0).await;
+    assert_eq!(harness.acc_modes(), vec!["speed", "spacing", "speed"]);
+
+    harness.publish(&harness.engage_uri, "0").await;
+    harness.sample(2.1, 72.0).await;
+    assert_eq!(harness.acc_modes().last().unwrap(), "standby");
+}
+
+#[tokio::test]
+async fn test_health_status_is_published_periodically() {
+    let harness = Harness::build(|handler| {
+        handler.with_health(HealthConfig { interval: Duration::from_millis(20), ttl_ms: 100 }, uri_provider())
+    })
+    .await;
+    harness.wait_for("a health status", |harness| !harness.health_statuses().is_empty()).await;
+    let statuses = harness.health_statuses();
+    assert!(!statuses.is_empty());
+    assert_eq!(statuses[0].mode, ControllerMode::Disengaged);
+    assert_eq!(statuses[0].last_command, None);
+    assert!(statuses[0].faults.stale_velocity);
+    assert_eq!(statuses[0].loop_timing, None);
+
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+    harness.publish(&harness.engage_uri, "1").await;
+    harness.sample(1.0, 30.0).await;
+    harness.sample(1.1, 31.0).await;
+    harness
+        .wait_for("an engaged health status", |harness| {
+            harness.health_statuses().last().is_some_and(|status| status.current_speed == 31.0)
+        })
+        .await;
+
+    let statuses = harness.health_statuses();
+    let status = statuses.last().unwrap();
+    assert!(statuses.windows(2).all(|pair| pair[1].sequence == pair[0].sequence + 1));
+    assert_eq!(status.mode, ControllerMode::Engaged);
+    assert_eq!((status.current_speed, status.desired_speed), (31.0, 50.0));
+    assert_eq!(status.last_command, harness.actuation_values().last().copied());
+    assert!(status.terms.unwrap().proportional > 0.0);
+    assert!(!status.faults.stale_velocity);
+
+    let message = harness.transport.published_to(&topics::HEALTH.uri()).pop().unwrap();
+    assert_eq!(message.ttl(), Some(100));
+}
+
+#[tokio::test]
+async fn test_stop_publishes_zero_and_unregisters() {
+    let harness = Harness::build(|handler| {
+        handler.with_health(HealthConfig { interval: Duration::from_millis(10), ttl_ms: 100 }, uri_provider())
+    })
+    .await;
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+    harness.publish(&harness.engage_uri, "1").await;
+    harness.sample(1.0, 30.0).await;
+    harness.sample(1.1, 30.0).await;
+    assert!(harness.actuation_values().last().unwrap() > &0.0);
+
+    harness.handler.stop().await;
+    assert!(!harness.handler.is_active());
+    assert_eq!(harness.actuation_values().last(), Some(&0.0));
+
+    // Inputs are no longer received and the health publisher stopped
+    let published = harness.transport.sent_messages().len();
+    harness.sample(1.2, 30.0).await;
+    harness.publish(&harness.engage_uri, "1").await;
+    // Only checks that nothing arrives, a slow runner cannot make it fail
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(harness.handler.get_state(), (30.0, 50.0, 1.1, false));
+    assert_eq!(harness.transport.sent_messages().len(), published + 3);
+    assert_eq!(harness.actuation_values().last(), Some(&0.0));
+}
+
+#[tokio::test]
+async fn test_fixed_rate_loop_runs_on_latest_inputs() {
+    let harness = Harness::build(|handler| {
+        handler
+            .with_time_base(TimeBaseConfig { source: TimeSource::Wall, ..TimeBaseConfig::default() })
+            .with_loop_mode(LoopMode::FixedRate(Duration::from_millis(10)))
+    })
+    .await;
+    let mut state = harness.handler.watch_state();
+    harness.publish(&harness.target_speed_uri, "50.0").await;
+    harness.publish(&harness.engage_uri, "1").await;
+
+    // A single velocity sample is enough: the loop keeps computing from the latest one
+    harness.publish(&harness.velocity_uri, "30.0").await;
+    harness.wait_for("three commands", |harness| harness.actuation_values().len() >= 3).await;
+    let commands = harness.actuation_values();
+    assert!(commands.len() >= 3);
+    assert!(commands[1..].iter().all(|command| *command > 0.0));
+
+    // Observers see the state of the control task
+    state.changed().await.unwrap();
+    let snapshot = state.borrow_and_update().clone();
+    assert!(snapshot.engaged);
+    assert_eq!((snapshot.current_velocity, snapshot.desired_velocity), (30.0, 50.0));
+    assert_eq!(snapshot.last_command.map(|(command, _)| command), commands.last().copied());
+    let timing = snapshot.timing.unwrap();
+    assert_eq!(timing.period, 0.01);
+    assert!(timing.steps as usize >= commands.len());
+    assert!(timing.max_jitter >= timing.mean_jitter);
+
+    // Disengaged, the loop keeps running without publishing
+    harness.publish(&harness.engage_uri, "0").await;
+    let published = harness.actuation_values().len();
+    let steps = harness.handler.watch_state().borrow().timing.unwrap().steps;
+    let later = state.wait_for(|state| state.timing.is_some_and(|timing| timing.steps >= steps + 3));
+    tokio::time::timeout(WAIT_TIMEOUT, later).await.unwrap().unwrap();
+    assert_eq!(harness.actuation_values().len(), published);
+}
+
+#[tokio::test]
+async fn test_fleet_vehicles_are_controlled_independently() {
+    let transport = Arc::new(LoopbackTransport::new());
+    let mut fleet = Vec::new();
+    for vehicle in ["hero", "hero2"] {
+        let handler = UProtocolHandler::new(PIDController::new(KP, KI, KD), transport.clone())
+            .unwrap()
+            .with_vehicle(vehicle);
+        handler.start().await.unwrap();
+        fleet.push((TopicUris::default().for_vehicle(vehicle).unwrap(), handler));
+    }
+    let (hero, hero2) = (&fleet[0].0, &fleet[1].0);
+    assert_eq!(hero2.velocity, topics::VELOCITY_STATUS.vehicle_uri("hero2").unwrap());
+    assert_eq!(String::from(&hero2.actuation), "//CruiseControl-hero2/0/2/8001");
+
+    let send = |uri: &UUri, value: f64| {
+        let message = UMessageBuilder::publish(uri.clone())
+            .build_with_payload(value.to_string(), UPayloadFormat::UPAYLOAD_FORMAT_TEXT)
+            .unwrap();
+        let transport = transport.clone();
+        async move { transport.send(message).await.unwrap() }
+    };
+
+    // Only hero is engaged, each vehicle tracks its own set speed
+    send(&hero.target_speed, 50.0).await;
+    send(&hero2.target_speed, 80.0).await;
+    send(&hero.engage, 1.0).await;
+    for (topics, velocity) in [(hero, 30.0), (hero2, 60.0)] {
+        send(&topics.clock, 1.0).await;
+        send(&topics.velocity, velocity).await;
+        send(&topics.clock, 1.1).await;
+        send(&topics.velocity, velocity).await;
+    }
+    for (_, handler) in &fleet {
+        handler.sync().await;
+    }
+
+    assert_eq!(fleet[0].1.get_state(), (30.0, 50.0, 1.1, true));
+    assert_eq!(fleet[1].1.get_state(), (60.0, 80.0, 1.1, false));
+    assert_eq!(transport.published_to(&hero.actuation).len(), 2);
+    assert!(transport.published_to(&hero2.actuation).is_empty());
+    assert!(transport.published_to(&topics::ACTUATION.uri()).is_empty());
+}
@@ -0,0 +1,315 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Typed payloads of the cruise-control topics.
+//!
+//! Publishers encode the messages of `proto/cruise_control/v1/messages.proto` with
+//! `UPAYLOAD_FORMAT_PROTOBUF`. Subscribers decode by the `payload_format` of the received
+//! message, so that publishers that still send the legacy payloads keep working:
+//!
+//! - `UPAYLOAD_FORMAT_PROTOBUF`: the protobuf message
+//! - `UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY`: the protobuf message packed in a `google.protobuf.Any`
+//! - `UPAYLOAD_FORMAT_TEXT` or unspecified: legacy text, e.g. `42.5` (`0`/`1` or `true`/`false` for engage)
+//! - `UPAYLOAD_FORMAT_JSON`: legacy JSON, e.g. `{"velocity": 42.5}`
+
+use std::fmt;
+use bytes::Bytes;
+use protobuf::well_known_types::any::Any;
+use protobuf::{Message, MessageFull};
+use up_rust::{UMessage, UPayloadFormat};
+
+mod proto {
+    include!(concat!(env!("OUT_DIR"), "/proto/mod.rs"));
+}
+
+pub use proto::messages::{ActuationCommand, ClockStatus, EngageStatus, TargetSpeed, VelocityStatus};
+
+/// Encoding used when publishing a payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Protobuf,
+    /// Plain text number, for subscribers that predate the protobuf schema
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    MissingPayload,
+    UnsupportedFormat(UPayloadFormat),
+    Protobuf(String),
+    Legacy(String),
+    /// NaN or an infinite value, which no cruise-control topic carries
+    NotFinite(f64),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::MissingPayload => write!(f, "message has no payload"),
+            DecodeError::UnsupportedFormat(format) => write!(f, "unsupported payload format {:?}", format),
+            DecodeError::Protobuf(e) => write!(f, "invalid protobuf payload: {}", e),
+            DecodeError::Legacy(payload) => write!(f, "invalid legacy payload '{}'", payload),
+            DecodeError::NotFinite(value) => write!(f, "payload value {} is not finite", value),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// A cruise-control message carrying a single value, with its legacy representations.
+pub trait CruiseControlPayload: MessageFull {
+    /// Key of the value in the legacy JSON payload, e.g. `velocity` in `{"velocity": 42.5}`
+    const LEGACY_JSON_KEY: &'static str;
+
+    fn from_value(value: f64) -> Self;
+
+    fn value(&self) -> f64;
+
+    /// Parses the legacy text payload; `nan` and `inf` are not accepted.
+    fn from_legacy_text(text: &str) -> Option<Self> {
+        parse_finite(text).map(Self::from_value)
+    }
+
+    fn to_legacy_text(&self) -> String {
+        self.value().to_string()
+    }
+
+    /// Decodes `payload` according to the `payload_format` it was published with.
+    fn decode(payload: &[u8], format: UPayloadFormat) -> Result<Self, DecodeError> {
+        let message = match format {
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF => {
+                Self::parse_from_bytes(payload).map_err(|e| DecodeError::Protobuf(e.to_string()))
+            }
+            UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY => Any::parse_from_bytes(payload)
+                .and_then(|any| any.unpack::<Self>())
+                .map_err(|e| DecodeError::Protobuf(e.to_string()))?
+                .ok_or_else(|| DecodeError::Protobuf(format!("Any does not contain a {}", Self::descriptor().full_name()))),
+            UPayloadFormat::UPAYLOAD_FORMAT_TEXT
+            | UPayloadFormat::UPAYLOAD_FORMAT_JSON
+            | UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED => decode_legacy(payload),
+            format => Err(DecodeError::UnsupportedFormat(format)),
+        }?;
+        // A protobuf double can carry NaN or an infinity just as well
+        if !message.value().is_finite() {
+            return Err(DecodeError::NotFinite(message.value()));
+        }
+        Ok(message)
+    }
+
+    /// Decodes the payload of a received message.
+    fn decode_message(message: &UMessage) -> Result<Self, DecodeError> {
+        let payload = message.payload.as_ref().ok_or(DecodeError::MissingPayload)?;
+        let format = message
+            .payload_format()
+            .unwrap_or(UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED);
+        Self::decode(payload, format)
+    }
+
+    /// Encodes the message, returning the payload and the format to publish it with.
+    fn encode(&self, encoding: Encoding) -> (Bytes, UPayloadFormat) {
+        match encoding {
+            Encoding::Protobuf => (
+                Bytes::from(self.write_to_bytes().expect("cruise-control messages always serialize")),
+                UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF,
+            ),
+            Encoding::Text => (Bytes::from(self.to_legacy_text()), UPayloadFormat::UPAYLOAD_FORMAT_TEXT),
+        }
+    }
+}
+
+fn parse_finite(text: &str) -> Option<f64> {
+    text.trim().parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+// Publishers used to send plain text, older ones JSON objects, both without a reliable
+// payload_format, so the content decides
+fn decode_legacy<T: CruiseControlPayload>(payload: &[u8]) -> Result<T, DecodeError> {
+    let text = std::str::from_utf8(payload).map_err(|_| DecodeError::Legacy(String::from_utf8_lossy(payload).into_owned()))?;
+    if let Some(message) = T::from_legacy_text(text) {
+        return Ok(message);
+    }
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|json| json.get(T::LEGACY_JSON_KEY).and_then(|value| value.as_f64()))
+        .map(T::from_value)
+        .ok_or_else(|| DecodeError::Legacy(text.to_string()))
+}
+
+impl CruiseControlPayload for VelocityStatus {
+    const LEGACY_JSON_KEY: &'static str = "velocity";
+
+    fn from_value(value: f64) -> Self {
+        VelocityStatus { velocity_kmh: value, ..Default::default() }
+    }
+
+    fn value(&self) -> f64 {
+        self.velocity_kmh
+    }
+}
+
+impl CruiseControlPayload for ClockStatus {
+    const LEGACY_JSON_KEY: &'static str = "time";
+
+    fn from_value(value: f64) -> Self {
+        ClockStatus { elapsed_seconds: value, ..Default::default() }
+    }
+
+    fn value(&self) -> f64 {
+        self.elapsed_seconds
+    }
+}
+
+impl CruiseControlPayload for TargetSpeed {
+    const LEGACY_JSON_KEY: &'static str = "speed";
+
+    fn from_value(value: f64) -> Self {
+        TargetSpeed { speed_kmh: value, ..Default::default() }
+    }
+
+    fn value(&self) -> f64 {
+        self.speed_kmh
+    }
+}
+
+impl CruiseControlPayload for EngageStatus {
+    const LEGACY_JSON_KEY: &'static str = "engaged";
+
+    fn from_value(value: f64) -> Self {
+        EngageStatus { engaged: value != 0.0, ..Default::default() }
+    }
+
+    fn value(&self) -> f64 {
+        if self.engaged { 1.0 } else { 0.0 }
+    }
+
+    // Legacy engage payloads are `0`/`1`, the AAOS cluster may also send `true`/`false`
+    fn from_legacy_text(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "true" => Some(Self::from_value(1.0)),
+            "false" => Some(Self::from_value(0.0)),
+            text => parse_finite(text).map(Self::from_value),
+        }
+    }
+}
+
+impl CruiseControlPayload for ActuationCommand {
+    const LEGACY_JSON_KEY: &'static str = "command";
+
+    fn from_value(value: f64) -> Self {
+        ActuationCommand { command: value, ..Default::default() }
+    }
+
+    fn value(&self) -> f64 {
+        self.command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protobuf_round_trip() {
+        let (payload, format) = VelocityStatus::from_value(42.5).encode(Encoding::Protobuf);
+        assert_eq!(format, UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF);
+        assert_eq!(VelocityStatus::decode(&payload, format).unwrap().velocity_kmh, 42.5);
+
+        let (payload, format) = EngageStatus::from_value(1.0).encode(Encoding::Protobuf);
+        assert!(EngageStatus::decode(&payload, format).unwrap().engaged);
+    }
+
+    #[test]
+    fn test_protobuf_wrapped_in_any() {
+        let any = Any::pack(&TargetSpeed::from_value(80.0)).unwrap();
+        let payload = any.write_to_bytes().unwrap();
+        let format = UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY;
+        assert_eq!(TargetSpeed::decode(&payload, format).unwrap().speed_kmh, 80.0);
+        assert!(matches!(ClockStatus::decode(&payload, format), Err(DecodeError::Protobuf(_))));
+    }
+
+    #[test]
+    fn test_legacy_text_and_json() {
+        let text = UPayloadFormat::UPAYLOAD_FORMAT_TEXT;
+        assert_eq!(ClockStatus::decode(b" 12.25\n", text).unwrap().elapsed_seconds, 12.25);
+        assert_eq!(ActuationCommand::decode(b"-0.4", text).unwrap().command, -0.4);
+        assert!(EngageStatus::decode(b"1", text).unwrap().engaged);
+        assert!(!EngageStatus::decode(b"False", text).unwrap().engaged);
+
+        let json = UPayloadFormat::UPAYLOAD_FORMAT_JSON;
+        assert_eq!(VelocityStatus::decode(br#"{"velocity": 33.0}"#, json).unwrap().velocity_kmh, 33.0);
+        assert!(EngageStatus::decode(br#"{"engaged": 1}"#, json).unwrap().engaged);
+        // Publishers that do not set the format at all
+        let unspecified = UPayloadFormat::UPAYLOAD_FORMAT_UNSPECIFIED;
+        assert_eq!(TargetSpeed::decode(br#"{"speed": 50}"#, unspecified).unwrap().speed_kmh, 50.0);
+
+        assert_eq!((ActuationCommand::from_value(0.5).encode(Encoding::Text).0), Bytes::from("0.5"));
+        assert_eq!((EngageStatus::from_value(1.0).encode(Encoding::Text).0), Bytes::from("1"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_payloads() {
+        let text = UPayloadFormat::UPAYLOAD_FORMAT_TEXT;
+        assert_eq!(VelocityStatus::decode(b"fast", text), Err(DecodeError::Legacy("fast".to_string())));
+        assert!(VelocityStatus::decode(br#"{"speed": 3}"#, text).is_err());
+        assert!(VelocityStatus::decode(&[0xff, 0xfe], text).is_err());
+        assert!(VelocityStatus::decode(&[0xff, 0xfe], UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF).is_err());
+        assert_eq!(
+            VelocityStatus::decode(b"1", UPayloadFormat::UPAYLOAD_FORMAT_SOMEIP),
+            Err(DecodeError::UnsupportedFormat(UPayloadFormat::UPAYLOAD_FORMAT_SOMEIP))
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_finite_values() {
+        let text = UPayloadFormat::UPAYLOAD_FORMAT_TEXT;
+        for payload in ["nan", "NaN", "inf", "-infinity"] {
+            assert_eq!(
+                VelocityStatus::decode(payload.as_bytes(), text),
+                Err(DecodeError::Legacy(payload.to_string()))
+            );
+        }
+        // "nan" != 0 must not engage the controller
+        assert!(EngageStatus::decode(b"nan", text).is_err());
+
+        let protobuf = UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF;
+        let (payload, _) = VelocityStatus::from_value(f64::NAN).encode(Encoding::Protobuf);
+        assert!(matches!(VelocityStatus::decode(&payload, protobuf), Err(DecodeError::NotFinite(value)) if value.is_nan()));
+        let (payload, _) = ActuationCommand::from_value(f64::INFINITY).encode(Encoding::Protobuf);
+        assert_eq!(ActuationCommand::decode(&payload, protobuf), Err(DecodeError::NotFinite(f64::INFINITY)));
+    }
+
+    #[test]
+    fn test_registry_payloads_exist() {
+        let messages = [
+            VelocityStatus::descriptor(),
+            ClockStatus::descriptor(),
+            TargetSpeed::descriptor(),
+            EngageStatus::descriptor(),
+            ActuationCommand::descriptor(),
+        ];
+        for topic in sdv_lab_topics::ALL_TOPICS {
+            if let sdv_lab_topics::PayloadType::Protobuf(name) = topic.payload {
+                assert!(
+                    messages.iter().any(|message| message.full_name() == name),
+                    "{} carries unknown message {}",
+                    topic.name,
+                    name
+                );
+            }
+        }
+    }
+}
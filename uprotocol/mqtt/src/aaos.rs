@@ -21,22 +21,49 @@
 //    EGOVehicle/0/2/8001
 //    Threadx/0/2/8001
 // ]
+// With VEHICLE_ID set (e.g. VEHICLE_ID=hero2), the AAOS and EGOVehicle authorities are the ones
+// derived for that vehicle of a fleet (AAOS-hero2, EGOVehicle-hero2)
 
 use async_trait::async_trait;
 use log::{debug, error, info, warn};
 use std::{sync::Arc, thread, time::SystemTime};
-use up_rust::{UListener, UMessage, UMessageBuilder, UPayloadFormat, UStatus, UTransport, UUri};
+use up_rust::{UCode, UListener, UMessage, UMessageBuilder, UPayloadFormat, UStatus, UTransport, UUri};
+use sdv_lab_topics::{self as topics, Component, Topic};
 use up_transport_mqtt5::{Mqtt5Transport, Mqtt5TransportOptions, MqttClientOptions};
 
-// publish
-const AAOS_AUTH: &str = "AAOS";
-// subscribe
-const EGO_AUTH: &str = "EGOVehicle";
-const X_AUTH: &str = "Threadx";
+// published and subscribed topics, from the SDV lab topic map
+const COMPONENT: Component = topics::AAOS_DEMO;
+// authority of the entity itself
+const AAOS_AUTH: &str = COMPONENT.authority;
+
+// UEID is not important so all entities of the lab share the same one
+const UEID: u32 = topics::UE_ID;
+
+// URI of `topic` for the vehicle `vehicle_id` of a fleet; the ThreadX board is shared
+fn vehicle_topic_uri(topic: &Topic, vehicle_id: Option<&str>) -> UUri {
+    match vehicle_id {
+        Some(vehicle_id) if topic.authority != topics::THREADX => topic
+            .vehicle_uri(vehicle_id)
+            .expect("VEHICLE_ID is validated at startup"),
+        _ => topic.uri(),
+    }
+}
 
-// UEID and VERSION are not important so lets make them all the same
-const UEID: u32 = 0;
-const VERSION: u8 = 2;
+// VEHICLE_ID, if set, once checked to make valid authorities for all the topics
+fn vehicle_id() -> Result<Option<String>, UStatus> {
+    let Ok(vehicle_id) = std::env::var("VEHICLE_ID") else {
+        return Ok(None);
+    };
+    for topic in COMPONENT.publishes.iter().chain(COMPONENT.subscribes) {
+        if let Err(e) = topic.vehicle_uri(&vehicle_id) {
+            return Err(UStatus::fail_with_code(
+                UCode::INVALID_ARGUMENT,
+                format!("Invalid VEHICLE_ID '{vehicle_id}': {e}"),
+            ));
+        }
+    }
+    Ok(Some(vehicle_id))
+}
 
 struct PublishReceiver;
 
@@ -60,17 +87,16 @@ async fn main() -> Result<(), UStatus> {
 
     println!("\n*** Started AAOS...");
 
+    let vehicle_id = vehicle_id()?;
+    let authority = match &vehicle_id {
+        Some(vehicle_id) => topics::vehicle_authority(AAOS_AUTH, vehicle_id),
+        None => AAOS_AUTH.to_string(),
+    };
+
     // --- Lists of pubish and subscribe topics ---
-    let pub_topics = [
-        UUri::try_from_parts(AAOS_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI"),
-        UUri::try_from_parts(AAOS_AUTH, UEID, VERSION, 0x8002).expect("Invalid UURI"),
-        UUri::try_from_parts(AAOS_AUTH, UEID, VERSION, 0x8003).expect("Invalid UURI"),
-    ];
-
-    let sub_topics = [
-        UUri::try_from_parts(EGO_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI"),
-        UUri::try_from_parts(X_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI"),
-    ];
+    let topic_uri = |topic: &Topic| vehicle_topic_uri(topic, vehicle_id.as_deref());
+    let pub_topics: Vec<UUri> = COMPONENT.publishes.iter().map(topic_uri).collect();
+    let sub_topics: Vec<UUri> = COMPONENT.subscribes.iter().map(topic_uri).collect();
     // --- End of Lists of pubish and subscribe topics ---
 
     // --- MQTT5 Transport Specific Stuff ---
@@ -85,7 +111,7 @@ async fn main() -> Result<(), UStatus> {
     };
 
     let client =
-        Arc::new(Mqtt5Transport::new(mqtt_transport_options, AAOS_AUTH.to_string()).await?);
+        Arc::new(Mqtt5Transport::new(mqtt_transport_options, authority.clone()).await?);
     // Connect to broker
     client.connect().await?;
     // --- End of MQTT5 Transport Specific Stuff ---
@@ -93,6 +119,7 @@ async fn main() -> Result<(), UStatus> {
     // --- Creation of Publishing Tasks ---
     for pub_topic in pub_topics {
         let client = client.clone();
+        let authority = authority.clone();
         tokio::spawn(async move {
             loop {
                 let current_time = SystemTime::now()
@@ -101,7 +128,7 @@ async fn main() -> Result<(), UStatus> {
                     .as_secs();
                 let payload_text = format!(
                     "Hello from '{}' - Resource: '0x{:X}' using {} - UTC: {current_time}",
-                    AAOS_AUTH,
+                    authority,
                     UEID,
                     std::any::type_name_of_val(&*client)
                         .split("::")
@@ -20,13 +20,15 @@
 use log::{error, info};
 use std::{sync::Arc, thread, time::SystemTime};
 use up_rust::{UMessageBuilder, UPayloadFormat, UStatus, UTransport, UUri};
+use sdv_lab_topics::{self as topics, Component, Topic};
 use up_transport_mqtt5::{Mqtt5Transport, Mqtt5TransportOptions, MqttClientOptions};
 
-// publish
-const X_AUTH: &str = "Threadx";
+// published topics, from the SDV lab topic map
+const COMPONENT: Component = topics::THREADX_DEMO;
+// authority of the entity itself
+const X_AUTH: &str = COMPONENT.authority;
 
-const UEID: u32 = 0;
-const VERSION: u8 = 2;
+const UEID: u32 = topics::UE_ID;
 
 #[tokio::main]
 async fn main() -> Result<(), UStatus> {
@@ -35,7 +37,7 @@ async fn main() -> Result<(), UStatus> {
     println!("\n*** Started threadx...");
 
     // --- Lists of pubish and subscribe topics ---
-    let pub_topics = [UUri::try_from_parts(X_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI")];
+    let pub_topics: Vec<UUri> = COMPONENT.publishes.iter().map(Topic::uri).collect();
 
     // no subscriptions here for now
     // --- End of Lists of pubish and subscribe topics ---
@@ -0,0 +1,511 @@
+//
+// Copyright (c) 2025 The X-Verse <https://github.com/The-Xverse>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//! Topic map of the SDV lab.
+//!
+//! Every uProtocol topic is declared once here, with the authority that owns it, its resource id,
+//! the payload it carries and the key expression of the plain Zenoh topic with the same signal.
+//! The binaries take their URIs from this crate instead of hard-coding them, and the
+//! [`COMPONENTS`] table lists who publishes and who subscribes to what, so that the tests can
+//! catch colliding ids and subscriptions that nobody publishes.
+//!
+//! All entities of the lab use entity id `0` and major version `2`; the authority tells them apart.
+//! With several vehicles, each one gets its own authorities, derived with [`vehicle_authority`].
+
+use std::fmt;
+#[cfg(feature = "up-rust")]
+use up_rust::{UUri, UUriError};
+
+/// Entity id shared by all entities of the lab.
+pub const UE_ID: u32 = 0;
+/// Major version shared by all entities of the lab.
+pub const UE_VERSION_MAJOR: u8 = 2;
+
+/// Authority of the CARLA ego vehicle and of the simulator standing in for it.
+pub const EGO_VEHICLE: &str = "EGOVehicle";
+/// Authority of the Android Automotive cluster, which sets the target speed and engages.
+pub const AAOS: &str = "AAOS";
+/// Authority of the cruise controller.
+pub const CRUISE_CONTROL: &str = "CruiseControl";
+/// Authority of the ThreadX board.
+pub const THREADX: &str = "Threadx";
+
+/// Authority of `authority` for the vehicle `vehicle_id` of a fleet, e.g. `EGOVehicle-hero2`
+/// for the CARLA vehicle with `role_name` `hero2`.
+pub fn vehicle_authority(authority: &str, vehicle_id: &str) -> String {
+    format!("{}-{}", authority, vehicle_id)
+}
+
+/// Payload carried by a topic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadType {
+    /// Protobuf message, by its full name (e.g. `cruise_control.v1.VelocityStatus`).
+    Protobuf(&'static str),
+    /// JSON document.
+    Json,
+    /// Plain text.
+    Text,
+}
+
+impl fmt::Display for PayloadType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadType::Protobuf(message) => write!(f, "protobuf {}", message),
+            PayloadType::Json => write!(f, "json"),
+            PayloadType::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// A topic of the lab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Topic {
+    /// Signal name, unique across the lab.
+    pub name: &'static str,
+    /// Authority of the entity that owns (publishes) the topic.
+    pub authority: &'static str,
+    pub ue_id: u32,
+    pub ue_version_major: u8,
+    pub resource_id: u16,
+    pub payload: PayloadType,
+    /// Key expression of the plain Zenoh topic with the same signal, if there is one.
+    pub zenoh_key: Option<&'static str>,
+}
+
+impl Topic {
+    const fn new(
+        name: &'static str,
+        authority: &'static str,
+        resource_id: u16,
+        payload: PayloadType,
+        zenoh_key: Option<&'static str>,
+    ) -> Self {
+        Topic {
+            name,
+            authority,
+            ue_id: UE_ID,
+            ue_version_major: UE_VERSION_MAJOR,
+            resource_id,
+            payload,
+            zenoh_key,
+        }
+    }
+
+    /// Whether the topic has the given address.
+    pub fn is(&self, authority: &str, ue_id: u32, ue_version_major: u8, resource_id: u16) -> bool {
+        self.authority == authority
+            && self.ue_id == ue_id
+            && self.ue_version_major == ue_version_major
+            && self.resource_id == resource_id
+    }
+
+    /// URI of the topic.
+    #[cfg(feature = "up-rust")]
+    pub fn uri(&self) -> UUri {
+        UUri::try_from_parts(
+            self.authority,
+            self.ue_id,
+            self.ue_version_major,
+            self.resource_id,
+        )
+        .expect("Registry topics are valid URIs")
+    }
+
+    /// URI of the topic for the vehicle `vehicle_id` of a fleet, an error if the id does not
+    /// make a valid authority.
+    #[cfg(feature = "up-rust")]
+    pub fn vehicle_uri(&self, vehicle_id: &str) -> Result<UUri, UUriError> {
+        UUri::try_from_parts(
+            &vehicle_authority(self.authority, vehicle_id),
+            self.ue_id,
+            self.ue_version_major,
+            self.resource_id,
+        )
+    }
+
+    /// Whether `uri` addresses this topic.
+    #[cfg(feature = "up-rust")]
+    pub fn matches(&self, uri: &UUri) -> bool {
+        self.is(
+            &uri.authority_name,
+            uri.ue_id,
+            uri.uentity_major_version(),
+            uri.resource_id(),
+        )
+    }
+}
+
+impl fmt::Display for Topic {
+    /// Formats the topic as its URI, e.g. `//EGOVehicle/0/2/8001`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "//{}/{:X}/{:X}/{:X}",
+            self.authority, self.ue_id, self.ue_version_major, self.resource_id
+        )
+    }
+}
+
+// Vehicle status, published by the ego vehicle (or the simulator)
+pub const VELOCITY_STATUS: Topic = Topic::new(
+    "curr_speed",
+    EGO_VEHICLE,
+    0x8001,
+    PayloadType::Protobuf("cruise_control.v1.VelocityStatus"),
+    Some("vehicle/status/velocity_status"),
+);
+pub const CLOCK_STATUS: Topic = Topic::new(
+    "clock_status",
+    EGO_VEHICLE,
+    0x8002,
+    PayloadType::Protobuf("cruise_control.v1.ClockStatus"),
+    Some("vehicle/status/clock_status"),
+);
+
+// Sensors of the ego vehicle, published as carla-data-serde JSON
+pub const LANE_INVASION: Topic = Topic::new(
+    "lane_invasion",
+    EGO_VEHICLE,
+    0x8010,
+    PayloadType::Json,
+    None,
+);
+pub const COLLISION: Topic = Topic::new("collision", EGO_VEHICLE, 0x8011, PayloadType::Json, None);
+pub const OBSTACLE_DETECTION: Topic = Topic::new(
+    "obstacle_detection",
+    EGO_VEHICLE,
+    0x8012,
+    PayloadType::Json,
+    None,
+);
+pub const IMAGE: Topic = Topic::new("image", EGO_VEHICLE, 0x8013, PayloadType::Json, None);
+pub const RADAR: Topic = Topic::new("radar", EGO_VEHICLE, 0x8014, PayloadType::Json, None);
+pub const LIDAR: Topic = Topic::new("lidar", EGO_VEHICLE, 0x8015, PayloadType::Json, None);
+pub const IMU: Topic = Topic::new("imu", EGO_VEHICLE, 0x8016, PayloadType::Json, None);
+
+// Driver inputs, published by the AAOS cluster
+pub const TARGET_SPEED: Topic = Topic::new(
+    "cc_speed",
+    AAOS,
+    0x8001,
+    PayloadType::Protobuf("cruise_control.v1.TargetSpeed"),
+    Some("adas/cruise_control/target_speed"),
+);
+pub const ENGAGE: Topic = Topic::new(
+    "cc_engage",
+    AAOS,
+    0x8002,
+    PayloadType::Protobuf("cruise_control.v1.EngageStatus"),
+    Some("adas/cruise_control/engage"),
+);
+/// Status text of the AAOS bridge demos.
+pub const AAOS_STATUS: Topic = Topic::new("aaos_status", AAOS, 0x8003, PayloadType::Text, None);
+
+// Cruise controller outputs
+pub const ACTUATION: Topic = Topic::new(
+    "cc_throttle",
+    CRUISE_CONTROL,
+    0x8001,
+    PayloadType::Protobuf("cruise_control.v1.ActuationCommand"),
+    Some("control/command/actuation_cmd"),
+);
+/// Gains of the PID controller, published by the tuning service whenever they change.
+pub const GAINS_STATUS: Topic =
+    Topic::new("cc_gains", CRUISE_CONTROL, 0x8002, PayloadType::Json, None);
+/// Faults of the cruise controller, e.g. stale inputs that made it disengage.
+pub const FAULT: Topic = Topic::new("cc_fault", CRUISE_CONTROL, 0x8003, PayloadType::Json, None);
+/// Active mode of the adaptive cruise control (set speed or spacing to a lead vehicle).
+pub const ACC_STATUS: Topic = Topic::new(
+    "cc_acc_status",
+    CRUISE_CONTROL,
+    0x8004,
+    PayloadType::Json,
+    None,
+);
+/// Periodic health of the cruise controller: engaged state, speeds, last command and faults.
+pub const HEALTH: Topic = Topic::new("cc_health", CRUISE_CONTROL, 0x8005, PayloadType::Json, None);
+
+/// Status text of the ThreadX board.
+pub const THREADX_STATUS: Topic =
+    Topic::new("threadx_status", THREADX, 0x8001, PayloadType::Text, None);
+
+/// Every topic of the lab.
+pub const ALL_TOPICS: &[Topic] = &[
+    VELOCITY_STATUS,
+    CLOCK_STATUS,
+    LANE_INVASION,
+    COLLISION,
+    OBSTACLE_DETECTION,
+    IMAGE,
+    RADAR,
+    LIDAR,
+    IMU,
+    TARGET_SPEED,
+    ENGAGE,
+    AAOS_STATUS,
+    ACTUATION,
+    GAINS_STATUS,
+    FAULT,
+    ACC_STATUS,
+    HEALTH,
+    THREADX_STATUS,
+];
+
+/// Looks up a topic by its address.
+pub fn lookup(
+    authority: &str,
+    ue_id: u32,
+    ue_version_major: u8,
+    resource_id: u16,
+) -> Option<&'static Topic> {
+    ALL_TOPICS
+        .iter()
+        .find(|topic| topic.is(authority, ue_id, ue_version_major, resource_id))
+}
+
+/// Looks up the topic addressed by `uri`.
+#[cfg(feature = "up-rust")]
+pub fn find(uri: &UUri) -> Option<&'static Topic> {
+    ALL_TOPICS.iter().find(|topic| topic.matches(uri))
+}
+
+/// Looks up a topic by its signal name.
+pub fn by_name(name: &str) -> Option<&'static Topic> {
+    ALL_TOPICS.iter().find(|topic| topic.name == name)
+}
+
+/// A binary of the lab and the topics it uses.
+#[derive(Debug, Clone, Copy)]
+pub struct Component {
+    pub name: &'static str,
+    /// Authority the component's transport is created with.
+    pub authority: &'static str,
+    pub publishes: &'static [Topic],
+    pub subscribes: &'static [Topic],
+}
+
+/// `pid_controller/rust-uprotocol`: the `pid_controller` binary.
+pub const PID_CONTROLLER: Component = Component {
+    name: "pid_controller",
+    authority: CRUISE_CONTROL,
+    publishes: &[ACTUATION, GAINS_STATUS, FAULT, ACC_STATUS, HEALTH],
+    subscribes: &[
+        VELOCITY_STATUS,
+        CLOCK_STATUS,
+        TARGET_SPEED,
+        ENGAGE,
+        OBSTACLE_DETECTION,
+        RADAR,
+    ],
+};
+
+/// `pid_controller/rust-uprotocol`: the `simulator` binary, standing in for the ego vehicle
+/// and the driver.
+pub const SIMULATOR: Component = Component {
+    name: "simulator",
+    authority: EGO_VEHICLE,
+    publishes: &[VELOCITY_STATUS, CLOCK_STATUS, TARGET_SPEED, ENGAGE],
+    subscribes: &[ACTUATION],
+};
+
+/// `ego-vehicle/uprotocol-control`.
+pub const EGO_VEHICLE_CONTROL: Component = Component {
+    name: "ego-vehicle-control",
+    authority: EGO_VEHICLE,
+    publishes: &[VELOCITY_STATUS, CLOCK_STATUS],
+    subscribes: &[ACTUATION, ENGAGE],
+};
+
+/// `ego-vehicle/uprotocol-sensors`.
+pub const EGO_VEHICLE_SENSORS: Component = Component {
+    name: "ego-vehicle-sensors",
+    authority: EGO_VEHICLE,
+    publishes: &[
+        VELOCITY_STATUS,
+        CLOCK_STATUS,
+        LANE_INVASION,
+        COLLISION,
+        OBSTACLE_DETECTION,
+        IMAGE,
+        RADAR,
+        LIDAR,
+        IMU,
+    ],
+    subscribes: &[ACTUATION, ENGAGE],
+};
+
+/// `uprotocol/mqtt`: the `aaos` demo.
+pub const AAOS_DEMO: Component = Component {
+    name: "aaos",
+    authority: AAOS,
+    publishes: &[TARGET_SPEED, ENGAGE, AAOS_STATUS],
+    subscribes: &[VELOCITY_STATUS, THREADX_STATUS],
+};
+
+/// `uprotocol/mqtt`: the `threadx` demo.
+pub const THREADX_DEMO: Component = Component {
+    name: "threadx",
+    authority: THREADX,
+    publishes: &[THREADX_STATUS],
+    subscribes: &[],
+};
+
+/// `uprotocol/zenoh`: the `cruise_control` demo.
+pub const CRUISE_CONTROL_DEMO: Component = Component {
+    name: "cruise_control",
+    authority: CRUISE_CONTROL,
+    publishes: &[ACTUATION],
+    subscribes: &[
+        TARGET_SPEED,
+        ENGAGE,
+        AAOS_STATUS,
+        VELOCITY_STATUS,
+        CLOCK_STATUS,
+        THREADX_STATUS,
+    ],
+};
+
+/// `uprotocol/zenoh`: the `ego_vehicle` demo.
+pub const EGO_VEHICLE_DEMO: Component = Component {
+    name: "ego_vehicle",
+    authority: EGO_VEHICLE,
+    publishes: &[VELOCITY_STATUS, CLOCK_STATUS],
+    subscribes: &[ACTUATION, TARGET_SPEED, THREADX_STATUS],
+};
+
+/// Every component of the lab.
+pub const COMPONENTS: &[Component] = &[
+    PID_CONTROLLER,
+    SIMULATOR,
+    EGO_VEHICLE_CONTROL,
+    EGO_VEHICLE_SENSORS,
+    AAOS_DEMO,
+    THREADX_DEMO,
+    CRUISE_CONTROL_DEMO,
+    EGO_VEHICLE_DEMO,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_topics_do_not_collide() {
+        let mut addresses = HashSet::new();
+        let mut names = HashSet::new();
+        let mut zenoh_keys = HashSet::new();
+        for topic in ALL_TOPICS {
+            assert!(
+                addresses.insert((
+                    topic.authority,
+                    topic.ue_id,
+                    topic.ue_version_major,
+                    topic.resource_id
+                )),
+                "{} collides with another topic at {}",
+                topic.name,
+                topic
+            );
+            assert!(
+                names.insert(topic.name),
+                "Duplicate topic name {}",
+                topic.name
+            );
+            if let Some(key) = topic.zenoh_key {
+                assert!(
+                    zenoh_keys.insert(key),
+                    "Duplicate Zenoh key {} ({})",
+                    key,
+                    topic.name
+                );
+            }
+            // Resource ids from 0x8000 up are topics, below that RPC methods
+            assert!(
+                topic.resource_id >= 0x8000,
+                "{} has a method resource id",
+                topic.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_components_use_declared_topics() {
+        for component in COMPONENTS {
+            for topic in component.publishes.iter().chain(component.subscribes) {
+                assert!(
+                    ALL_TOPICS.contains(topic),
+                    "{} uses {}, which is not in ALL_TOPICS",
+                    component.name,
+                    topic.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_orphan_subscriptions() {
+        for component in COMPONENTS {
+            for topic in component.subscribes {
+                assert!(
+                    COMPONENTS
+                        .iter()
+                        .any(|other| other.publishes.contains(topic)),
+                    "{} subscribes to {} ({}), which nobody publishes",
+                    component.name,
+                    topic.name,
+                    topic
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_topics_are_published_by_their_owner() {
+        for topic in ALL_TOPICS {
+            assert!(
+                COMPONENTS
+                    .iter()
+                    .any(|component| component.authority == topic.authority
+                        && component.publishes.contains(topic)),
+                "No {} component publishes {}",
+                topic.authority,
+                topic.name
+            );
+        }
+    }
+
+    #[cfg(feature = "up-rust")]
+    #[test]
+    fn test_uri_round_trip() {
+        for topic in ALL_TOPICS {
+            let uri = topic.uri();
+            assert_eq!(String::from(&uri), topic.to_string());
+            assert_eq!(find(&uri), Some(topic));
+            assert_eq!(by_name(topic.name), Some(topic));
+        }
+        let unknown = UUri::try_from_parts(EGO_VEHICLE, UE_ID, UE_VERSION_MAJOR, 0x8fff).unwrap();
+        assert_eq!(find(&unknown), None);
+    }
+
+    #[cfg(feature = "up-rust")]
+    #[test]
+    fn test_vehicle_uri() {
+        let uri = VELOCITY_STATUS.vehicle_uri("hero2").unwrap();
+        assert_eq!(String::from(&uri), "//EGOVehicle-hero2/0/2/8001");
+        assert_eq!(find(&uri), None);
+    }
+}
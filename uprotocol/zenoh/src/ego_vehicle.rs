@@ -28,18 +28,27 @@ use std::sync::Arc;
 use std::thread;
 use std::{str::FromStr, time::SystemTime};
 use up_rust::{UListener, UMessage, UMessageBuilder, UPayloadFormat, UStatus, UTransport, UUri};
+use sdv_lab_topics::{self as topics, Component, Topic};
 use up_transport_zenoh::UPTransportZenoh;
 use zenoh::config::{Config, EndPoint};
 
-// publish
-const EGO_AUTH: &str = "EGOVehicle";
-// subscribe
-const CRUISE_AUTH: &str = "CruiseControl";
-const AAOS_AUTH: &str = "AAOS";
-const X_AUTH: &str = "Threadx";
-
-const UEID: u32 = 0;
-const VERSION: u8 = 2;
+// published and subscribed topics, from the SDV lab topic map
+const COMPONENT: Component = topics::EGO_VEHICLE_DEMO;
+// authority of the entity itself
+const EGO_AUTH: &str = COMPONENT.authority;
+
+const UEID: u32 = topics::UE_ID;
+const VERSION: u8 = topics::UE_VERSION_MAJOR;
+
+fn topic_uri(topic: &Topic) -> UUri {
+    UUri::try_from_parts(
+        topic.authority,
+        topic.ue_id,
+        topic.ue_version_major,
+        topic.resource_id,
+    )
+    .expect("Invalid UURI")
+}
 
 struct PublishReceiver;
 
@@ -64,16 +73,8 @@ async fn main() -> Result<(), UStatus> {
     info!("Started ego vehicle");
 
     // --- Lists of pubish and subscribe topics ---
-    let pub_topics = [
-        UUri::try_from_parts(EGO_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI"),
-        UUri::try_from_parts(EGO_AUTH, UEID, VERSION, 0x8002).expect("Invalid UURI"),
-    ];
-
-    let sub_topics = [
-        UUri::try_from_parts(CRUISE_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI"),
-        UUri::try_from_parts(AAOS_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI"),
-        UUri::try_from_parts(X_AUTH, UEID, VERSION, 0x8001).expect("Invalid UURI"),
-    ];
+    let pub_topics: Vec<UUri> = COMPONENT.publishes.iter().map(topic_uri).collect();
+    let sub_topics: Vec<UUri> = COMPONENT.subscribes.iter().map(topic_uri).collect();
     // --- End of Lists of pubish and subscribe topics ---
 
     // --- Zenoh Transport Specific Stuff ---